@@ -1,8 +1,13 @@
-use std::sync::atomic::{AtomicU8, Ordering};
-use std::sync::mpsc::{Receiver, Sender};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::mpsc::Sender;
 use std::sync::{Arc, RwLock};
 
-use crate::translator::Translator;
+use tokio::sync::Semaphore;
+
+use crate::i18n::{tr, tr_args};
+use crate::queue::BoundedQueue;
+use crate::translator::{DetectFilter, RequestPolicy, SourceHint, Translator};
 
 #[derive(Clone, Copy)]
 pub struct SelectionPos {
@@ -12,94 +17,393 @@ pub struct SelectionPos {
     pub up_y: i32,
 }
 
+impl SelectionPos {
+    /// Whether both the press and release points are within `tolerance`
+    /// pixels of `other`'s, per axis.
+    pub fn is_near(&self, other: &SelectionPos, tolerance: i32) -> bool {
+        (self.down_x - other.down_x).abs() <= tolerance
+            && (self.down_y - other.down_y).abs() <= tolerance
+            && (self.up_x - other.up_x).abs() <= tolerance
+            && (self.up_y - other.up_y).abs() <= tolerance
+    }
+}
+
+/// What to do with a translation once it comes back.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RequestKind {
+    /// Show it in the popup near the selection.
+    Popup,
+    /// Paste it over the selection (hotkey-triggered).
+    Replace,
+    /// Tray "Test Translation": report success or the error in a dialog.
+    Test,
+    /// Translate the shown translation back into its source language and
+    /// append it to the popup, to gauge how much meaning drifted.
+    RoundTrip,
+    /// Typed into the tray "Translate Text" window; shown under the field.
+    Input,
+    /// Collect mode: add the selection to the list instead of translating it.
+    /// Never reaches the translation thread.
+    Collect,
+    /// The collected selections, one per line, translated together and shown
+    /// as a list pairing each with its translation.
+    Batch,
+    /// `hover_translate`: read the word under the pointer, then handled as
+    /// `Popup`. Never reaches the translation thread.
+    Hover,
+}
+
+/// How `grab_selection` tells a fresh copy from what was already on the clipboard.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ClipboardStrategy {
+    /// Empty the clipboard before copying, put the previous text back after.
+    ClearAndRestore,
+    /// Never clear; a copy happened if the clipboard's change counter moved.
+    ChangeCount,
+    /// Never clear; a copy happened if the text differs from a snapshot.
+    NoClear,
+}
+
+impl ClipboardStrategy {
+    /// Parse the `clipboard_strategy` config value.
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "clear_and_restore" => Some(Self::ClearAndRestore),
+            "changecount" => Some(Self::ChangeCount),
+            "no_clear" => Some(Self::NoClear),
+            _ => None,
+        }
+    }
+}
+
 pub struct TranslationRequest {
     pub text: String,
     pub pos: SelectionPos,
+    pub kind: RequestKind,
+    /// `(source, target)` overriding the configured languages.
+    pub langs: Option<(String, String)>,
+    /// Target overriding the configured one (an inline `>>code` marker); the
+    /// source is resolved as usual.
+    pub target: Option<String>,
 }
 
+#[derive(Clone)]
 pub struct TranslationResult {
     pub original: String,
     pub translated: String,
     pub pos: SelectionPos,
+    pub kind: RequestKind,
+    /// Language pair actually used (source is the detected language for "auto").
+    /// Empty when the translation failed.
+    pub source: String,
+    pub target: String,
+    /// `translated` holds an error message rather than a translation.
+    pub failed: bool,
+    /// Translation into `secondary_target_lang`, shown below the primary one.
+    pub secondary: Option<String>,
+    /// Every backend's translation when `compare_backends` is set, the main
+    /// backend first. Failed backends carry their error message.
+    pub comparisons: Vec<Comparison>,
+    /// Order the request was taken from the queue. Requests run concurrently,
+    /// so results can arrive out of order.
+    pub seq: u64,
+    /// Dismiss generation the request was taken under; a dismiss since then
+    /// makes the result stale.
+    pub generation: u64,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct Comparison {
+    pub backend: String,
+    pub text: String,
+}
+
+/// Backend settings for the translation thread, resolved from `Config` at startup.
+pub struct TranslationSettings {
+    pub api_url: String,
+    pub api_key: Option<String>,
+    /// The `headers` table, for `api_url` only.
+    pub headers: std::collections::BTreeMap<String, String>,
+    pub source_lang: String,
+    pub source_hint: Option<SourceHint>,
+    pub detect_filter: Option<DetectFilter>,
+    pub target_rules: HashMap<String, String>,
+    pub secondary_target_lang: Option<String>,
+    pub compare_backends: Vec<crate::config::CompareBackend>,
+    pub max_concurrent_requests: usize,
+    /// Cache the main backend's translations per sentence (`incremental_translation`).
+    pub incremental: bool,
+    /// Timeout and retries for `api_url`, and the defaults for compare backends.
+    pub request_policy: RequestPolicy,
 }
 
 pub fn spawn_translation_thread(
-    text_rx: Receiver<TranslationRequest>,
+    text_queue: BoundedQueue<TranslationRequest>,
     result_tx: Sender<TranslationResult>,
-    api_url: String,
-    api_key: Option<String>,
-    source_lang: String,
+    settings: TranslationSettings,
     target_lang: Arc<RwLock<String>>,
     server_status: Arc<AtomicU8>,
+    dismiss_generation: Arc<AtomicU64>,
 ) -> std::thread::JoinHandle<()> {
     std::thread::spawn(move || {
-        let rt = tokio::runtime::Builder::new_current_thread()
+        let TranslationSettings {
+            api_url,
+            api_key,
+            headers,
+            source_lang,
+            source_hint,
+            detect_filter,
+            target_rules,
+            secondary_target_lang,
+            compare_backends,
+            max_concurrent_requests,
+            incremental,
+            request_policy,
+        } = settings;
+        let max_concurrent = max_concurrent_requests.max(1);
+        // How long the popup waits for compare backends, however they retry
+        let compare_timeout = request_policy.timeout;
+        let is_local = crate::server::is_loopback_url(&api_url);
+        let current_target = target_lang.clone();
+        let primary_label = crate::server::backend_label(&api_url);
+        let compare: Arc<Vec<(String, Arc<Translator>)>> = Arc::new(
+            compare_backends
+                .into_iter()
+                .filter_map(|b| {
+                    let name = if b.name.trim().is_empty() {
+                        crate::server::backend_label(&b.api_url)
+                    } else {
+                        b.name.trim().to_string()
+                    };
+                    let policy = request_policy.for_backend(&b);
+                    match Translator::new(
+                        b.api_url,
+                        b.api_key.filter(|k| !k.is_empty()),
+                        source_lang.clone(),
+                        source_hint.clone(),
+                        detect_filter.clone(),
+                        target_rules.clone(),
+                        target_lang.clone(),
+                    ) {
+                        Ok(translator) => Some((name, Arc::new(translator.with_request_policy(policy)))),
+                        Err(e) => {
+                            tracing::warn!("Skipping compare backend \"{}\": {}", name, e);
+                            None
+                        }
+                    }
+                })
+                .collect(),
+        );
+        let translator = Translator::new(
+            api_url,
+            api_key,
+            source_lang,
+            source_hint,
+            detect_filter,
+            target_rules,
+            target_lang,
+        )
+        .and_then(|t| t.with_headers(&headers))
+        .map(|t| t.with_request_policy(request_policy));
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(max_concurrent.min(4))
             .enable_all()
             .build()
-            .expect("Failed to build tokio runtime");
+            .map_err(anyhow::Error::from);
+        let (mut translator, rt) = match translator.and_then(|t| rt.map(|rt| (t, rt))) {
+            Ok(ready) => ready,
+            Err(e) => {
+                tracing::error!("Failed to initialize translator: {:#}", e);
+                answer_with_error(
+                    &text_queue,
+                    &result_tx,
+                    &dismiss_generation,
+                    &tr_args("error.translator_init", &[("error", &e)]),
+                );
+                return;
+            }
+        };
+        if incremental {
+            translator = translator.with_sentence_cache();
+        }
 
         rt.block_on(async move {
-            let is_local = api_url.contains("localhost") || api_url.contains("127.0.0.1");
-            let translator = Translator::new(api_url, api_key, source_lang, target_lang);
-
-            while let Ok(req) = text_rx.recv() {
-                match translator.translate(&req.text).await {
-                    Ok(translated) => {
-                        tracing::info!("Translation complete");
-                        let _ = result_tx.send(TranslationResult {
-                            original: req.text,
-                            translated,
-                            pos: req.pos,
+            let translator = Arc::new(translator);
+            // Requests run in parallel, but never more than this many hit the backend at once
+            let permits = Arc::new(Semaphore::new(max_concurrent));
+
+            for seq in 0.. {
+                let req = text_queue.pop();
+                let generation = dismiss_generation.load(Ordering::Relaxed);
+                let Ok(permit) = permits.clone().acquire_owned().await else {
+                    break;
+                };
+                let translator = translator.clone();
+                let result_tx = result_tx.clone();
+                let server_status = server_status.clone();
+                let primary_label = primary_label.clone();
+                let overridden = req.langs.is_some() || req.target.is_some();
+                let compare_with = if req.kind == RequestKind::Popup && !overridden {
+                    compare.clone()
+                } else {
+                    Arc::new(Vec::new())
+                };
+                // Only popups from a plain selection get the second target
+                let secondary_target = secondary_target_lang.clone().filter(|lang| {
+                    req.kind == RequestKind::Popup
+                        && req.langs.is_none()
+                        && req.target.as_ref().unwrap_or(&*current_target.read().unwrap()) != lang
+                });
+
+                tokio::spawn(async move {
+                    let _permit = permit;
+                    // Started first so they run alongside the main request
+                    let compare_deadline = tokio::time::Instant::now() + compare_timeout;
+                    let compare_tasks: Vec<_> = compare_with
+                        .iter()
+                        .map(|(name, translator)| {
+                            let (backend, translator, text) = (name.clone(), translator.clone(), req.text.clone());
+                            let task = tokio::spawn(async move {
+                                let text = match translator.translate(&text).await {
+                                    Ok(t) => t.text,
+                                    Err(e) => format!("⚠️ {}", e),
+                                };
+                                Comparison { backend, text }
+                            });
+                            (name.clone(), task)
+                        })
+                        .collect();
+                    let primary = async {
+                        match (&req.langs, &req.target) {
+                            (Some((source, target)), _) => translator.translate_with(&req.text, source, target).await,
+                            (None, Some(target)) => translator.translate_to(&req.text, target).await,
+                            (None, None) => translator.translate(&req.text).await,
+                        }
+                    };
+                    let secondary = async {
+                        match &secondary_target {
+                            Some(target) => Some(translator.translate_to(&req.text, target).await),
+                            None => None,
+                        }
+                    };
+                    let (outcome, secondary) = tokio::join!(primary, secondary);
+                    let secondary = secondary.and_then(|r| {
+                        r.map_err(|e| tracing::warn!("Secondary translation failed: {}", e)).ok()
+                    });
+                    let (translated, source, target, failed) = match outcome {
+                        Ok(t) => {
+                            tracing::info!("Translation complete");
+                            (t.text, t.source, t.target, false)
+                        }
+                        Err(e) => {
+                            tracing::error!("Translation failed: {}", e);
+                            let msg = error_message(&e, server_status.load(Ordering::Relaxed), is_local);
+                            (msg, String::new(), String::new(), true)
+                        }
+                    };
+
+                    // Every backend is listed, the main one with its error if it failed
+                    let mut comparisons = Vec::new();
+                    if !compare_tasks.is_empty() {
+                        comparisons.push(Comparison {
+                            backend: primary_label,
+                            text: translated.clone(),
                         });
-                    }
-                    Err(e) => {
-                        tracing::error!("Translation failed: {}", e);
-
-                        let status = server_status.load(Ordering::Relaxed);
-                        let error_str = e.to_string();
-
-                        let error_msg = if status == crate::server::SERVER_FAILED {
-                            "⚠️ LibreTranslate failed to start\n\
-                             Check libretranslate.log in app data folder"
-                                .to_string()
-                        } else if is_local {
-                            let is_conn_error = error_str.contains("Connection refused")
-                                || error_str.contains("connect")
-                                || error_str.contains("timed out")
-                                || error_str.contains("timeout")
-                                || error_str.contains("500")
-                                || error_str.contains("503")
-                                || error_str.contains("model");
-
-                            if is_conn_error
-                                && status == crate::server::SERVER_STARTING
-                            {
-                                "⏳ LibreTranslate is loading...\n\
-                                 First launch may take a few minutes\n\
-                                 to download language models"
-                                    .to_string()
-                            } else if is_conn_error {
-                                "⚠️ Cannot connect to LibreTranslate\n\
-                                 Server may have crashed.\n\
-                                 Check libretranslate.log for details"
-                                    .to_string()
-                            } else {
-                                "⚠️ Translation Unavailable\n\
-                                 Check if app installed correctly"
-                                    .to_string()
+                        for (backend, mut task) in compare_tasks {
+                            match tokio::time::timeout_at(compare_deadline, &mut task).await {
+                                Ok(Ok(comparison)) => comparisons.push(comparison),
+                                Ok(Err(e)) => tracing::warn!("Compare backend \"{}\" failed: {}", backend, e),
+                                Err(_) => {
+                                    tracing::warn!("Compare backend \"{}\" timed out", backend);
+                                    task.abort();
+                                    let text =
+                                        tr_args("error.compare_timeout", &[("secs", &compare_timeout.as_secs())]);
+                                    comparisons.push(Comparison { backend, text });
+                                }
                             }
-                        } else {
-                            format!("⚠️ API Error:\n{}", e)
-                        };
-
-                        let _ = result_tx.send(TranslationResult {
-                            original: req.text,
-                            translated: error_msg,
-                            pos: req.pos,
-                        });
+                        }
                     }
-                }
+
+                    let _ = result_tx.send(TranslationResult {
+                        original: req.text,
+                        translated,
+                        pos: req.pos,
+                        kind: req.kind,
+                        source,
+                        target,
+                        failed,
+                        secondary: secondary.filter(|_| !failed).map(|t| t.text),
+                        comparisons,
+                        seq,
+                        generation,
+                    });
+                });
             }
         });
     })
 }
+
+/// The translator couldn't be set up: answer every request with `message`,
+/// so the popup says why nothing translates instead of the thread dying.
+fn answer_with_error(
+    text_queue: &BoundedQueue<TranslationRequest>,
+    result_tx: &Sender<TranslationResult>,
+    dismiss_generation: &AtomicU64,
+    message: &str,
+) {
+    for seq in 0.. {
+        let req = text_queue.pop();
+        let result = TranslationResult {
+            original: req.text,
+            translated: message.to_string(),
+            pos: req.pos,
+            kind: req.kind,
+            source: String::new(),
+            target: String::new(),
+            failed: true,
+            secondary: None,
+            comparisons: Vec::new(),
+            seq,
+            generation: dismiss_generation.load(Ordering::Relaxed),
+        };
+        if result_tx.send(result).is_err() {
+            break;
+        }
+    }
+}
+
+/// Map a translation failure to the message shown in the popup.
+fn error_message(e: &anyhow::Error, status: u8, is_local: bool) -> String {
+    let error_str = e.to_string();
+
+    if status == crate::server::SERVER_PORT_CONFLICT {
+        tr("error.port_in_use").to_string()
+    } else if status == crate::server::SERVER_FAILED {
+        tr("error.server_failed").to_string()
+    } else if let Some(limited) = e.downcast_ref::<crate::translator::RateLimited>() {
+        match limited.retry_after_secs {
+            Some(secs) => tr_args("error.rate_limited_secs", &[("secs", &secs)]),
+            None => tr("error.rate_limited").to_string(),
+        }
+    } else if e.is::<crate::translator::EmptyTranslation>() {
+        tr("error.empty").to_string()
+    } else if is_local {
+        let is_conn_error = error_str.contains("Connection refused")
+            || error_str.contains("connect")
+            || error_str.contains("timed out")
+            || error_str.contains("timeout")
+            || error_str.contains("500")
+            || error_str.contains("503")
+            || error_str.contains("model");
+
+        if is_conn_error && status == crate::server::SERVER_STARTING {
+            tr("error.loading").to_string()
+        } else if is_conn_error {
+            tr("error.cannot_connect").to_string()
+        } else {
+            tr("error.unavailable").to_string()
+        }
+    } else {
+        tr_args("error.api", &[("error", &e)])
+    }
+}
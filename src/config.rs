@@ -1,22 +1,135 @@
-use anyhow::Result;
-use serde::Deserialize;
-use std::path::PathBuf;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Deserialize)]
+/// Files in the app directory carried by "Export Settings".
+const SETTINGS_FILES: &[&str] = &["config.toml"];
+
+/// A settings export: the contents of `SETTINGS_FILES`, keyed by file name.
+#[derive(Deserialize, Serialize)]
+struct SettingsBundle {
+    app_version: String,
+    files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(default)]
 pub struct Config {
     pub api_url: String,
+    /// Server root; when set, `api_url` becomes its `/translate` endpoint.
+    pub api_base_url: Option<String>,
     pub api_key: Option<String>,
     pub target_lang: String,
+    pub secondary_target_lang: Option<String>,
+    pub retranslate_on_lang_change: bool,
     pub source_lang: String,
     pub poll_interval_ms: u64,
     pub popup_duration_secs: u64,
     pub max_text_length: usize,
+    pub dedup_window_ms: u64,
+    pub dedup_tolerance_px: u32,
     pub python_path: Option<String>,
     pub api_port: u16,
     pub load_languages: String,
     pub auto_update: bool,
     pub start_local_server: bool,
+    pub server_startup_timeout_secs: u64,
+    pub restart_on_crash: bool,
+    pub trigger_button: String,
+    pub suppress_modifier: String,
+    pub selection_threshold_x: i32,
+    pub selection_threshold_y: i32,
+    pub translate_on_double_click: bool,
+    pub hover_translate: bool,
+    pub hover_dwell_ms: u64,
+    pub min_drag_hold_ms: u64,
+    pub debug_overlay: bool,
+    pub active_monitors: String,
+    pub monitoring_enabled: bool,
+    pub toast_on_toggle: bool,
+    pub max_concurrent_requests: usize,
+    pub request_timeout_secs: u64,
+    pub max_retries: u32,
+    pub retry_backoff_ms: u64,
+    pub request_queue_capacity: usize,
+    pub github_token: Option<String>,
+    pub skip_secure_input: bool,
+    pub auto_pause_fullscreen: bool,
+    pub accessibility_selection: bool,
+    pub accessibility_selection_exclude: String,
+    pub clipboard_strategy: String,
+    pub preserve_full_clipboard: bool,
+    pub remote_session: String,
+    pub popup_fade_in_ms: u64,
+    pub popup_fade_out_ms: u64,
+    pub popup_fade_out_deselect_ms: u64,
+    pub popup_fade: bool,
+    pub popup_slide: bool,
+    pub popup_material: String,
+    pub popup_renderer: String,
+    pub popup_accent: Option<String>,
+    pub popup_text_rendering: String,
+    pub short_translation_chars: usize,
+    pub short_translation_min_secs: u64,
+    pub popup_sticky_size: bool,
+    pub popup_countdown_bar: bool,
+    pub popup_text_align: String,
+    pub popup_display_change: String,
+    pub popup_placement: String,
+    pub popup_cursor_offset_x: i32,
+    pub popup_cursor_offset_y: i32,
+    pub popup_max_lifetime_secs: u64,
+    pub popup_interaction_extend_ms: u64,
+    pub max_popups: usize,
+    pub popup_min_width: u32,
+    pub popup_word_min_width: u32,
+    pub popup_error_min_width: u32,
+    pub popup_original_on_hover: bool,
+    pub display_mode: String,
+    pub popup_font_by_script: String,
+    pub ui_lang: String,
+    pub replace_hotkey: String,
+    pub panic_hotkey: String,
+    pub round_trip_hotkey: String,
+    pub paragraph_hotkey: String,
+    pub collect_hotkey: String,
+    pub history_prev_hotkey: String,
+    pub history_next_hotkey: String,
+    pub history_size: usize,
+    pub event_socket: Option<String>,
+    pub source_lang_hint: Option<String>,
+    pub source_hint_max_chars: usize,
+    pub detect_allowlist: String,
+    pub detect_fallback_lang: String,
+    pub source_target_rules: String,
+    pub require_foreign_script: bool,
+    pub native_scripts: String,
+    pub trigger_scripts: String,
+    pub join_lines: String,
+    pub inline_lang_markers: bool,
+    pub incremental_translation: bool,
+    pub min_words: usize,
+    pub min_cjk_chars: usize,
+    pub popup_only_if_changed: bool,
+    pub compare_backends: Vec<CompareBackend>,
+    /// Extra HTTP headers sent with every request to `api_url`.
+    pub headers: BTreeMap<String, String>,
+}
+
+/// An extra LibreTranslate-compatible server queried alongside `api_url`,
+/// so their translations can be compared side by side in the popup.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CompareBackend {
+    /// Label shown in the popup; defaults to the URL's host.
+    #[serde(default)]
+    pub name: String,
+    pub api_url: String,
+    pub api_key: Option<String>,
+    /// Override the top-level request settings for this backend.
+    pub request_timeout_secs: Option<u64>,
+    pub max_retries: Option<u32>,
+    pub retry_backoff_ms: Option<u64>,
 }
 
 impl Default for Config {
@@ -28,18 +141,106 @@ impl Default for Config {
         let default_port: u16 = 5000;
 
         Self {
-            api_url: format!("http://127.0.0.1:{}/translate", default_port),
+            api_url: crate::server::endpoint_url(
+                &crate::server::local_base_url(default_port),
+                "translate",
+            ),
+            api_base_url: None,
             api_key: None,
             target_lang: "id".into(),
+            secondary_target_lang: None,
+            retranslate_on_lang_change: false,
             source_lang: "auto".into(),
             poll_interval_ms: 100,
             popup_duration_secs: 5,
             max_text_length: 5000,
+            dedup_window_ms: 0,
+            dedup_tolerance_px: 8,
             python_path: None,
             api_port: default_port,
             load_languages: "en,ar,zh,fr,de,hi,id,it,ja,ko,fa,pl,pt,ru,es,tr,uk,vi".into(),
             auto_update: true,
             start_local_server: true,
+            server_startup_timeout_secs: 180,
+            restart_on_crash: false,
+            trigger_button: "left".into(),
+            suppress_modifier: String::new(),
+            selection_threshold_x: 5,
+            selection_threshold_y: 5,
+            translate_on_double_click: true,
+            hover_translate: false,
+            hover_dwell_ms: 800,
+            min_drag_hold_ms: 0,
+            debug_overlay: false,
+            active_monitors: String::new(),
+            monitoring_enabled: true,
+            toast_on_toggle: false,
+            max_concurrent_requests: 4,
+            request_timeout_secs: 10,
+            max_retries: 0,
+            retry_backoff_ms: 500,
+            request_queue_capacity: 8,
+            github_token: None,
+            skip_secure_input: true,
+            auto_pause_fullscreen: false,
+            accessibility_selection: true,
+            accessibility_selection_exclude: String::new(),
+            clipboard_strategy: "clear_and_restore".into(),
+            preserve_full_clipboard: false,
+            remote_session: "auto".into(),
+            popup_fade_in_ms: 180,
+            popup_fade_out_ms: 220,
+            popup_fade_out_deselect_ms: 120,
+            popup_fade: true,
+            popup_slide: true,
+            popup_material: "hud".into(),
+            popup_renderer: "styled".into(),
+            popup_accent: None,
+            popup_text_rendering: "cleartype".into(),
+            short_translation_chars: 3,
+            short_translation_min_secs: 4,
+            popup_sticky_size: false,
+            popup_countdown_bar: false,
+            popup_text_align: "auto".into(),
+            popup_display_change: "dismiss".into(),
+            popup_placement: "selection".into(),
+            popup_cursor_offset_x: 0,
+            popup_cursor_offset_y: 20,
+            popup_max_lifetime_secs: 0,
+            popup_interaction_extend_ms: 8000,
+            max_popups: 1,
+            popup_min_width: 200,
+            popup_word_min_width: 260,
+            popup_error_min_width: 320,
+            popup_original_on_hover: false,
+            display_mode: "popup".into(),
+            popup_font_by_script: String::new(),
+            ui_lang: "en".into(),
+            replace_hotkey: String::new(),
+            panic_hotkey: String::new(),
+            round_trip_hotkey: String::new(),
+            paragraph_hotkey: String::new(),
+            collect_hotkey: String::new(),
+            history_prev_hotkey: String::new(),
+            history_next_hotkey: String::new(),
+            history_size: 10,
+            event_socket: None,
+            source_lang_hint: None,
+            source_hint_max_chars: 12,
+            detect_allowlist: String::new(),
+            detect_fallback_lang: "en".into(),
+            source_target_rules: String::new(),
+            require_foreign_script: false,
+            native_scripts: String::new(),
+            trigger_scripts: String::new(),
+            join_lines: "none".into(),
+            inline_lang_markers: false,
+            incremental_translation: false,
+            min_words: 1,
+            min_cjk_chars: 1,
+            popup_only_if_changed: false,
+            compare_backends: Vec::new(),
+            headers: BTreeMap::new(),
         }
     }
 }
@@ -70,7 +271,34 @@ impl Config {
         let path = Self::config_path();
         if path.exists() {
             let contents = std::fs::read_to_string(&path)?;
-            let mut config: Config = toml::from_str(&contents)?;
+            let mut config: Config = match toml::from_str(&contents) {
+                Ok(config) => config,
+                Err(e) => {
+                    // Keep what the user wrote in case the file gets rewritten
+                    // (e.g. by changing the language from the tray) before it's
+                    // fixed. Not `.bak`, which belongs to import_settings.
+                    let backup = path.with_extension("toml.invalid");
+                    // Each load of the same broken file would copy it again
+                    let unchanged = std::fs::read(&backup).is_ok_and(|old| old == contents.as_bytes());
+                    let saved = if unchanged {
+                        Ok(())
+                    } else {
+                        std::fs::copy(&path, &backup).map(|_| ())
+                    };
+                    let context = match saved {
+                        Ok(()) => format!(
+                            "Invalid config file {} (a copy was saved as {})",
+                            path.display(),
+                            backup.display()
+                        ),
+                        Err(copy_err) => {
+                            tracing::warn!("Failed to back up {}: {}", path.display(), copy_err);
+                            format!("Invalid config file {}", path.display())
+                        }
+                    };
+                    return Err(e).context(context);
+                }
+            };
             tracing::info!("Loaded config from {}", path.display());
 
             // Migrate old default load_languages to include all supported languages
@@ -100,13 +328,22 @@ impl Config {
 
 # LibreTranslate API endpoint (default: local server, no internet needed)
 # api_url = \"http://127.0.0.1:{}/translate\"
+# Or give just the server address; /translate, /languages etc. are added
+# api_base_url = \"http://127.0.0.1:{}\"
 
 # API key - NOT NEEDED for local usage! Only for remote APIs.
 # api_key = \"\"
 
-# Target language for translations (ISO 639 code)
+# Target language for translations (ISO 639 code), or \"system\" for the
+# OS language
 # target_lang = \"id\"
 
+# Second language shown as a smaller line below the popup translation
+# secondary_target_lang = \"en\"
+
+# Re-translate the popup on screen when the target language is changed from the tray
+# retranslate_on_lang_change = false
+
 # Source language (\"auto\" for auto-detection)
 # source_lang = \"auto\"
 
@@ -116,6 +353,12 @@ impl Config {
 # Maximum text length to translate (characters)
 # max_text_length = 5000
 
+# Selecting the same text again is skipped. With a window (ms) set, it's only
+# skipped when re-selected within dedup_tolerance_px of the last selection
+# inside that window (0 = always skip the same text)
+# dedup_window_ms = 0
+# dedup_tolerance_px = 8
+
 # LibreTranslate server port (macOS defaults to 5001 to avoid AirPlay conflict)
 # api_port = {}
 
@@ -131,7 +374,261 @@ impl Config {
 
 # Start local LibreTranslate server (disable if using remote API)
 # start_local_server = true
-", defaults.api_port, defaults.api_port);
+
+# How long the local server may take to come up before it's reported as failed
+# (10-3600). Selections show \"loading\" until then
+# server_startup_timeout_secs = 180
+
+# Relaunch automatically after a crash (gives up after repeated crashes)
+# restart_on_crash = false
+
+# Mouse button that triggers selection detection (\"left\", \"middle\", \"right\")
+# trigger_button = \"left\"
+
+# Hold this modifier while selecting to not translate that selection
+# (\"shift\", \"ctrl\", \"alt\", \"cmd\"; empty = off)
+# suppress_modifier = \"\"
+
+# Pointer movement (pixels) beyond which a press-and-release counts as a drag
+# selection, per axis
+# selection_threshold_x = 5
+# selection_threshold_y = 5
+
+# Double-clicking a word translates it (false = only drag-selections do)
+# translate_on_double_click = true
+
+# Translate the word under the pointer after it rests there for
+# hover_dwell_ms (200-5000), without clicking
+# hover_translate = false
+# hover_dwell_ms = 800
+
+# Ignore drags released sooner than this (quick drag-scrolls), 0 = off
+# min_drag_hold_ms = 0
+
+# Briefly outline each detected selection on screen (for troubleshooting)
+# debug_overlay = false
+
+# Only translate selections on these monitors, by number (e.g. \"1,3\").
+# Empty = all monitors
+# active_monitors = \"\"
+
+# Whether monitoring starts active (updated automatically from the tray toggle)
+# monitoring_enabled = true
+
+# Briefly show \"Monitoring paused\" / \"Monitoring resumed\" mid-screen when
+# monitoring is toggled
+# toast_on_toggle = false
+
+# Maximum translation requests sent to the backend at the same time
+# max_concurrent_requests = 4
+
+# Per-request timeout and retries (with doubling backoff) for api_url;
+# compare backends use these unless they set their own
+# request_timeout_secs = 10
+# max_retries = 0
+# retry_backoff_ms = 500
+
+# Pending selections kept while the backend is busy; the oldest is dropped when full
+# request_queue_capacity = 8
+
+# GitHub personal access token for update checks (raises the API rate limit)
+# github_token = \"\"
+
+# Skip copying while a password field / secure input is focused
+# skip_secure_input = true
+
+# Ignore selections while the focused app is full-screen (games, videos)
+# auto_pause_fullscreen = false
+
+# Read the selection through UI Automation / Accessibility before falling
+# back to simulating Ctrl/Cmd+C (avoids touching the clipboard)
+# accessibility_selection = true
+
+# Apps (comma-separated) that always use the copy fallback, e.g. \"excel,Microsoft Word\"
+# accessibility_selection_exclude = \"\"
+
+# How the copy fallback tells a fresh copy from old clipboard content:
+# \"clear_and_restore\", \"changecount\" or \"no_clear\"
+# clipboard_strategy = \"clear_and_restore\"
+
+# Put back everything on the clipboard after the copy fallback (images,
+# files, rich text), whichever strategy is set
+# preserve_full_clipboard = false
+
+# Wait longer for the copy fallback over RDP/VNC, where the clipboard syncs
+# slowly: \"auto\" (detect a Remote Desktop session, Windows only), \"on\", \"off\"
+# remote_session = \"auto\"
+
+# Popup fade durations in milliseconds (0-2000, 0 = instant)
+# popup_fade_in_ms = 180
+# popup_fade_out_ms = 220
+# popup_fade_out_deselect_ms = 120
+
+# Popup show/hide effects, each on its own (the durations above apply to both)
+# popup_fade = true
+# popup_slide = true
+
+# macOS popup blur material: hud, popover, menu, sidebar, sheet, tooltip,
+# titlebar, window, under_window
+# popup_material = \"hud\"
+
+# Popup drawing: \"styled\" (translucent, rounded, animated) or \"plain\"
+# (opaque, square, no animation) for RDP, VNC and minimal window managers
+# popup_renderer = \"styled\"
+
+# Windows popup accent color blended into the card (#RRGGBB)
+# popup_accent = \"#3B82F6\"
+
+# Windows popup text smoothing: \"cleartype\", \"antialiased\" (grayscale)
+# or \"default\" (the system setting)
+# popup_text_rendering = \"cleartype\"
+
+# Translations of at most this many characters get a wider popup and stay up
+# for at least short_translation_min_secs (0 = treat like any other text)
+# short_translation_chars = 3
+# short_translation_min_secs = 4
+
+# Keep the popup size steady across quick, similar-length translations
+# popup_sticky_size = false
+
+# Show a thin bar in the popup that shrinks until it auto-hides
+# popup_countdown_bar = false
+
+# Popup text alignment: start, center, end, or auto (end for right-to-left
+# target languages, start otherwise)
+# popup_text_align = \"auto\"
+
+# When monitors change under a visible popup: \"dismiss\" it if its monitor is
+# gone (otherwise keep it on-screen), or \"move\" it to the nearest monitor
+# popup_display_change = \"dismiss\"
+
+# Popup position: \"selection\" (above/below the selected text) or \"cursor\"
+# (below the pointer, shifted by the offsets; positive = right/down)
+# popup_placement = \"selection\"
+# popup_cursor_offset_x = 0
+# popup_cursor_offset_y = 20
+
+# Close the popup this many seconds after it appears, even while scrolling
+# or dragging it (0 = no limit)
+# popup_max_lifetime_secs = 0
+
+# Time left before the popup hides after you hover, scroll or move it, in
+# milliseconds (1000-60000)
+# popup_interaction_extend_ms = 8000
+
+# Keep up to this many popups on screen, stacked; the oldest goes first (1-5)
+# max_popups = 1
+
+# Narrowest the popup gets, in pixels (100-640): for most translations,
+# for short ones (see short_translation_chars) and for error messages
+# popup_min_width = 200
+# popup_word_min_width = 260
+# popup_error_min_width = 320
+
+# Windows: show the original text under the translation once the pointer
+# moves over the popup
+# popup_original_on_hover = false
+
+# Where translations appear: \"popup\" (next to the selection, auto-hiding)
+# or \"sidebar\" (a panel docked to the right edge that keeps every
+# translation, newest at top)
+# display_mode = \"popup\"
+
+# Popup font per script, as script:font pairs. Scripts: latin, greek,
+# cyrillic, arabic, hebrew, devanagari, thai, han, kana, hangul.
+# Example: \"han:Microsoft YaHei UI,hangul:Malgun Gothic\"
+# popup_font_by_script = \"\"
+
+# Language of the app's own menus and messages: en, id
+# ui_lang = \"en\"
+
+# Hotkey that translates the selection and pastes it over the original
+# (empty = disabled). Example: \"CmdOrCtrl+Shift+R\"
+# replace_hotkey = \"\"
+
+# Hotkey that hides the popup instantly, without fading (empty = disabled)
+# panic_hotkey = \"\"
+
+# Hotkey that translates the popup's text back into the source language and
+# appends it, to check for drift (empty = disabled)
+# round_trip_hotkey = \"\"
+
+# Hotkey that selects the paragraph under the pointer (by triple-clicking it)
+# and translates it (empty = disabled)
+# paragraph_hotkey = \"\"
+
+# Hotkey that starts collecting selections into a list, and on the next press
+# translates them all together (empty = disabled)
+# collect_hotkey = \"\"
+
+# Hotkeys that page back and forth through recent translations in the popup
+# (empty = disabled), and how many translations are kept
+# history_prev_hotkey = \"\"
+# history_next_hotkey = \"\"
+# history_size = 10
+
+# Stream each translation as a JSON line to clients of this Unix socket
+# (macOS) or named pipe (Windows). Empty = off.
+# event_socket = \"/tmp/screen-translate.sock\"
+
+# With source_lang = \"auto\", treat selections of at most
+# source_hint_max_chars characters as this language instead of auto-detecting
+# source_lang_hint = \"ja\"
+# source_hint_max_chars = 12
+
+# With source_lang = \"auto\", languages detection may report (comma-separated,
+# empty = any). Other detections are retranslated from detect_fallback_lang.
+# detect_allowlist = \"en,ja,de\"
+# detect_fallback_lang = \"en\"
+
+# Per-source target languages as comma-separated source:target pairs; other
+# sources use target_lang
+# source_target_rules = \"ja:id,es:en\"
+
+# Only translate selections containing letters outside native_scripts
+# (comma-separated: latin, greek, cyrillic, arabic, hebrew, devanagari, thai,
+# han, kana, hangul). Empty native_scripts = the target language's script.
+# require_foreign_script = false
+# native_scripts = \"\"
+
+# Only translate selections containing letters in one of these scripts
+# (comma-separated, same names as native_scripts). Empty = any script.
+# trigger_scripts = \"\"
+
+# Line breaks in selections: \"none\" keeps them, \"smart\" joins lines wrapped
+# mid-sentence (PDFs) but keeps paragraphs, \"all\" joins everything
+# join_lines = \"none\"
+
+# Start a selection with >>code (e.g. \">>ja hello\") to translate just that
+# selection into another language
+# inline_lang_markers = false
+
+# Translate sentence by sentence and reuse sentences translated before, so a
+# growing selection only requests what changed
+# incremental_translation = false
+
+# Skip selections with fewer words than this (Chinese/Japanese text is
+# measured in characters against min_cjk_chars instead)
+# min_words = 1
+# min_cjk_chars = 1
+
+# Show no popup when the translation is just the selected text again (already
+# in the target language, names, codes)
+# popup_only_if_changed = false
+
+# Extra HTTP headers for api_url, e.g. for an API gateway. Keep this section
+# at the end of the file.
+# [headers]
+# \"X-Api-Key\" = \"\"
+
+# Also query these servers and show every backend's translation in the popup,
+# labeled. Keep this section at the end of the file.
+# [[compare_backends]]
+# name = \"public\"
+# api_url = \"https://libretranslate.com/translate\"
+# api_key = \"\"
+# request_timeout_secs = 20
+", defaults.api_port, defaults.api_port, defaults.api_port);
                 if let Err(e) = std::fs::write(&path, default_config) {
                     tracing::warn!("Failed to write default config: {}", e);
                 } else {
@@ -142,12 +639,222 @@ impl Config {
         }
     }
 
+    /// Longest accepted popup fade; anything slower feels broken rather than smooth.
+    const MAX_FADE_MS: u64 = 2000;
+
+    /// Accepted `popup_interaction_extend_ms` range.
+    const INTERACTION_EXTEND_RANGE: std::ops::RangeInclusive<u64> = 1000..=60000;
+
+    /// Accepted `hover_dwell_ms` range; shorter fires while just moving past.
+    const HOVER_DWELL_RANGE: std::ops::RangeInclusive<u64> = 200..=5000;
+
+    /// Accepted `server_startup_timeout_secs` range.
+    const STARTUP_TIMEOUT_RANGE: std::ops::RangeInclusive<u64> = 10..=3600;
+
+    /// Accepted `max_popups` range; more than a handful just buries the screen.
+    const MAX_POPUPS_RANGE: std::ops::RangeInclusive<usize> = 1..=5;
+
+    /// Accepted popup minimum width range; the top is the popup's maximum width.
+    const POPUP_MIN_WIDTH_RANGE: std::ops::RangeInclusive<u32> = 100..=640;
+
+    /// The resolved settings as a TOML table, for comparing two configs.
+    pub fn settings_table(&self) -> toml::Table {
+        toml::Table::try_from(self).unwrap_or_default()
+    }
+
+    /// The resolved settings as `key = value` lines, secrets redacted.
+    pub fn summary(&self) -> String {
+        let mut table = match toml::Table::try_from(self) {
+            Ok(table) => table,
+            Err(e) => return format!("Failed to format settings: {}", e),
+        };
+        for key in ["api_key", "github_token"] {
+            if let Some(value) = table.get_mut(key) {
+                *value = toml::Value::String("<redacted>".into());
+            }
+        }
+        if let Some(toml::Value::Table(headers)) = table.get_mut("headers") {
+            for (_, value) in headers.iter_mut() {
+                *value = toml::Value::String("<redacted>".into());
+            }
+        }
+        if let Some(toml::Value::Array(backends)) = table.get_mut("compare_backends") {
+            for backend in backends.iter_mut().filter_map(toml::Value::as_table_mut) {
+                if let Some(value) = backend.get_mut("api_key") {
+                    *value = toml::Value::String("<redacted>".into());
+                }
+            }
+        }
+        table
+            .iter()
+            .map(|(key, value)| format!("{} = {}", key, value))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Local ports other features expect to reach, which the local server
+    /// must not fall back to when `api_port` is busy.
+    pub fn reserved_ports(&self) -> Vec<u16> {
+        self.compare_backends
+            .iter()
+            .filter_map(|b| crate::server::loopback_port(&b.api_url))
+            .filter(|&port| port != self.api_port)
+            .collect()
+    }
+
+    /// Warn about configured local ports that collide: two features that each
+    /// need their own server on the same port, or an `api_url` that misses
+    /// the local server started on `api_port`.
+    fn check_ports(&self) {
+        let mut ports: Vec<(String, u16)> = Vec::new();
+        let api_url_port = crate::server::loopback_port(&self.api_url);
+        if self.start_local_server {
+            ports.push(("api_port".into(), self.api_port));
+            if let Some(port) = api_url_port.filter(|&p| p != self.api_port) {
+                tracing::warn!(
+                    "api_url uses local port {} but the local server starts on api_port {}; \
+                     translations won't reach it",
+                    port,
+                    self.api_port
+                );
+            }
+        } else if let Some(port) = api_url_port {
+            ports.push(("api_url".into(), port));
+        }
+        for backend in &self.compare_backends {
+            if let Some(port) = crate::server::loopback_port(&backend.api_url) {
+                let name = if backend.name.trim().is_empty() { &backend.api_url } else { &backend.name };
+                ports.push((format!("compare backend \"{}\"", name), port));
+            }
+        }
+
+        for (i, (name, port)) in ports.iter().enumerate() {
+            if let Some((other, _)) = ports[..i].iter().find(|(_, p)| p == port) {
+                tracing::warn!("{} and {} both use local port {}", other, name, port);
+            }
+        }
+    }
+
+    /// Reset out-of-range values to their defaults, logging each correction.
+    pub fn validate(&mut self) {
+        let defaults = Config::default();
+        if let Some(base) = self.api_base_url.as_deref().map(str::trim).filter(|b| !b.is_empty()) {
+            let api_url = crate::server::endpoint_url(base, "translate");
+            if self.api_url != defaults.api_url && self.api_url != api_url {
+                tracing::warn!(
+                    "Both api_base_url and api_url are set; using {} from api_base_url",
+                    api_url
+                );
+            }
+            self.api_url = api_url;
+        }
+        // The old "preserve" strategy was changecount plus the full restore
+        if self.clipboard_strategy.trim().eq_ignore_ascii_case("preserve") {
+            tracing::warn!(
+                "clipboard_strategy = \"preserve\" is deprecated, using \"changecount\" with preserve_full_clipboard = true"
+            );
+            self.clipboard_strategy = "changecount".to_string();
+            self.preserve_full_clipboard = true;
+        }
+        self.check_ports();
+        if !Self::STARTUP_TIMEOUT_RANGE.contains(&self.server_startup_timeout_secs) {
+            tracing::warn!(
+                "server_startup_timeout_secs = {} is out of range ({}-{}), using {}",
+                self.server_startup_timeout_secs,
+                Self::STARTUP_TIMEOUT_RANGE.start(),
+                Self::STARTUP_TIMEOUT_RANGE.end(),
+                defaults.server_startup_timeout_secs
+            );
+            self.server_startup_timeout_secs = defaults.server_startup_timeout_secs;
+        }
+        if !Self::INTERACTION_EXTEND_RANGE.contains(&self.popup_interaction_extend_ms) {
+            tracing::warn!(
+                "popup_interaction_extend_ms = {} is out of range ({}-{}), using {}",
+                self.popup_interaction_extend_ms,
+                Self::INTERACTION_EXTEND_RANGE.start(),
+                Self::INTERACTION_EXTEND_RANGE.end(),
+                defaults.popup_interaction_extend_ms
+            );
+            self.popup_interaction_extend_ms = defaults.popup_interaction_extend_ms;
+        }
+        if !Self::HOVER_DWELL_RANGE.contains(&self.hover_dwell_ms) {
+            tracing::warn!(
+                "hover_dwell_ms = {} is out of range ({}-{}), using {}",
+                self.hover_dwell_ms,
+                Self::HOVER_DWELL_RANGE.start(),
+                Self::HOVER_DWELL_RANGE.end(),
+                defaults.hover_dwell_ms
+            );
+            self.hover_dwell_ms = defaults.hover_dwell_ms;
+        }
+        if !Self::MAX_POPUPS_RANGE.contains(&self.max_popups) {
+            tracing::warn!(
+                "max_popups = {} is out of range ({}-{}), using {}",
+                self.max_popups,
+                Self::MAX_POPUPS_RANGE.start(),
+                Self::MAX_POPUPS_RANGE.end(),
+                defaults.max_popups
+            );
+            self.max_popups = defaults.max_popups;
+        }
+        for (key, value, default) in [
+            ("popup_min_width", &mut self.popup_min_width, defaults.popup_min_width),
+            (
+                "popup_word_min_width",
+                &mut self.popup_word_min_width,
+                defaults.popup_word_min_width,
+            ),
+            (
+                "popup_error_min_width",
+                &mut self.popup_error_min_width,
+                defaults.popup_error_min_width,
+            ),
+        ] {
+            if !Self::POPUP_MIN_WIDTH_RANGE.contains(value) {
+                tracing::warn!(
+                    "{} = {} is out of range ({}-{}), using {}",
+                    key,
+                    value,
+                    Self::POPUP_MIN_WIDTH_RANGE.start(),
+                    Self::POPUP_MIN_WIDTH_RANGE.end(),
+                    default
+                );
+                *value = default;
+            }
+        }
+        for (key, value, default) in [
+            ("popup_fade_in_ms", &mut self.popup_fade_in_ms, defaults.popup_fade_in_ms),
+            ("popup_fade_out_ms", &mut self.popup_fade_out_ms, defaults.popup_fade_out_ms),
+            (
+                "popup_fade_out_deselect_ms",
+                &mut self.popup_fade_out_deselect_ms,
+                defaults.popup_fade_out_deselect_ms,
+            ),
+        ] {
+            if *value > Self::MAX_FADE_MS {
+                tracing::warn!(
+                    "{} = {} is out of range (0-{}), using {}",
+                    key,
+                    value,
+                    Self::MAX_FADE_MS,
+                    default
+                );
+                *value = default;
+            }
+        }
+    }
+
     fn config_path() -> PathBuf {
         Self::app_dir().join("config.toml")
     }
 
-    /// Update a single field in the config file, preserving the rest.
+    /// Update a single string field in the config file, preserving the rest.
     fn save_field(path: &std::path::Path, key: &str, value: &str) {
+        Self::save_raw_field(path, key, &format!("\"{}\"", value));
+    }
+
+    /// Update a single field with an already-formatted TOML value (bools, numbers).
+    fn save_raw_field(path: &std::path::Path, key: &str, value: &str) {
         if let Ok(contents) = std::fs::read_to_string(path) {
             let mut found = false;
             let updated: Vec<String> = contents
@@ -158,7 +865,7 @@ impl Config {
                     let is_commented = trimmed.starts_with(&format!("# {}", key)) && trimmed.contains('=');
                     if is_active || is_commented {
                         found = true;
-                        format!("{} = \"{}\"", key, value)
+                        format!("{} = {}", key, value)
                     } else {
                         line.to_string()
                     }
@@ -168,7 +875,7 @@ impl Config {
             let new_contents = if found {
                 updated.join("\n")
             } else {
-                format!("{} = \"{}\"\n{}", key, value, contents)
+                format!("{} = {}\n{}", key, value, contents)
             };
 
             if let Err(e) = std::fs::write(path, new_contents) {
@@ -177,9 +884,69 @@ impl Config {
         }
     }
 
+    /// Write the settings files into one portable TOML file at `dest`.
+    pub fn export_settings(dest: &Path) -> Result<()> {
+        let dir = Self::app_dir();
+        let mut files = BTreeMap::new();
+        for name in SETTINGS_FILES {
+            match std::fs::read_to_string(dir.join(name)) {
+                Ok(contents) => {
+                    files.insert(name.to_string(), contents);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e).with_context(|| format!("reading {}", name)),
+            }
+        }
+        let bundle = SettingsBundle { app_version: env!("CARGO_PKG_VERSION").into(), files };
+        std::fs::write(dest, toml::to_string_pretty(&bundle)?)?;
+        tracing::info!("Exported settings to {}", dest.display());
+        Ok(())
+    }
+
+    /// Replace the settings files with the ones in an export at `src`. Every
+    /// file is checked before anything is written; existing ones are kept
+    /// as `<name>.bak`. Takes effect on the next launch.
+    pub fn import_settings(src: &Path) -> Result<()> {
+        let contents = std::fs::read_to_string(src)?;
+        let bundle: SettingsBundle =
+            toml::from_str(&contents).context("not a Screen Translate settings file")?;
+        if bundle.files.is_empty() {
+            bail!("the file contains no settings");
+        }
+        for (name, contents) in &bundle.files {
+            if !SETTINGS_FILES.contains(&name.as_str()) {
+                bail!("unknown file \"{}\" in settings", name);
+            }
+            toml::from_str::<Config>(contents).with_context(|| format!("invalid {}", name))?;
+        }
+
+        let dir = Self::app_dir();
+        std::fs::create_dir_all(&dir)?;
+        for (name, contents) in &bundle.files {
+            let path = dir.join(name);
+            if path.exists() {
+                std::fs::copy(&path, dir.join(format!("{}.bak", name)))
+                    .with_context(|| format!("backing up {}", name))?;
+            }
+            std::fs::write(&path, contents)?;
+        }
+        tracing::info!(
+            "Imported settings from {} (exported by v{})",
+            src.display(),
+            bundle.app_version
+        );
+        Ok(())
+    }
+
     /// Persist target_lang change to config file so it survives restarts.
     pub fn save_target_lang(lang: &str) {
         Self::save_field(&Self::config_path(), "target_lang", lang);
         tracing::info!("Saved target_lang = \"{}\" to config", lang);
     }
+
+    /// Persist the tray "Monitoring Active" state so it survives restarts.
+    pub fn save_monitoring_enabled(enabled: bool) {
+        Self::save_raw_field(&Self::config_path(), "monitoring_enabled", &enabled.to_string());
+        tracing::info!("Saved monitoring_enabled = {} to config", enabled);
+    }
 }
@@ -0,0 +1,136 @@
+use std::io::Write;
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+
+use crate::clipboard::TranslationResult;
+
+/// Lines waiting for a client before it counts as stalled and is dropped.
+const CLIENT_QUEUE: usize = 64;
+
+/// Each client's queue, drained by its own writer thread.
+type Clients = Arc<Mutex<Vec<SyncSender<Arc<str>>>>>;
+
+/// Streams translation results as JSON lines to every connected client of a
+/// Unix domain socket (macOS) or named pipe (Windows), for integrations.
+pub struct EventStream {
+    clients: Clients,
+}
+
+impl EventStream {
+    /// Start listening at `path` on a background thread.
+    pub fn start(path: &str) -> Result<Self> {
+        let clients: Clients = Arc::new(Mutex::new(Vec::new()));
+        spawn_listener(path, clients.clone())?;
+        tracing::info!("Publishing translation events at {}", path);
+        Ok(Self { clients })
+    }
+
+    /// Queue `result` for all clients, dropping the ones that disconnected or
+    /// fell `CLIENT_QUEUE` lines behind. Never blocks on a client.
+    pub fn publish(&self, result: &TranslationResult) {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let mut json = serde_json::json!({
+            "original": result.original,
+            "translated": result.translated,
+            "source": result.source,
+            "target": result.target,
+            "failed": result.failed,
+            "secondary": result.secondary,
+            "comparisons": result.comparisons,
+            "timestamp_ms": timestamp,
+        })
+        .to_string();
+        json.push('\n');
+        let line: Arc<str> = json.into();
+
+        let mut clients = self.clients.lock().unwrap();
+        clients.retain(|client| client.try_send(line.clone()).is_ok());
+    }
+}
+
+/// Register a connected client, written to from its own thread so a slow
+/// reader never holds up `publish`. The thread ends when a write fails or the
+/// client is dropped.
+fn add_client(clients: &Clients, mut writer: impl Write + Send + 'static) {
+    let (tx, rx) = mpsc::sync_channel::<Arc<str>>(CLIENT_QUEUE);
+    std::thread::spawn(move || {
+        for line in rx {
+            if writer.write_all(line.as_bytes()).and_then(|()| writer.flush()).is_err() {
+                break;
+            }
+        }
+    });
+    clients.lock().unwrap().push(tx);
+}
+
+#[cfg(unix)]
+fn spawn_listener(path: &str, clients: Clients) -> Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::UnixListener;
+
+    // A socket left behind by a previous run blocks bind(); never remove anything else
+    if std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_socket()) {
+        let _ = std::fs::remove_file(path);
+    }
+    let listener = UnixListener::bind(path)?;
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => add_client(&clients, stream),
+                Err(e) => tracing::warn!("Event socket accept failed: {}", e),
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(windows)]
+fn spawn_listener(path: &str, clients: Clients) -> Result<()> {
+    use std::os::windows::io::FromRawHandle;
+    use std::ptr;
+    use windows_sys::Win32::Foundation::{
+        CloseHandle, GetLastError, ERROR_PIPE_CONNECTED, INVALID_HANDLE_VALUE,
+    };
+    use windows_sys::Win32::Storage::FileSystem::PIPE_ACCESS_OUTBOUND;
+    use windows_sys::Win32::System::Pipes::{
+        ConnectNamedPipe, CreateNamedPipeW, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    };
+
+    if !path.starts_with(r"\\.\pipe\") {
+        anyhow::bail!("event_socket must be a named pipe path like \\\\.\\pipe\\screen-translate");
+    }
+    let name: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+    std::thread::spawn(move || loop {
+        // One pipe instance per client; a fresh one waits for the next client
+        let pipe = unsafe {
+            CreateNamedPipeW(
+                name.as_ptr(),
+                PIPE_ACCESS_OUTBOUND,
+                PIPE_TYPE_BYTE | PIPE_WAIT,
+                PIPE_UNLIMITED_INSTANCES,
+                4096,
+                0,
+                0,
+                ptr::null(),
+            )
+        };
+        if pipe == INVALID_HANDLE_VALUE {
+            tracing::warn!("Failed to create event pipe (error {})", unsafe { GetLastError() });
+            return;
+        }
+        let connected = unsafe { ConnectNamedPipe(pipe, ptr::null_mut()) } != 0
+            || unsafe { GetLastError() } == ERROR_PIPE_CONNECTED;
+        if connected {
+            let file = unsafe { std::fs::File::from_raw_handle(pipe) };
+            add_client(&clients, file);
+        } else {
+            unsafe { CloseHandle(pipe) };
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use anyhow::{bail, Result};
+
+pub const MOD_CTRL: u8 = 1 << 0;
+pub const MOD_ALT: u8 = 1 << 1;
+pub const MOD_SHIFT: u8 = 1 << 2;
+/// Cmd on macOS, Win on Windows.
+pub const MOD_SUPER: u8 = 1 << 3;
+
+/// Non-modifier key of a hotkey. Platform backends map these to virtual keys / keycodes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Key {
+    /// `b'A'..=b'Z'`
+    Letter(u8),
+    /// `b'0'..=b'9'`
+    Digit(u8),
+    /// F1-F12
+    Function(u8),
+    Space,
+    Enter,
+    Tab,
+    Escape,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Hotkey {
+    pub modifiers: u8,
+    pub key: Key,
+}
+
+impl Hotkey {
+    /// Parse strings like "Ctrl+Shift+R", "CmdOrCtrl+Alt+T" or "F8".
+    ///
+    /// `CmdOrCtrl` resolves to Cmd on macOS and Ctrl elsewhere, so one config
+    /// value works on both platforms.
+    pub fn parse(value: &str) -> Result<Self> {
+        let mut modifiers = 0u8;
+        let mut key = None;
+
+        for part in value.split('+').map(str::trim) {
+            let lower = part.to_ascii_lowercase();
+            if let Some(m) = parse_modifier(&lower) {
+                modifiers |= m;
+                continue;
+            }
+            if key.is_some() {
+                bail!("Hotkey \"{}\" has more than one key", value);
+            }
+            key = Some(parse_key(&lower).ok_or_else(|| {
+                anyhow::anyhow!("Unknown key \"{}\" in hotkey \"{}\"", part, value)
+            })?);
+        }
+
+        let Some(key) = key else {
+            bail!("Hotkey \"{}\" has no key", value);
+        };
+        // A bare letter or digit would fire while typing
+        if modifiers == 0 && matches!(key, Key::Letter(_) | Key::Digit(_)) {
+            bail!("Hotkey \"{}\" needs at least one modifier", value);
+        }
+        Ok(Self { modifiers, key })
+    }
+}
+
+/// One modifier name ("Ctrl", "Alt", "Shift", "Cmd", "CmdOrCtrl", ...) as a
+/// `MOD_*` bit.
+pub fn parse_modifier(name: &str) -> Option<u8> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "ctrl" | "control" => Some(MOD_CTRL),
+        "alt" | "option" | "opt" => Some(MOD_ALT),
+        "shift" => Some(MOD_SHIFT),
+        "cmd" | "command" | "super" | "win" | "meta" => Some(MOD_SUPER),
+        "cmdorctrl" | "commandorcontrol" => Some(if cfg!(target_os = "macos") {
+            MOD_SUPER
+        } else {
+            MOD_CTRL
+        }),
+        _ => None,
+    }
+}
+
+fn parse_key(name: &str) -> Option<Key> {
+    match name {
+        "space" => return Some(Key::Space),
+        "enter" | "return" => return Some(Key::Enter),
+        "tab" => return Some(Key::Tab),
+        "esc" | "escape" => return Some(Key::Escape),
+        _ => {}
+    }
+    let bytes = name.as_bytes();
+    if bytes.len() == 1 {
+        let c = bytes[0].to_ascii_uppercase();
+        return match c {
+            b'A'..=b'Z' => Some(Key::Letter(c)),
+            b'0'..=b'9' => Some(Key::Digit(c)),
+            _ => None,
+        };
+    }
+    let n: u8 = name.strip_prefix('f')?.parse().ok()?;
+    (1..=12).contains(&n).then_some(Key::Function(n))
+}
+
+/// What a registered hotkey does. The discriminant is the id handed to the platform.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u32)]
+pub enum HotkeyAction {
+    /// Translate the selection and paste the result over it.
+    ReplaceInPlace = 1,
+    /// Hide the popup instantly (no fade) and drop queued results.
+    DismissAll = 2,
+    /// Translate the popup's translation back into the source language.
+    RoundTrip = 3,
+    /// Show the previous translation from the history in the popup.
+    HistoryPrev = 4,
+    /// Show the next (more recent) translation from the history.
+    HistoryNext = 5,
+    /// Select the paragraph under the pointer and translate it.
+    Paragraph = 6,
+    /// Start collecting selections, or translate the collected ones together.
+    Collect = 7,
+}
+
+impl HotkeyAction {
+    pub fn from_id(id: u32) -> Option<Self> {
+        match id {
+            1 => Some(Self::ReplaceInPlace),
+            2 => Some(Self::DismissAll),
+            3 => Some(Self::RoundTrip),
+            4 => Some(Self::HistoryPrev),
+            5 => Some(Self::HistoryNext),
+            6 => Some(Self::Paragraph),
+            7 => Some(Self::Collect),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! The app's own UI strings in the language picked by `ui_lang`, from the
+//! tables bundled in assets/locales. Messages a table lacks fall back to
+//! English, then to the message id itself.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/// Bundled string tables by language code.
+const LOCALES: &[(&str, &str)] = &[
+    ("en", include_str!("../assets/locales/en.toml")),
+    ("id", include_str!("../assets/locales/id.toml")),
+];
+
+struct Strings {
+    selected: HashMap<String, String>,
+    english: HashMap<String, String>,
+}
+
+static STRINGS: OnceLock<Strings> = OnceLock::new();
+
+/// Pick the UI language. Call once at startup, before any string is looked
+/// up; until then (and for unknown codes) English is used.
+pub fn init(ui_lang: &str) {
+    let code = ui_lang.trim().to_lowercase();
+    let selected = match LOCALES.iter().find(|(lang, _)| *lang == code) {
+        Some((_, source)) => parse(&code, source),
+        None => {
+            tracing::warn!("No UI strings for ui_lang \"{}\", using English", ui_lang.trim());
+            HashMap::new()
+        }
+    };
+    let _ = STRINGS.set(Strings {
+        selected,
+        english: parse("en", LOCALES[0].1),
+    });
+}
+
+fn parse(lang: &str, source: &str) -> HashMap<String, String> {
+    toml::from_str(source).unwrap_or_else(|e| {
+        tracing::error!("Bundled UI strings for \"{}\" are invalid: {}", lang, e);
+        HashMap::new()
+    })
+}
+
+/// The UI string for message `id`.
+pub fn tr(id: &'static str) -> &'static str {
+    let strings = STRINGS.get_or_init(|| Strings {
+        selected: HashMap::new(),
+        english: parse("en", LOCALES[0].1),
+    });
+    strings
+        .selected
+        .get(id)
+        .or_else(|| strings.english.get(id))
+        .map_or(id, String::as_str)
+}
+
+/// `tr(id)` with each `{name}` replaced by its value from `args`.
+pub fn tr_args(id: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter().fold(tr(id).to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), &value.to_string())
+    })
+}
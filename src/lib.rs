@@ -0,0 +1,118 @@
+//! Translation core of Screen Translate: config loading, the LibreTranslate
+//! client and local server management. The tray, popup and platform hooks
+//! live in the binary on top of this.
+
+pub mod config;
+pub mod server;
+pub mod text;
+pub mod translator;
+
+use std::sync::{Arc, RwLock};
+
+use anyhow::Result;
+
+use config::Config;
+use server::LibreTranslateServer;
+use translator::{DetectFilter, SourceHint, Translator};
+
+/// Translates text the same way the app does, without any UI.
+pub struct ScreenTranslate {
+    config: Config,
+    target_lang: Arc<RwLock<String>>,
+    translator: Translator,
+    /// The local server started by `ensure_server`, stopped on drop.
+    server: Option<LibreTranslateServer>,
+}
+
+impl ScreenTranslate {
+    /// Use the user's config file, as the app does. Like the app, this saves
+    /// a malformed file as `config.toml.invalid` before failing.
+    pub fn from_user_config() -> Result<Self> {
+        let mut config = Config::load()?;
+        config.validate();
+        Self::new(config)
+    }
+
+    /// Fails when the HTTP client can't be built, e.g. over a malformed proxy setting.
+    pub fn new(config: Config) -> Result<Self> {
+        let target_lang = Arc::new(RwLock::new(config.target_lang.clone()));
+        let translator = Self::build_translator(&config, &config.api_url, target_lang.clone())?;
+        Ok(Self {
+            config,
+            target_lang,
+            translator,
+            server: None,
+        })
+    }
+
+    fn build_translator(config: &Config, api_url: &str, target_lang: Arc<RwLock<String>>) -> Result<Translator> {
+        let translator = Translator::new(
+            api_url.to_string(),
+            config.api_key.clone(),
+            config.source_lang.clone(),
+            SourceHint::from_config(config),
+            DetectFilter::from_config(config),
+            translator::parse_target_rules(&config.source_target_rules),
+            target_lang,
+        )?
+        .with_headers(&config.headers)?
+        .with_request_policy(translator::RequestPolicy::from_config(config));
+        Ok(if config.incremental_translation {
+            translator.with_sentence_cache()
+        } else {
+            translator
+        })
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn target_lang(&self) -> String {
+        self.target_lang.read().unwrap().clone()
+    }
+
+    pub fn set_target_lang(&self, lang: &str) {
+        *self.target_lang.write().unwrap() = lang.to_string();
+    }
+
+    /// Start the local LibreTranslate server and wait until it accepts
+    /// connections, unless `start_local_server` is off or one is already
+    /// running on `api_port`. The server stops when this is dropped.
+    pub fn ensure_server(&mut self) -> Result<()> {
+        if !self.config.start_local_server
+            || self.server.is_some()
+            || server::is_libretranslate_running(self.config.api_port)
+        {
+            return Ok(());
+        }
+
+        let started = LibreTranslateServer::start(
+            self.config.python_path.as_deref(),
+            self.config.api_port,
+            &self.config.load_languages,
+            &self.config.reserved_ports(),
+            std::time::Duration::from_secs(self.config.server_startup_timeout_secs),
+        )?;
+        // The preferred port may have been taken
+        if started.port() != self.config.api_port {
+            let base_url = crate::server::local_base_url(started.port());
+            let api_url = crate::server::endpoint_url(&base_url, "translate");
+            tracing::info!("Updated API URL to: {}", api_url);
+            self.translator = Self::build_translator(&self.config, &api_url, self.target_lang.clone())?;
+        }
+        self.server = Some(started);
+        Ok(())
+    }
+
+    /// Translate `text` into the target language, applying the configured
+    /// source hint, detection filter and per-source target rules.
+    pub async fn translate(&self, text: &str) -> Result<String> {
+        Ok(self.translator.translate(text).await?.text)
+    }
+
+    /// The underlying client, for the detected source language or explicit pairs.
+    pub fn translator(&self) -> &Translator {
+        &self.translator
+    }
+}
@@ -1,25 +1,332 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod clipboard;
-mod config;
+mod events;
+mod hotkey;
+mod i18n;
 mod platform;
-mod server;
-mod translator;
+mod queue;
 mod tray;
 mod updater;
 
-use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use screen_translate::{config, server, text, translator};
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Duration, Instant};
 
-use clipboard::{SelectionPos, TranslationRequest, TranslationResult};
+use clipboard::{ClipboardStrategy, RequestKind, SelectionPos, TranslationRequest, TranslationResult};
+use hotkey::HotkeyAction;
+use i18n::{tr, tr_args};
 use platform::MouseEvent;
+use queue::BoundedQueue;
 use tray::TrayAction;
 
+/// Phrase sent by the tray's "Test Translation" item.
+const TEST_PHRASE: &str = "Hello, world";
+
+/// Pause in typing after which the "Translate Text" window translates.
+const INPUT_DEBOUNCE: Duration = Duration::from_millis(400);
+
+/// Server startups slower than this get a "Ready" dialog, not just a tooltip.
+const SERVER_READY_NOTICE_AFTER: Duration = Duration::from_secs(30);
+
+/// `restart_on_crash` gives up after this many crashes within the window.
+const MAX_CRASH_RESTARTS: usize = 3;
+const CRASH_RESTART_WINDOW: Duration = Duration::from_secs(10 * 60);
+/// Timestamps of recent crash restarts, one per line, in the app dir.
+const CRASH_RESTARTS_FILE: &str = "crash-restarts";
+
+/// File name suggested by "Export Settings".
+const SETTINGS_EXPORT_NAME: &str = "screen-translate-settings.toml";
+
+/// Set from `restart_on_crash` once the config is loaded; read by the panic hook.
+static RESTART_ON_CRASH: AtomicBool = AtomicBool::new(false);
+
+/// Push the popup look and behaviour settings to the platform layer; run at
+/// startup and again by "Reload Config".
+fn apply_popup_settings(config: &config::Config) {
+    #[cfg(target_os = "macos")]
+    let popup_material = platform::PopupMaterial::from_config(&config.popup_material)
+        .unwrap_or_else(|| {
+            tracing::warn!(
+                "Unknown popup_material \"{}\", using hud",
+                config.popup_material
+            );
+            platform::PopupMaterial::Hud
+        });
+    let popup_renderer = platform::PopupRenderer::from_config(&config.popup_renderer)
+        .unwrap_or_else(|| {
+            tracing::warn!(
+                "Unknown popup_renderer \"{}\", using styled",
+                config.popup_renderer
+            );
+            platform::PopupRenderer::Styled
+        });
+    // The plain renderer shows and hides at once
+    let animated = popup_renderer == platform::PopupRenderer::Styled;
+    let anim_ms = |ms: u64| if animated { ms as f64 } else { 0.0 };
+    #[cfg(target_os = "windows")]
+    let popup_accent = config.popup_accent.as_deref().filter(|a| !a.is_empty()).and_then(|a| {
+        let rgb = platform::parse_hex_color(a);
+        if rgb.is_none() {
+            tracing::warn!("Invalid popup_accent \"{}\" (expected #RRGGBB), ignoring", a);
+        }
+        rgb
+    });
+    #[cfg(target_os = "windows")]
+    let popup_text_rendering = platform::TextRendering::from_config(&config.popup_text_rendering)
+        .unwrap_or_else(|| {
+            tracing::warn!(
+                "Unknown popup_text_rendering \"{}\", using cleartype",
+                config.popup_text_rendering
+            );
+            platform::TextRendering::ClearType
+        });
+    let popup_text_align = platform::TextAlign::from_config(&config.popup_text_align)
+        .unwrap_or_else(|| {
+            tracing::warn!(
+                "Unknown popup_text_align \"{}\", using auto",
+                config.popup_text_align
+            );
+            platform::TextAlign::Auto
+        });
+    let popup_display_change = platform::DisplayChange::from_config(&config.popup_display_change)
+        .unwrap_or_else(|| {
+            tracing::warn!(
+                "Unknown popup_display_change \"{}\", using dismiss",
+                config.popup_display_change
+            );
+            platform::DisplayChange::Dismiss
+        });
+    let popup_placement = platform::Placement::from_config(&config.popup_placement)
+        .unwrap_or_else(|| {
+            tracing::warn!(
+                "Unknown popup_placement \"{}\", using selection",
+                config.popup_placement
+            );
+            platform::Placement::Selection
+        });
+    platform::set_script_fonts(platform::parse_script_fonts(&config.popup_font_by_script));
+    platform::set_popup_options(platform::PopupOptions {
+        fade_in_ms: anim_ms(config.popup_fade_in_ms),
+        fade_out_ms: anim_ms(config.popup_fade_out_ms),
+        fade_out_deselect_ms: anim_ms(config.popup_fade_out_deselect_ms),
+        fade: config.popup_fade && animated,
+        slide: config.popup_slide && animated,
+        #[cfg(target_os = "macos")]
+        material: popup_material,
+        renderer: popup_renderer,
+        #[cfg(target_os = "windows")]
+        accent: popup_accent,
+        #[cfg(target_os = "windows")]
+        text_rendering: popup_text_rendering,
+        short_text_chars: config.short_translation_chars,
+        short_min_display_secs: config.short_translation_min_secs as f64,
+        sticky_size: config.popup_sticky_size,
+        countdown_bar: config.popup_countdown_bar,
+        text_align: popup_text_align,
+        display_change: popup_display_change,
+        placement: popup_placement,
+        cursor_offset: (config.popup_cursor_offset_x, config.popup_cursor_offset_y),
+        max_lifetime_secs: config.popup_max_lifetime_secs as f64,
+        interaction_extend_ms: config.popup_interaction_extend_ms as f64,
+        max_popups: config.max_popups,
+        min_width: config.popup_min_width as f64,
+        word_min_width: config.popup_word_min_width as f64,
+        error_min_width: config.popup_error_min_width as f64,
+        #[cfg(target_os = "windows")]
+        original_on_hover: config.popup_original_on_hover,
+    });
+}
+
+/// Settings that decide which selections the grab thread passes on; shared
+/// with it so "Reload Config" can swap them.
+#[derive(Clone)]
+struct SelectionFilter {
+    max_text_length: usize,
+    min_words: usize,
+    min_cjk_chars: usize,
+    require_foreign_script: bool,
+    native_scripts: Vec<text::Script>,
+    trigger_scripts: Vec<text::Script>,
+}
+
+impl SelectionFilter {
+    fn from_config(config: &config::Config) -> Self {
+        Self {
+            max_text_length: config.max_text_length,
+            min_words: config.min_words,
+            min_cjk_chars: config.min_cjk_chars,
+            require_foreign_script: config.require_foreign_script,
+            native_scripts: parse_script_list(&config.native_scripts, "native_scripts"),
+            trigger_scripts: parse_script_list(&config.trigger_scripts, "trigger_scripts"),
+        }
+    }
+}
+
+/// Config keys "Reload Config" applies to the running app; any other change
+/// waits for a restart.
+const RELOADABLE_KEYS: &[&str] = &[
+    "target_lang",
+    "monitoring_enabled",
+    "selection_threshold_x",
+    "selection_threshold_y",
+    "min_drag_hold_ms",
+    "max_text_length",
+    "min_words",
+    "min_cjk_chars",
+    "require_foreign_script",
+    "native_scripts",
+    "trigger_scripts",
+    "popup_material",
+    "popup_renderer",
+    "popup_fade_in_ms",
+    "popup_fade_out_ms",
+    "popup_fade_out_deselect_ms",
+    "popup_fade",
+    "popup_slide",
+    "popup_accent",
+    "popup_text_rendering",
+    "popup_text_align",
+    "popup_display_change",
+    "popup_placement",
+    "popup_cursor_offset_x",
+    "popup_cursor_offset_y",
+    "popup_max_lifetime_secs",
+    "popup_interaction_extend_ms",
+    "popup_sticky_size",
+    "popup_countdown_bar",
+    "popup_original_on_hover",
+    "popup_font_by_script",
+    "short_translation_chars",
+    "short_translation_min_secs",
+    "max_popups",
+    "popup_min_width",
+    "popup_word_min_width",
+    "popup_error_min_width",
+];
+
+/// Apply the reloadable settings of a freshly loaded `config`. `applied` is
+/// the `settings_table` of what's in effect; each changed key is logged as
+/// applied or left for a restart. Returns (applied, needs restart) counts.
+/// `target_lang` and the main loop's own settings are applied by the caller.
+fn reload_config(
+    config: &config::Config,
+    applied: &mut toml::Table,
+    selection_filter: &RwLock<SelectionFilter>,
+) -> (usize, usize) {
+    let table = config.settings_table();
+    let mut keys: Vec<&String> = table.keys().chain(applied.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    let changed: Vec<String> = keys
+        .into_iter()
+        .filter(|key| table.get(*key) != applied.get(*key))
+        .cloned()
+        .collect();
+
+    apply_popup_settings(config);
+    *selection_filter.write().unwrap() = SelectionFilter::from_config(config);
+
+    let (mut reloaded, mut pending) = (0, 0);
+    for key in changed {
+        if RELOADABLE_KEYS.contains(&key.as_str()) {
+            tracing::info!("Reload: applied {}", key);
+            match table.get(&key) {
+                Some(value) => applied.insert(key, value.clone()),
+                None => applied.remove(&key),
+            };
+            reloaded += 1;
+        } else {
+            tracing::warn!("Reload: {} changed, takes effect after a restart", key);
+            pending += 1;
+        }
+    }
+    (reloaded, pending)
+}
+
+/// `target_lang = "system"`: replace it with the OS language, or the default
+/// target if that can't be read.
+fn resolve_system_target_lang(config: &mut config::Config) {
+    if !config.target_lang.trim().eq_ignore_ascii_case("system") {
+        return;
+    }
+    let Some(code) = platform::system_language() else {
+        let fallback = config::Config::default().target_lang;
+        tracing::warn!("Could not read the system language, translating into \"{}\"", fallback);
+        config.target_lang = fallback;
+        return;
+    };
+    tracing::info!("System language is \"{}\", using it as target_lang", code);
+    if !config.load_languages.split(',').any(|lang| lang.trim() == code) {
+        tracing::warn!(
+            "System language \"{}\" isn't in load_languages; translations into it may fail",
+            code
+        );
+    }
+    config.target_lang = code;
+}
+
+/// Whether (`x`, `y`) is on one of `active_monitors` (empty = all monitors).
+fn on_active_monitor(active_monitors: &[usize], x: i32, y: i32) -> bool {
+    if active_monitors.is_empty() {
+        return true;
+    }
+    let monitor = platform::monitor_index_at(x, y);
+    tracing::debug!("Selection on monitor {:?}", monitor);
+    monitor.is_some_and(|m| active_monitors.contains(&m))
+}
+
+/// Whether a full-screen app is in front, for `auto_pause_fullscreen`.
+/// `paused` is the previous answer, so the pause and resume are logged once each.
+fn fullscreen_active(paused: &mut bool) -> bool {
+    let fullscreen = platform::is_fullscreen_app_active();
+    if fullscreen != *paused {
+        *paused = fullscreen;
+        if fullscreen {
+            tracing::info!("Full-screen app active, pausing selections");
+        } else {
+            tracing::info!("Left full screen, resuming selections");
+        }
+    }
+    fullscreen
+}
+
+/// Pointer jitter still counted as resting for `hover_translate`, in pixels.
+const HOVER_TOLERANCE_PX: i32 = 4;
+
+fn is_near_point(a: (i32, i32), b: (i32, i32)) -> bool {
+    (a.0 - b.0).abs() <= HOVER_TOLERANCE_PX && (a.1 - b.1).abs() <= HOVER_TOLERANCE_PX
+}
+
+/// Parse a comma-separated script list such as `native_scripts`, warning
+/// about and dropping names that aren't known.
+fn parse_script_list(value: &str, key: &str) -> Vec<text::Script> {
+    value
+        .split(',')
+        .filter(|s| !s.trim().is_empty())
+        .filter_map(|s| {
+            let script = text::Script::from_config(s);
+            if script.is_none() {
+                tracing::warn!("Unknown script \"{}\" in {}, ignoring", s.trim(), key);
+            }
+            script
+        })
+        .collect()
+}
+
+type LogFilterHandle =
+    tracing_subscriber::reload::Handle<tracing_subscriber::filter::LevelFilter, tracing_subscriber::Registry>;
+
+/// Swaps the log level at runtime for the tray's "Verbose Logging".
+static LOG_FILTER: std::sync::OnceLock<LogFilterHandle> = std::sync::OnceLock::new();
+
 fn setup_logging() {
     use tracing_subscriber::prelude::*;
-    use tracing_subscriber::fmt;
+    use tracing_subscriber::{fmt, reload};
 
     let log_dir = config::Config::app_dir();
     let _ = std::fs::create_dir_all(&log_dir);
@@ -32,6 +339,9 @@ fn setup_logging() {
         .with_target(false)
         .with_writer(std::io::stdout);
 
+    let (filter, filter_handle) = reload::Layer::new(tracing_subscriber::filter::LevelFilter::INFO);
+    let _ = LOG_FILTER.set(filter_handle);
+
     if let Some(file) = file {
         let file_layer = fmt::layer()
             .with_target(false)
@@ -39,50 +349,164 @@ fn setup_logging() {
             .with_writer(std::sync::Mutex::new(file));
 
         tracing_subscriber::registry()
-            .with(tracing_subscriber::filter::LevelFilter::INFO)
+            .with(filter)
             .with(stdout_layer)
             .with(file_layer)
             .init();
     } else {
         tracing_subscriber::registry()
-            .with(tracing_subscriber::filter::LevelFilter::INFO)
+            .with(filter)
             .with(stdout_layer)
             .init();
     }
 }
 
+/// Log at DEBUG instead of INFO until turned off again, without a restart.
+fn set_verbose_logging(verbose: bool) {
+    use tracing_subscriber::filter::LevelFilter;
+
+    let Some(handle) = LOG_FILTER.get() else {
+        return;
+    };
+    let level = if verbose { LevelFilter::DEBUG } else { LevelFilter::INFO };
+    // Logged at INFO so the switch shows up either way
+    tracing::info!("Log level set to {}", level);
+    if let Err(e) = handle.modify(|filter| *filter = level) {
+        tracing::warn!("Failed to change the log level: {}", e);
+    }
+}
+
+/// Handle `--export-settings <path>` and `--import-settings <path>`, which
+/// do their work and exit instead of starting the app. Returns the exit code,
+/// or `None` to start normally.
+fn run_settings_command() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [command, path] = args.as_slice() else {
+        return None;
+    };
+    let path = std::path::Path::new(path);
+    let result = match command.as_str() {
+        "--export-settings" => config::Config::export_settings(path),
+        "--import-settings" => config::Config::import_settings(path),
+        _ => return None,
+    };
+    match result {
+        Ok(()) => Some(0),
+        Err(e) => {
+            tracing::error!("{} failed: {:#}", command, e);
+            Some(1)
+        }
+    }
+}
+
+/// Note a crash restart in the state file. Returns false, meaning don't
+/// restart, when the limit for the window is already used up or the file
+/// can't be written (so a crash loop can't go unbounded).
+fn record_crash_restart() -> bool {
+    let path = config::Config::app_dir().join(CRASH_RESTARTS_FILE);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let since = now.saturating_sub(CRASH_RESTART_WINDOW.as_secs());
+    let mut recent: Vec<u64> = std::fs::read_to_string(&path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.trim().parse().ok())
+        .filter(|&t| t >= since)
+        .collect();
+
+    if recent.len() >= MAX_CRASH_RESTARTS {
+        tracing::error!(
+            "Crashed {} times in {} min, not relaunching",
+            recent.len() + 1,
+            CRASH_RESTART_WINDOW.as_secs() / 60
+        );
+        return false;
+    }
+    recent.push(now);
+    let contents: String = recent.iter().map(|t| format!("{}\n", t)).collect();
+    if let Err(e) = std::fs::write(&path, contents) {
+        tracing::error!("Failed to record crash restart: {}", e);
+        return false;
+    }
+    true
+}
+
 fn main() {
     setup_logging();
 
+    if let Some(code) = run_settings_command() {
+        std::process::exit(code);
+    }
+
     std::panic::set_hook(Box::new(|info| {
         let msg = format!("{}", info);
         tracing::error!("PANIC: {}", msg);
-        platform::show_error("Screen Translate crashed", &msg);
+        if RESTART_ON_CRASH.load(Ordering::Relaxed) && record_crash_restart() {
+            match std::env::current_exe().and_then(|exe| std::process::Command::new(exe).spawn()) {
+                Ok(_) => {
+                    tracing::info!("Relaunched after crash");
+                    std::process::exit(1);
+                }
+                Err(e) => tracing::error!("Failed to relaunch after crash: {}", e),
+            }
+        }
+        platform::show_error(tr("dialog.crashed"), &msg);
     }));
 
     platform::init_platform();
 
     tracing::info!("Screen Translate starting");
 
-    let config = config::Config::load().unwrap_or_else(|e| {
-        tracing::warn!("Failed to load config: {}, using defaults", e);
+    let mut load_error = None;
+    let mut config = config::Config::load().unwrap_or_else(|e| {
+        tracing::warn!("Failed to load config: {:#}, using defaults", e);
+        load_error = Some(e);
         config::Config::default()
     });
+    config.validate();
+    resolve_system_target_lang(&mut config);
+    RESTART_ON_CRASH.store(config.restart_on_crash, Ordering::Relaxed);
+    i18n::init(&config.ui_lang);
+    if let Some(e) = load_error {
+        // Otherwise a typo silently discards every setting
+        platform::show_error(
+            "Screen Translate",
+            &tr_args("dialog.config_invalid", &[("error", &format!("{:#}", e))]),
+        );
+    }
+    // Snapshot for the tray's "Show Settings", before fields are moved out;
+    // replaced by "Reload Config"
+    let mut settings_summary = config.summary();
+    // What's in effect, for telling "Reload Config" changes apart
+    let mut applied_settings = config.settings_table();
+    let selection_filter = Arc::new(RwLock::new(SelectionFilter::from_config(&config)));
+
+    apply_popup_settings(&config);
+    let display_mode = platform::DisplayMode::from_config(&config.display_mode).unwrap_or_else(|| {
+        tracing::warn!("Unknown display_mode \"{}\", using popup", config.display_mode);
+        platform::DisplayMode::Popup
+    });
 
     updater::cleanup_old_binary();
 
     enum UpdateNotification {
         UpToDate,
         Available(updater::UpdateInfo),
+        RateLimited(String),
+        /// Update applied; shut down and start the new version.
+        Installed(updater::Relaunch),
     }
 
     let update_notify: Arc<Mutex<Option<UpdateNotification>>> = Arc::new(Mutex::new(None));
 
     if config.auto_update {
         let notify = update_notify.clone();
+        let github_token = config.github_token.clone();
         std::thread::spawn(move || {
             std::thread::sleep(Duration::from_secs(5));
-            match updater::check_for_update() {
+            match updater::check_for_update(github_token.as_deref()) {
                 Ok(Some(info)) => {
                     tracing::info!("Update available: v{}", info.version);
                     *notify.lock().unwrap() = Some(UpdateNotification::Available(info));
@@ -107,13 +531,18 @@ fn main() {
     ));
 
     // Create tray icon FIRST so user sees the app is running
-    let tray = match tray::Tray::new(&languages, &config.target_lang, &installed_languages.read().unwrap()) {
+    let tray = match tray::Tray::new(
+        &languages,
+        &config.target_lang,
+        &installed_languages.read().unwrap(),
+        config.monitoring_enabled,
+    ) {
         Ok(t) => t,
         Err(e) => {
             tracing::error!("Failed to create tray icon: {}", e);
             platform::show_error(
                 "Screen Translate",
-                &format!("Failed to create system tray icon: {}\n\nThe application cannot continue.", e),
+                &tr_args("dialog.tray_failed", &[("error", &e)]),
             );
             return;
         }
@@ -124,7 +553,7 @@ fn main() {
     // Track server readiness so we can show accurate error messages
     let server_status = Arc::new(AtomicU8::new(server::SERVER_READY));
 
-    let _server = if !config.start_local_server {
+    let local_server = if !config.start_local_server {
         tracing::info!("Local server disabled, using external API: {}", config.api_url);
         None
     } else if server::is_libretranslate_running(config.api_port) {
@@ -138,15 +567,22 @@ fn main() {
             config.python_path.as_deref(),
             config.api_port,
             &config.load_languages,
+            &config.reserved_ports(),
         ) {
             Ok(s) => {
                 let actual_port = s.port();
                 if actual_port != config.api_port {
-                    actual_api_url = format!("http://127.0.0.1:{}/translate", actual_port);
+                    actual_api_url =
+                        server::endpoint_url(&server::local_base_url(actual_port), "translate");
                     tracing::info!("Updated API URL to: {}", actual_api_url);
                 }
                 // Monitor process health and readiness in background
-                server::spawn_readiness_monitor(actual_port, s.child_handle(), server_status.clone());
+                server::spawn_readiness_monitor(
+                    actual_port,
+                    s.child_handle(),
+                    server_status.clone(),
+                    Duration::from_secs(config.server_startup_timeout_secs),
+                );
                 tracing::info!("App ready - LibreTranslate starting on port {}", actual_port);
                 Some(s)
             }
@@ -162,55 +598,271 @@ fn main() {
     // Channel for language download completion notifications
     let (lang_download_tx, lang_download_rx) = mpsc::channel::<(String, bool)>();
 
-    let monitoring = std::sync::Arc::new(AtomicBool::new(true));
+    let monitoring = std::sync::Arc::new(AtomicBool::new(config.monitoring_enabled));
+    if !config.monitoring_enabled {
+        tracing::info!("Monitoring restored as paused from last session");
+    }
 
-    let (text_tx, text_rx) = mpsc::channel::<TranslationRequest>();
+    // Bounded so a stalled backend can't queue up a flood of stale requests
+    let text_queue =
+        BoundedQueue::<TranslationRequest>::new("Translation", config.request_queue_capacity);
     let (result_tx, result_rx) = mpsc::channel::<TranslationResult>();
+    // Bumped by the dismiss hotkey; results from before it are dropped
+    let dismiss_generation = Arc::new(AtomicU64::new(0));
+
+    // The server's /languages list, fetched once it's up, greys out tray
+    // languages it can't produce
+    let (server_langs_tx, server_langs_rx) = mpsc::channel::<HashSet<String>>();
+    let mut server_langs_fetch = Some((
+        actual_api_url.clone(),
+        config.api_key.clone(),
+        config.headers.clone(),
+        config.source_lang.clone(),
+    ));
 
     let _translation_handle = clipboard::spawn_translation_thread(
-        text_rx,
+        text_queue.clone(),
         result_tx,
-        actual_api_url,
-        config.api_key,
-        config.source_lang,
+        clipboard::TranslationSettings {
+            // Borrowed from config before the fields below are moved out of it
+            source_hint: translator::SourceHint::from_config(&config),
+            detect_filter: translator::DetectFilter::from_config(&config),
+            target_rules: translator::parse_target_rules(&config.source_target_rules),
+            request_policy: translator::RequestPolicy::from_config(&config),
+            api_url: actual_api_url,
+            api_key: config.api_key,
+            headers: config.headers,
+            source_lang: config.source_lang,
+            secondary_target_lang: config
+                .secondary_target_lang
+                .map(|lang| lang.trim().to_string())
+                .filter(|lang| !lang.is_empty()),
+            compare_backends: config.compare_backends,
+            max_concurrent_requests: config.max_concurrent_requests,
+            incremental: config.incremental_translation,
+        },
         target_lang.clone(),
-        server_status,
+        server_status.clone(),
+        dismiss_generation.clone(),
     );
 
+    // Tell the user once when a cold-started local server becomes usable
+    let mut server_wait_start = (server_status.load(Ordering::Relaxed) == server::SERVER_STARTING)
+        .then(Instant::now);
+    if server_wait_start.is_some() {
+        tray.set_tooltip(tr("tooltip.loading"));
+    }
+
     // grab thread — reads clipboard off the main thread
-    let (grab_tx, grab_rx) = mpsc::channel::<SelectionPos>();
-    let text_tx_clone = text_tx.clone();
-    let max_text_length = config.max_text_length;
+    let grab_queue =
+        BoundedQueue::<(SelectionPos, RequestKind)>::new("Selection", config.request_queue_capacity);
+    let grab_queue_worker = grab_queue.clone();
+    let text_queue_grab = text_queue.clone();
+    let selection_filter_grab = selection_filter.clone();
+    let dedup_window = Duration::from_millis(config.dedup_window_ms);
+    let dedup_tolerance = config.dedup_tolerance_px.min(i32::MAX as u32) as i32;
+    let skip_secure_input = config.skip_secure_input;
+    let accessibility_selection = config.accessibility_selection;
+    let accessibility_exclude: Vec<String> = config
+        .accessibility_selection_exclude
+        .split(',')
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let clipboard_strategy = ClipboardStrategy::from_config(&config.clipboard_strategy)
+        .unwrap_or_else(|| {
+            tracing::warn!(
+                "Unknown clipboard_strategy \"{}\", using clear_and_restore",
+                config.clipboard_strategy
+            );
+            ClipboardStrategy::ClearAndRestore
+        });
+    let preserve_full_clipboard = config.preserve_full_clipboard;
+    let remote_session = match config.remote_session.trim().to_ascii_lowercase().as_str() {
+        "on" => true,
+        "off" => false,
+        value => {
+            if value != "auto" {
+                tracing::warn!("Unknown remote_session \"{}\", using auto", config.remote_session);
+            }
+            platform::is_remote_session()
+        }
+    };
+    if remote_session {
+        tracing::info!("Remote session mode: waiting longer for copied selections");
+    }
+    let inline_lang_markers = config.inline_lang_markers;
+    let join_lines = text::JoinLines::from_config(&config.join_lines).unwrap_or_else(|| {
+        tracing::warn!("Unknown join_lines \"{}\", using none", config.join_lines);
+        text::JoinLines::None
+    });
+    let target_lang_grab = target_lang.clone();
+    let dismiss_generation_grab = dismiss_generation.clone();
+    // User's clipboard from before a replace-in-place grab, restored after the paste
+    let saved_clipboard: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+    let saved_clipboard_grab = saved_clipboard.clone();
+    // Selections grabbed in collect mode, handed back instead of translated
+    let (collected_tx, collected_rx) = mpsc::channel::<String>();
     std::thread::spawn(move || {
         let mut last_text = String::new();
-        while let Ok(pos) = grab_rx.recv() {
-            let mut pos = pos;
-            while let Ok(newer) = grab_rx.try_recv() {
-                pos = newer;
+        // Target language `last_text` was grabbed under; a switch lets it through again
+        let mut last_target = String::new();
+        // Where and when `last_text` was last selected, for dedup_window_ms
+        let mut last_seen: Option<(SelectionPos, Instant)> = None;
+        loop {
+            let (mut pos, mut kind) = grab_queue_worker.pop();
+            while let Some(newer) = grab_queue_worker.try_pop() {
+                (pos, kind) = newer;
+            }
+            let generation = dismiss_generation_grab.load(Ordering::Relaxed);
+
+            if skip_secure_input && platform::is_secure_input_active() {
+                tracing::debug!("Secure input active, skipping selection grab");
+                continue;
+            }
+
+            if kind == RequestKind::Replace {
+                *saved_clipboard_grab.lock().unwrap() =
+                    arboard::Clipboard::new().ok().and_then(|mut c| c.get_text().ok());
+            }
+
+            // The hovered word when accessibility can read it; otherwise
+            // select it with a double-click, but only in plain text
+            let mut hovered = None;
+            if kind == RequestKind::Hover {
+                kind = RequestKind::Popup;
+                match platform::hover_target(pos.up_x, pos.up_y) {
+                    Some(platform::HoverTarget::Word(word)) => hovered = Some(word),
+                    Some(platform::HoverTarget::SelectableText) => {
+                        platform::select_word_at_cursor();
+                        // Give the app time to apply the selection before the copy
+                        std::thread::sleep(Duration::from_millis(150));
+                    }
+                    None => {
+                        tracing::debug!("No text under the pointer, hover ignored");
+                        continue;
+                    }
+                }
             }
 
-            if let Some(text) = grab_selection() {
-                let trimmed = text.trim().to_string();
-                if trimmed.len() >= 2 && trimmed.len() <= max_text_length && trimmed != last_text {
+            let direct_read = accessibility_selection
+                && !platform::frontmost_app_name().is_some_and(|app| {
+                    let app = app.to_lowercase();
+                    let stem = app.strip_suffix(".exe").unwrap_or(&app);
+                    accessibility_exclude.iter().any(|ex| *ex == app || ex == stem)
+                });
+
+            let grabbed = hovered.or_else(|| {
+                grab_selection(direct_read, clipboard_strategy, preserve_full_clipboard, remote_session)
+            });
+            if let Some(text) = grabbed {
+                let mut trimmed = text::join_lines(text.trim(), join_lines);
+                let mut target_override = None;
+                if inline_lang_markers {
+                    if let Some((code, rest)) = text::split_lang_marker(&trimmed) {
+                        tracing::info!("Inline marker, translating into \"{}\"", code);
+                        target_override = Some(code.to_string());
+                        trimmed = rest.to_string();
+                    }
+                }
+                let target = target_override.clone().unwrap_or_else(|| target_lang_grab.read().unwrap().clone());
+                let mut repeat = trimmed == last_text && target == last_target;
+                if repeat && !dedup_window.is_zero() {
+                    // Only a re-fire of the same selection counts; restart the window
+                    repeat = last_seen.is_some_and(|(last_pos, at)| {
+                        at.elapsed() < dedup_window && pos.is_near(&last_pos, dedup_tolerance)
+                    });
+                    last_seen = Some((pos, Instant::now()));
+                }
+                // An explicit replace always goes through, even for the same text
+                let is_new = kind == RequestKind::Replace || !repeat;
+                let filter = selection_filter_grab.read().unwrap().clone();
+                if trimmed.len() >= 2 && trimmed.len() <= filter.max_text_length && is_new {
+                    if kind == RequestKind::Popup
+                        && !text::meets_min_length(&trimmed, filter.min_words, filter.min_cjk_chars)
+                    {
+                        tracing::debug!("Selection shorter than min_words, skipping");
+                        continue;
+                    }
+                    if !filter.trigger_scripts.is_empty()
+                        && kind == RequestKind::Popup
+                        && !text::contains_any_script(&trimmed, &filter.trigger_scripts)
+                    {
+                        tracing::debug!("Selection has none of trigger_scripts, skipping");
+                        continue;
+                    }
+                    if filter.require_foreign_script && kind == RequestKind::Popup {
+                        let native = if filter.native_scripts.is_empty() {
+                            text::Script::for_language(&target)
+                        } else {
+                            &filter.native_scripts
+                        };
+                        if text::is_only_in_scripts(&trimmed, native) {
+                            tracing::debug!("Selection is in a native script, skipping");
+                            continue;
+                        }
+                    }
                     let preview: String = trimmed.chars().take(50).collect();
                     tracing::info!("Selection: \"{}\"", preview);
                     last_text = trimmed.clone();
-                    let _ = text_tx_clone.send(TranslationRequest { text: trimmed, pos });
+                    last_target = target;
+                    last_seen = Some((pos, Instant::now()));
+                    if kind == RequestKind::Collect {
+                        let _ = collected_tx.send(trimmed);
+                        continue;
+                    }
+                    if dismiss_generation_grab.load(Ordering::Relaxed) != generation {
+                        tracing::debug!("Dismissed during the grab, skipping");
+                        continue;
+                    }
+                    text_queue_grab.push(TranslationRequest {
+                        text: trimmed,
+                        pos,
+                        kind,
+                        langs: None,
+                        target: target_override,
+                    });
                 }
             }
         }
     });
 
-    let _hook = match platform::install_mouse_hook() {
+    register_hotkey(HotkeyAction::ReplaceInPlace, &config.replace_hotkey);
+    register_hotkey(HotkeyAction::DismissAll, &config.panic_hotkey);
+    register_hotkey(HotkeyAction::RoundTrip, &config.round_trip_hotkey);
+    register_hotkey(HotkeyAction::Paragraph, &config.paragraph_hotkey);
+    register_hotkey(HotkeyAction::Collect, &config.collect_hotkey);
+    register_hotkey(HotkeyAction::HistoryPrev, &config.history_prev_hotkey);
+    register_hotkey(HotkeyAction::HistoryNext, &config.history_next_hotkey);
+
+    let trigger_button = platform::TriggerButton::from_config(&config.trigger_button)
+        .unwrap_or_else(|| {
+            tracing::warn!(
+                "Unknown trigger_button \"{}\", using left button",
+                config.trigger_button
+            );
+            platform::TriggerButton::Left
+        });
+
+    platform::set_suppress_modifiers(match config.suppress_modifier.trim() {
+        "" => 0,
+        name => hotkey::parse_modifier(name).unwrap_or_else(|| {
+            tracing::warn!("Unknown suppress_modifier \"{}\", ignoring", name);
+            0
+        }),
+    });
+
+    let hook = match platform::install_mouse_hook(trigger_button) {
         Ok(h) => h,
         Err(e) => {
             tracing::error!("{}", e);
+            #[cfg(target_os = "macos")]
+            platform::show_input_monitoring_denied(&tr_args("dialog.will_exit", &[("error", &e)]));
+            #[cfg(not(target_os = "macos"))]
             platform::show_error(
-                "Screen Translate — Permission Required",
-                &format!("{}\n\nThe app will now exit.\nOpening System Settings for you...", e),
+                tr("dialog.permission_title"),
+                &tr_args("dialog.will_exit", &[("error", &e)]),
             );
-            #[cfg(target_os = "macos")]
-            platform::open_input_monitoring_settings();
             return;
         }
     };
@@ -220,14 +872,7 @@ fn main() {
     #[cfg(target_os = "macos")]
     if !platform::check_accessibility() {
         tracing::warn!("Accessibility permission not granted — requesting...");
-        platform::show_error(
-            "Screen Translate — Permission Required",
-            "Accessibility permission is needed to copy selected text.\n\n\
-             Please enable this app in:\n\
-             System Settings > Privacy & Security > Accessibility\n\n\
-             Opening System Settings for you...\n\
-             After enabling, relaunch the app.",
-        );
+        platform::show_error(tr("dialog.permission_title"), tr("dialog.accessibility"));
         platform::open_accessibility_settings();
         return;
     }
@@ -236,27 +881,106 @@ fn main() {
     let mut pending_pos = SelectionPos { down_x: 0, down_y: 0, up_x: 0, up_y: 0 };
     let debounce_ms = Duration::from_millis(config.poll_interval_ms.max(50));
     let mut last_click_time: Option<Instant> = None;
+    // Mouse events until then are our own simulated clicks
+    let mut ignore_mouse_until: Option<Instant> = None;
+    let event_stream = config
+        .event_socket
+        .as_deref()
+        .filter(|path| !path.trim().is_empty())
+        .and_then(|path| match events::EventStream::start(path.trim()) {
+            Ok(stream) => Some(stream),
+            Err(e) => {
+                tracing::warn!("Event socket disabled: {}", e);
+                None
+            }
+        });
+
+    // The translation currently (or last) shown in the popup
+    let mut last_popup: Option<TranslationResult> = None;
+    // Queue order of the newest selection's translation shown so far
+    let mut newest_popup_seq: Option<u64> = None;
+    // Recent popup translations, oldest first, and which one is on screen
+    let history_size = config.history_size.max(1);
+    let mut history: VecDeque<TranslationResult> = VecDeque::with_capacity(history_size);
+    let mut history_index: Option<usize> = None;
+    // "Translate Text" window: text waiting for typing to pause, and the last
+    // text sent (older results are dropped)
+    let mut input_pending: Option<(String, Instant)> = None;
+    let mut input_sent: Option<String> = None;
+    // Collect mode: the selections gathered so far, `None` when off
+    let mut collected: Option<Vec<String>> = None;
     let mut last_click_x: i32 = 0;
     let mut last_click_y: i32 = 0;
     let dblclick_ms = platform::get_double_click_time_ms();
+    let mut selection_threshold_x = config.selection_threshold_x;
+    let mut selection_threshold_y = config.selection_threshold_y;
+    let translate_on_double_click = config.translate_on_double_click;
+    let toast_on_toggle = config.toast_on_toggle;
+    let hover_dwell = config.hover_translate.then(|| Duration::from_millis(config.hover_dwell_ms));
+    // Where the pointer came to rest and since when; `true` once it fired there
+    let mut hover_rest: Option<((i32, i32), Instant, bool)> = None;
+    let retranslate_on_lang_change = config.retranslate_on_lang_change;
+    let mut min_drag_hold_ms = config.min_drag_hold_ms;
+    // target_lang from "Reload Config", switched to like a tray language pick
+    let mut reloaded_lang: Option<String> = None;
+    let debug_overlay = config.debug_overlay;
+    let auto_pause_fullscreen = config.auto_pause_fullscreen;
+    // Whether the last selection was ignored for a full-screen app, to log the
+    // pause and resume once each
+    let mut fullscreen_paused = false;
+    let popup_only_if_changed = config.popup_only_if_changed;
+    let active_monitors: Vec<usize> = config
+        .active_monitors
+        .split(',')
+        .filter(|s| !s.trim().is_empty())
+        .filter_map(|s| {
+            let index = s.trim().parse().ok().filter(|&i| i > 0);
+            if index.is_none() {
+                tracing::warn!("Invalid monitor \"{}\" in active_monitors, ignoring", s.trim());
+            }
+            index
+        })
+        .collect();
 
     let monitoring_ref = monitoring.clone();
-    loop {
+    let mut relaunch = None;
+    'main: loop {
         while let Some(event) = platform::poll_mouse_event() {
             match event {
                 MouseEvent::Quit => {
-                    tracing::info!("Screen Translate exiting");
-                    return;
+                    config::Config::save_monitoring_enabled(monitoring_ref.load(Ordering::Relaxed));
+                    break 'main;
                 }
-                MouseEvent::SelectionDone { down_x, down_y, up_x, up_y } => {
+                MouseEvent::SelectionDone { .. } | MouseEvent::Click
+                    if ignore_mouse_until.is_some_and(|t| Instant::now() < t) => {}
+                MouseEvent::SelectionDone { down_x, down_y, up_x, up_y, held_ms } => {
+                    if debug_overlay {
+                        tracing::info!(
+                            "Selection ({}, {}) -> ({}, {}), held {}ms",
+                            down_x, down_y, up_x, up_y, held_ms
+                        );
+                        platform::show_selection_rect(SelectionPos { down_x, down_y, up_x, up_y });
+                    }
+                    if !on_active_monitor(&active_monitors, up_x, up_y) {
+                        continue;
+                    }
+                    if auto_pause_fullscreen && fullscreen_active(&mut fullscreen_paused) {
+                        continue;
+                    }
                     if monitoring_ref.load(Ordering::Relaxed) {
                         pending_pos = SelectionPos { down_x, down_y, up_x, up_y };
                         let dx = (up_x - down_x).abs();
                         let dy = (up_y - down_y).abs();
 
-                        if dx > 5 || dy > 5 {
-                            debounce_start = Some(Instant::now());
-                        } else {
+                        let is_drag = dx > selection_threshold_x || dy > selection_threshold_y;
+                        if is_drag {
+                            // A quick flick is more likely a drag-scroll than a selection
+                            if held_ms >= min_drag_hold_ms {
+                                debounce_start = Some(Instant::now());
+                            } else {
+                                tracing::debug!("Drag held {}ms, below min_drag_hold_ms", held_ms);
+                            }
+                        } else if translate_on_double_click {
                             let now = Instant::now();
                             let is_dblclick = if let Some(prev) = last_click_time {
                                 let dt = now.duration_since(prev).as_millis() as u64;
@@ -280,26 +1004,277 @@ fn main() {
                 MouseEvent::Click => {
                     platform::on_click_away();
                 }
+                MouseEvent::Hotkey(id) => match HotkeyAction::from_id(id) {
+                    Some(HotkeyAction::ReplaceInPlace) => {
+                        debounce_start = None;
+                        grab_queue.push((pending_pos, RequestKind::Replace));
+                    }
+                    Some(HotkeyAction::DismissAll) => {
+                        debounce_start = None;
+                        platform::hide_popup_now();
+                        // Translations still running are dropped when they arrive
+                        dismiss_generation.fetch_add(1, Ordering::Relaxed);
+                        while text_queue.try_pop().is_some() {}
+                        while result_rx.try_recv().is_ok() {}
+                        last_popup = None;
+                    }
+                    Some(HotkeyAction::RoundTrip) => match &last_popup {
+                        Some(shown) if shown.source != "auto" => {
+                            text_queue.push(TranslationRequest {
+                                text: shown.translated.clone(),
+                                pos: shown.pos,
+                                kind: RequestKind::RoundTrip,
+                                langs: Some((shown.target.clone(), shown.source.clone())),
+                                target: None,
+                            });
+                        }
+                        Some(_) => tracing::info!("Round trip skipped, source language unknown"),
+                        None => {}
+                    },
+                    Some(HotkeyAction::Paragraph) => {
+                        let (x, y) = platform::select_paragraph_at_cursor();
+                        ignore_mouse_until = Some(Instant::now() + Duration::from_millis(500));
+                        // The debounce gives the app time to apply the selection before the copy
+                        pending_pos = SelectionPos { down_x: x, down_y: y, up_x: x, up_y: y };
+                        debounce_start = Some(Instant::now());
+                    }
+                    Some(HotkeyAction::Collect) => match collected.take() {
+                        None => {
+                            tracing::info!("Collect mode on");
+                            collected = Some(Vec::new());
+                            platform::show_popup(
+                                "",
+                                &collect_list_text(&[]),
+                                None,
+                                config.popup_duration_secs,
+                                pending_pos,
+                                platform::PopupContent::Translation,
+                            );
+                        }
+                        Some(items) if items.is_empty() => {
+                            tracing::info!("Collect mode off, nothing collected");
+                            platform::hide_popup_now();
+                        }
+                        Some(items) => {
+                            tracing::info!("Translating {} collected selections", items.len());
+                            text_queue.push(TranslationRequest {
+                                text: items.join("\n"),
+                                pos: pending_pos,
+                                kind: RequestKind::Batch,
+                                langs: None,
+                                target: None,
+                            });
+                        }
+                    },
+                    Some(action @ (HotkeyAction::HistoryPrev | HotkeyAction::HistoryNext)) => {
+                        let Some(newest) = history.len().checked_sub(1) else {
+                            continue;
+                        };
+                        let index = match (action, history_index) {
+                            (_, None) => newest,
+                            (HotkeyAction::HistoryPrev, Some(i)) => i.saturating_sub(1),
+                            (_, Some(i)) => (i + 1).min(newest),
+                        };
+                        let entry = &history[index];
+                        platform::show_popup(
+                            &entry.original,
+                            &popup_text(entry),
+                            entry.secondary.as_deref(),
+                            config.popup_duration_secs,
+                            entry.pos,
+                            platform::PopupContent::Translation,
+                        );
+                        history_index = Some(index);
+                        last_popup = Some(entry.clone());
+                    }
+                    None => {}
+                },
+            }
+        }
+
+        if let Some(dwell) = hover_dwell.filter(|_| monitoring_ref.load(Ordering::Relaxed)) {
+            match platform::hover_point() {
+                Some(point) => match &mut hover_rest {
+                    Some((rest, since, fired)) if is_near_point(*rest, point) => {
+                        if !*fired && since.elapsed() >= dwell && debounce_start.is_none() {
+                            *fired = true;
+                            let (x, y) = point;
+                            let allowed = on_active_monitor(&active_monitors, x, y)
+                                && !(auto_pause_fullscreen && fullscreen_active(&mut fullscreen_paused));
+                            if allowed {
+                                // A fallback double-click must not count as a selection
+                                ignore_mouse_until = Some(Instant::now() + Duration::from_millis(1000));
+                                pending_pos = SelectionPos { down_x: x, down_y: y, up_x: x, up_y: y };
+                                grab_queue.push((pending_pos, RequestKind::Hover));
+                            }
+                        }
+                    }
+                    _ => hover_rest = Some((point, Instant::now(), false)),
+                },
+                None => hover_rest = None,
             }
         }
 
         if let Some(start) = debounce_start {
             if start.elapsed() >= debounce_ms {
                 debounce_start = None;
-                let _ = grab_tx.send(pending_pos);
+                let kind = if collected.is_some() { RequestKind::Collect } else { RequestKind::Popup };
+                grab_queue.push((pending_pos, kind));
+            }
+        }
+
+        while let Ok(text) = collected_rx.try_recv() {
+            // A late grab after collect mode ended is dropped
+            if let Some(items) = &mut collected {
+                // One line per selection, so the batch translation lines up
+                items.push(text.split_whitespace().collect::<Vec<_>>().join(" "));
+                platform::show_popup(
+                    "",
+                    &collect_list_text(items),
+                    None,
+                    config.popup_duration_secs,
+                    pending_pos,
+                    platform::PopupContent::Translation,
+                );
+            }
+        }
+
+        let input_ready = match platform::poll_input_event() {
+            Some(platform::InputEvent::Changed(text)) => {
+                input_pending = Some((text, Instant::now()));
+                None
+            }
+            Some(platform::InputEvent::Submitted(text)) => {
+                input_pending = None;
+                Some(text)
+            }
+            None => input_pending
+                .take_if(|(_, changed)| changed.elapsed() >= INPUT_DEBOUNCE)
+                .map(|(text, _)| text),
+        };
+        if let Some(text) = input_ready {
+            let text = text.trim().to_string();
+            if text.is_empty() {
+                platform::set_input_result("");
+                input_sent = None;
+            } else if input_sent.as_deref() != Some(text.as_str()) {
+                input_sent = Some(text.clone());
+                text_queue.push(TranslationRequest {
+                    text,
+                    pos: pending_pos,
+                    kind: RequestKind::Input,
+                    langs: None,
+                    target: None,
+                });
             }
         }
 
-        while let Ok(result) = result_rx.try_recv() {
+        while let Ok(mut result) = result_rx.try_recv() {
+            if result.generation != dismiss_generation.load(Ordering::Relaxed) {
+                tracing::debug!("Dropping a translation requested before a dismiss");
+                continue;
+            }
             let orig_preview: String = result.original.chars().take(40).collect();
             let trans_preview: String = result.translated.chars().take(40).collect();
             tracing::info!("\"{}\" -> \"{}\"", orig_preview, trans_preview);
-            platform::show_popup(
-                &result.original,
-                &result.translated,
-                config.popup_duration_secs,
-                result.pos,
-            );
+            if let Some(stream) = &event_stream {
+                if result.kind != RequestKind::Test {
+                    stream.publish(&result);
+                }
+            }
+            if result.kind == RequestKind::Test {
+                if result.failed {
+                    platform::show_error(tr("dialog.test_failed_title"), &result.translated);
+                } else {
+                    platform::show_info(
+                        tr("dialog.test_title"),
+                        &tr_args(
+                            "dialog.test_ok",
+                            &[("original", &result.original), ("translated", &result.translated)],
+                        ),
+                    );
+                }
+                continue;
+            }
+            // A slow translation must not cover the popup of a newer selection
+            if result.kind == RequestKind::Popup {
+                if newest_popup_seq.is_some_and(|newest| result.seq < newest) {
+                    tracing::debug!("Dropping a translation older than the one on screen");
+                    continue;
+                }
+                newest_popup_seq = Some(result.seq);
+            }
+            if result.kind == RequestKind::Batch && !result.failed {
+                result.translated = pair_collected(&result.original, &result.translated);
+            }
+            if result.kind == RequestKind::Input {
+                if input_sent.as_deref() == Some(result.original.as_str()) {
+                    platform::set_input_result(&result.translated);
+                }
+                continue;
+            }
+            if result.kind == RequestKind::Replace && !result.failed {
+                // Pasting into a read-only view would silently do nothing; show it instead
+                if platform::is_focused_editable() != Some(false) {
+                    let restore = saved_clipboard.lock().unwrap().take();
+                    std::thread::spawn(move || replace_selection(&result.translated, restore));
+                    continue;
+                }
+                tracing::info!("Focused element is read-only, showing translation instead");
+            }
+            if result.kind == RequestKind::RoundTrip {
+                if let Some(shown) = &last_popup {
+                    platform::show_popup(
+                        &shown.original,
+                        &format!("{}\n\n↩ {}", shown.translated, result.translated),
+                        shown.secondary.as_deref(),
+                        config.popup_duration_secs,
+                        shown.pos,
+                        platform::PopupContent::Translation,
+                    );
+                }
+                continue;
+            }
+            if popup_only_if_changed
+                && result.kind == RequestKind::Popup
+                && !result.failed
+                && text::is_unchanged(&result.original, &result.translated)
+                && result.secondary.as_deref().is_none_or(|s| text::is_unchanged(&result.original, s))
+            {
+                tracing::info!("Translation same as the selection, no popup");
+                continue;
+            }
+            if display_mode == platform::DisplayMode::Sidebar {
+                // No room for a separate secondary block; it goes under the translation
+                let mut text = popup_text(&result);
+                if let Some(secondary) = &result.secondary {
+                    text.push('\n');
+                    text.push_str(secondary);
+                }
+                platform::show_in_sidebar(&result.original, &text);
+            } else {
+                platform::show_popup(
+                    &result.original,
+                    &popup_text(&result),
+                    result.secondary.as_deref(),
+                    config.popup_duration_secs,
+                    result.pos,
+                    if result.failed {
+                        platform::PopupContent::Error
+                    } else {
+                        platform::PopupContent::Translation
+                    },
+                );
+            }
+            if !result.failed {
+                if history.len() >= history_size {
+                    history.pop_front();
+                }
+                history.push_back(result.clone());
+                history_index = Some(history.len() - 1);
+            }
+            // Kept so a round trip knows what is on screen and its language pair
+            last_popup = (!result.failed).then_some(result);
         }
 
         // Check for language download completions
@@ -308,59 +1283,140 @@ fn main() {
                 tray.mark_language_installed(&lang_code);
                 platform::show_info(
                     "Screen Translate",
-                    &format!("{} language model installed.\nRestart the app to use it.", tray::lang_display_name_pub(&lang_code)),
+                    &tr_args("dialog.language_installed", &[("language", &tray::lang_display_name_pub(&lang_code))]),
                 );
             } else {
                 platform::show_error(
                     "Screen Translate",
-                    &format!("Failed to download {} language model.\nCheck your internet connection.", tray::lang_display_name_pub(&lang_code)),
+                    &tr_args("dialog.language_failed", &[("language", &tray::lang_display_name_pub(&lang_code))]),
                 );
             }
         }
 
+        if let Some(started) = server_wait_start {
+            match server_status.load(Ordering::Relaxed) {
+                server::SERVER_READY => {
+                    server_wait_start = None;
+                    tray.set_tooltip(tr("tooltip.ready"));
+                    // Quick warm-ups only update the tooltip; a long first-run
+                    // model download deserves a visible heads-up
+                    if started.elapsed() >= SERVER_READY_NOTICE_AFTER {
+                        platform::show_info("Screen Translate", tr("dialog.ready"));
+                    }
+                }
+                server::SERVER_STARTING => {}
+                _ => {
+                    server_wait_start = None;
+                    tray.set_tooltip(tr("tooltip.unavailable"));
+                }
+            }
+        }
+
+        if server_status.load(Ordering::Relaxed) == server::SERVER_READY {
+            if let Some((api_url, api_key, headers, source_lang)) = server_langs_fetch.take() {
+                let tx = server_langs_tx.clone();
+                std::thread::spawn(move || match server::fetch_languages(&api_url, api_key.as_deref(), &headers) {
+                    Ok(langs) => {
+                        let _ = tx.send(server::supported_targets(&langs, &source_lang));
+                    }
+                    Err(e) => tracing::warn!("Failed to fetch server languages: {}", e),
+                });
+            }
+        }
+        while let Ok(supported) = server_langs_rx.try_recv() {
+            tracing::info!("Server supports {} target languages", supported.len());
+            // A local server can download missing models, so keep those selectable
+            tray.set_supported_languages(&supported, config.start_local_server);
+        }
+
         if let Some(notification) = update_notify.lock().unwrap().take() {
             match notification {
                 UpdateNotification::UpToDate => {
                     platform::show_info(
                         "Screen Translate",
-                        &format!(
-                            "You're running the latest version (v{}).",
-                            env!("CARGO_PKG_VERSION")
-                        ),
+                        &tr_args("dialog.up_to_date", &[("version", &env!("CARGO_PKG_VERSION"))]),
+                    );
+                }
+                UpdateNotification::RateLimited(retry_after) => {
+                    platform::show_info(
+                        "Screen Translate",
+                        &tr_args("dialog.update_rate_limited", &[("time", &retry_after)]),
                     );
                 }
                 UpdateNotification::Available(info) => {
                     tray.set_update_in_progress();
                     tracing::info!("Auto-installing update v{}...", info.version);
-                    std::thread::spawn(move || {
-                        if let Err(e) = updater::perform_update(&info) {
-                            tracing::error!("Update failed: {}", e);
+                    let notify = update_notify.clone();
+                    std::thread::spawn(move || match updater::perform_update(&info) {
+                        Ok(relaunch) => {
+                            *notify.lock().unwrap() = Some(UpdateNotification::Installed(relaunch));
                         }
+                        Err(e) => tracing::error!("Update failed: {}", e),
                     });
                 }
+                UpdateNotification::Installed(new_version) => {
+                    config::Config::save_monitoring_enabled(monitoring.load(Ordering::Relaxed));
+                    relaunch = Some(new_version);
+                    break 'main;
+                }
             }
         }
 
-        match tray.handle_menu_event() {
+        let (action, from_reload) = match reloaded_lang.take() {
+            Some(code) => (TrayAction::ChangeLanguage(code), true),
+            None => (tray.handle_menu_event(), false),
+        };
+        match action {
             TrayAction::Quit => {
                 tracing::info!("Quit requested");
-                break;
+                config::Config::save_monitoring_enabled(monitoring.load(Ordering::Relaxed));
+                break 'main;
             }
             TrayAction::ToggleMonitoring(active) => {
                 monitoring.store(active, Ordering::Relaxed);
                 tracing::info!("Monitoring: {}", active);
+                config::Config::save_monitoring_enabled(active);
+                // Already in effect, so "Reload Config" doesn't report it
+                applied_settings.insert("monitoring_enabled".into(), toml::Value::Boolean(active));
+                if toast_on_toggle {
+                    platform::show_status(tr(if active {
+                        "status.monitoring_resumed"
+                    } else {
+                        "status.monitoring_paused"
+                    }));
+                }
             }
             TrayAction::ChangeLanguage(code) => {
                 *target_lang.write().unwrap() = code.clone();
                 tracing::info!("Target language changed to: {}", code);
-                config::Config::save_target_lang(&code);
+                // The reloaded file already has it, or "system" for it
+                if !from_reload {
+                    config::Config::save_target_lang(&code);
+                    applied_settings.insert("target_lang".into(), toml::Value::String(code.clone()));
+                }
 
                 // If language isn't installed, download it in the background
                 let needs_download = !installed_languages.read().unwrap().contains(&code);
+                // Not while a model downloads (it would fail) or the collect list is up
+                let retranslate = retranslate_on_lang_change
+                    && !needs_download
+                    && collected.is_none()
+                    && platform::is_popup_visible();
+                if retranslate {
+                    if let Some(shown) = &last_popup {
+                        text_queue.push(TranslationRequest {
+                            text: shown.original.clone(),
+                            pos: shown.pos,
+                            kind: RequestKind::Popup,
+                            langs: None,
+                            target: None,
+                        });
+                    }
+                }
                 if needs_download {
                     platform::show_info(
                         "Screen Translate",
-                        &format!("Downloading {} language model...\nThis may take a minute.", tray::lang_display_name_pub(&code)),
+                        &tr_args("dialog.language_downloading", &[("language", &tray::lang_display_name_pub(&code))]),
                     );
                     let python_path = config.python_path.clone();
                     let lang_code = code.clone();
@@ -400,8 +1456,9 @@ fn main() {
             }
             TrayAction::CheckForUpdates => {
                 let notify = update_notify.clone();
+                let github_token = config.github_token.clone();
                 std::thread::spawn(move || {
-                    match updater::check_for_update() {
+                    match updater::check_for_update(github_token.as_deref()) {
                         Ok(Some(info)) => {
                             tracing::info!("Update available: v{}", info.version);
                             *notify.lock().unwrap() = Some(UpdateNotification::Available(info));
@@ -409,30 +1466,309 @@ fn main() {
                         Ok(None) => {
                             *notify.lock().unwrap() = Some(UpdateNotification::UpToDate);
                         }
-                        Err(e) => tracing::error!("Update check failed: {}", e),
+                        Err(e) => {
+                            if let Some(limited) = e.downcast_ref::<updater::RateLimited>() {
+                                tracing::warn!("Update check: {}", limited);
+                                *notify.lock().unwrap() =
+                                    Some(UpdateNotification::RateLimited(limited.retry_after()));
+                            } else {
+                                tracing::error!("Update check failed: {}", e);
+                            }
+                        }
                     }
                 });
             }
+            TrayAction::TestTranslation => {
+                tracing::info!("Sending test translation");
+                text_queue.push(TranslationRequest {
+                    text: TEST_PHRASE.to_string(),
+                    pos: pending_pos,
+                    kind: RequestKind::Test,
+                    langs: None,
+                    target: None,
+                });
+            }
+            TrayAction::ShowSettings => {
+                platform::show_info(tr("dialog.settings_title"), &settings_summary);
+            }
+            TrayAction::ExportSettings => {
+                if let Some(path) = platform::choose_save_path(SETTINGS_EXPORT_NAME) {
+                    match config::Config::export_settings(&path) {
+                        Ok(()) => platform::show_info(
+                            "Screen Translate",
+                            &tr_args("dialog.export_done", &[("path", &path.display())]),
+                        ),
+                        Err(e) => platform::show_error(
+                            "Screen Translate",
+                            &tr_args("dialog.export_failed", &[("error", &format!("{:#}", e))]),
+                        ),
+                    }
+                }
+            }
+            TrayAction::ImportSettings => {
+                if let Some(path) = platform::choose_open_path() {
+                    match config::Config::import_settings(&path) {
+                        Ok(()) => platform::show_info("Screen Translate", tr("dialog.import_done")),
+                        Err(e) => platform::show_error(
+                            "Screen Translate",
+                            &tr_args("dialog.import_failed", &[("error", &format!("{:#}", e))]),
+                        ),
+                    }
+                }
+            }
+            TrayAction::ToggleVerboseLogging(verbose) => set_verbose_logging(verbose),
+            TrayAction::ReloadConfig => match config::Config::load() {
+                Ok(mut reloaded) => {
+                    reloaded.validate();
+                    resolve_system_target_lang(&mut reloaded);
+                    let (applied, pending) =
+                        reload_config(&reloaded, &mut applied_settings, &selection_filter);
+                    selection_threshold_x = reloaded.selection_threshold_x;
+                    selection_threshold_y = reloaded.selection_threshold_y;
+                    min_drag_hold_ms = reloaded.min_drag_hold_ms;
+                    settings_summary = reloaded.summary();
+                    if reloaded.monitoring_enabled != monitoring.load(Ordering::Relaxed) {
+                        monitoring.store(reloaded.monitoring_enabled, Ordering::Relaxed);
+                        tray.monitor_item.set_checked(reloaded.monitoring_enabled);
+                        tracing::info!("Monitoring: {}", reloaded.monitoring_enabled);
+                    }
+                    if *target_lang.read().unwrap() != reloaded.target_lang {
+                        // Through ChangeLanguage next pass, so a missing model downloads
+                        tray.set_target_language(&reloaded.target_lang);
+                        reloaded_lang = Some(reloaded.target_lang);
+                    }
+                    platform::show_info(
+                        "Screen Translate",
+                        &tr_args("dialog.reload_done", &[("applied", &applied), ("pending", &pending)]),
+                    );
+                }
+                Err(e) => {
+                    tracing::error!("Failed to reload config: {:#}", e);
+                    platform::show_error(
+                        "Screen Translate",
+                        &tr_args("dialog.reload_failed", &[("error", &format!("{:#}", e))]),
+                    );
+                }
+            },
+            TrayAction::TranslateText => {
+                input_sent = None;
+                platform::open_input_window();
+            }
             TrayAction::None => {}
         }
 
         std::thread::sleep(Duration::from_millis(10));
     }
 
+    shutdown(hook, local_server, relaunch);
+}
+
+/// Orderly teardown for Quit and update relaunches: release the input hook,
+/// stop the local LibreTranslate (its `Drop` kills the child), start the
+/// updated app if there is one, then flush logs.
+fn shutdown(
+    hook: platform::HookHandle,
+    local_server: Option<server::LibreTranslateServer>,
+    relaunch: Option<updater::Relaunch>,
+) {
+    drop(hook);
+    drop(local_server);
+    if let Some(relaunch) = relaunch {
+        tracing::info!("Starting the updated version");
+        if let Err(e) = relaunch.spawn() {
+            tracing::error!("{}", e);
+        }
+    }
     tracing::info!("Screen Translate exiting");
+    let _ = std::io::Write::flush(&mut std::io::stdout());
+}
+
+/// The collect-mode list shown while selections are being gathered.
+fn collect_list_text(items: &[String]) -> String {
+    let mut text = tr_args("collect.title", &[("count", &items.len())]);
+    for item in items {
+        text.push_str("\n• ");
+        text.push_str(item);
+    }
+    text
+}
+
+/// One "original → translation" line per collected selection. Falls back to
+/// the plain translation if the backend didn't keep one line per selection.
+fn pair_collected(original: &str, translated: &str) -> String {
+    let translations: Vec<&str> = translated.lines().collect();
+    if original.lines().count() != translations.len() {
+        return translated.to_string();
+    }
+    original
+        .lines()
+        .zip(translations)
+        .map(|(o, t)| format!("{} → {}", o.trim(), t.trim()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// What the popup shows for `result`: the translation, or with
+/// `compare_backends` every backend's translation under its name.
+fn popup_text(result: &TranslationResult) -> String {
+    if result.comparisons.is_empty() {
+        return result.translated.clone();
+    }
+    result
+        .comparisons
+        .iter()
+        .map(|c| format!("▸ {}\n{}", c.backend, c.text))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Register the hotkey for `action` from its config value; empty disables it.
+fn register_hotkey(action: HotkeyAction, value: &str) {
+    if value.trim().is_empty() {
+        return;
+    }
+    match hotkey::Hotkey::parse(value)
+        .and_then(|hk| platform::register_hotkey(action as u32, &hk))
+    {
+        Ok(()) => tracing::info!("Hotkey {} -> {:?}", value, action),
+        Err(e) => tracing::warn!("Hotkey for {:?} disabled: {}", action, e),
+    }
+}
+
+/// Paste `text` over the current selection, then put the user's clipboard back.
+fn replace_selection(text: &str, restore: Option<String>) {
+    let Ok(mut clip) = arboard::Clipboard::new() else {
+        return;
+    };
+    if clip.set_text(text).is_err() {
+        return;
+    }
+    platform::send_paste_command();
+    // Give the target app time to read the clipboard before restoring it
+    std::thread::sleep(Duration::from_millis(300));
+    let _ = match restore {
+        Some(previous) => clip.set_text(previous),
+        None => clip.clear(),
+    };
 }
 
-fn grab_selection() -> Option<String> {
+/// Get the selected text: from the accessibility API when `direct_read` is set
+/// and the app supports it, otherwise by simulating Ctrl/Cmd+C, detecting the
+/// copy as `strategy` says. With `preserve_full` everything on the clipboard is put back afterwards, not just its text.
+/// `remote` allows for the slow clipboard sync of a remote desktop.
+fn grab_selection(
+    direct_read: bool,
+    strategy: ClipboardStrategy,
+    preserve_full: bool,
+    remote: bool,
+) -> Option<String> {
+    if direct_read {
+        if let Some(text) = platform::read_selected_text() {
+            return Some(text);
+        }
+    }
+
     let mut clip = arboard::Clipboard::new().ok()?;
-    let _ = clip.set_text(String::new());
+    let previous = clip.get_text().ok();
+    if preserve_full {
+        platform::save_clipboard();
+    }
+    let change_count = platform::clipboard_change_count();
+    if strategy == ClipboardStrategy::ClearAndRestore {
+        let _ = clip.set_text(String::new());
+    }
 
+    let before_copy = platform::clipboard_change_count();
     platform::send_copy_command();
+    if remote {
+        wait_for_remote_copy(before_copy);
+    } else {
+        std::thread::sleep(Duration::from_millis(80));
+    }
+    let read = |clip: &mut arboard::Clipboard| {
+        if remote {
+            read_remote_copied_text(clip)
+        } else {
+            read_copied_text(clip)
+        }
+    };
+
+    let text = match strategy {
+        ClipboardStrategy::ClearAndRestore => read(&mut clip),
+        ClipboardStrategy::ChangeCount => {
+            if platform::clipboard_change_count() == change_count {
+                tracing::debug!("Clipboard unchanged after copy, nothing selected");
+                return None;
+            }
+            read(&mut clip)
+        }
+        ClipboardStrategy::NoClear => read(&mut clip).filter(|t| Some(t) != previous.as_ref()),
+    };
+
+    if preserve_full {
+        platform::restore_clipboard();
+    } else if platform::clipboard_change_count() != change_count {
+        // The clear or the copy replaced it, whatever the strategy. Only
+        // text can be put back; anything else was lost
+        if let Some(previous) = previous {
+            let _ = clip.set_text(previous);
+        }
+    }
+    text
+}
+
+/// Longest wait for a copy to reach the clipboard from a remote session.
+const REMOTE_COPY_TIMEOUT: Duration = Duration::from_millis(2000);
+/// Clipboard check interval while waiting on a remote copy.
+const REMOTE_COPY_POLL: Duration = Duration::from_millis(25);
+
+/// Wait until the clipboard changes from `before` (the copy arrived) or
+/// `REMOTE_COPY_TIMEOUT` passes, then a little longer: the remote side may
+/// still be adding formats.
+fn wait_for_remote_copy(before: u64) {
+    let deadline = Instant::now() + REMOTE_COPY_TIMEOUT;
+    while platform::clipboard_change_count() == before {
+        if Instant::now() >= deadline {
+            tracing::debug!("No clipboard change within {:?} of the remote copy", REMOTE_COPY_TIMEOUT);
+            return;
+        }
+        std::thread::sleep(REMOTE_COPY_POLL);
+    }
     std::thread::sleep(Duration::from_millis(80));
+}
 
-    let new_text = clip.get_text().ok();
+/// `read_copied_text` for a remote session, retried a few times: the remote
+/// desktop client fetches the data on first read, which can fail while the
+/// transfer is under way.
+fn read_remote_copied_text(clip: &mut arboard::Clipboard) -> Option<String> {
+    for attempt in 0..4 {
+        if attempt > 0 {
+            std::thread::sleep(Duration::from_millis(150));
+        }
+        if let Some(text) = read_copied_text(clip) {
+            return Some(text);
+        }
+    }
+    None
+}
+
+/// Text put on the clipboard by the simulated copy, from plain text or a rich flavor.
+fn read_copied_text(clip: &mut arboard::Clipboard) -> Option<String> {
+    if let Some(t) = clip.get_text().ok().filter(|t| !t.is_empty()) {
+        return Some(t);
+    }
 
-    match new_text {
-        Some(t) if !t.is_empty() => Some(t),
-        _ => None,
+    // Some apps only copy a rich flavor; recover its text
+    let rich = clip
+        .get()
+        .html()
+        .ok()
+        .map(|html| text::html_to_text(&html))
+        .filter(|t| !t.trim().is_empty())
+        .or_else(|| platform::read_clipboard_rtf().map(|rtf| text::rtf_to_text(&rtf)))
+        .filter(|t| !t.trim().is_empty());
+    if rich.is_some() {
+        tracing::debug!("Selection recovered from rich clipboard content");
     }
+    rich
 }
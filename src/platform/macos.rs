@@ -1,22 +1,33 @@
-use std::ffi::c_void;
-use std::sync::atomic::{AtomicBool, AtomicI32, AtomicPtr, Ordering};
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CStr};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicPtr, AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::Mutex;
 use std::time::Instant;
 
 use objc2::rc::Retained;
-use objc2::runtime::AnyClass;
+use objc2::runtime::{AnyClass, ProtocolObject};
 use objc2::{msg_send, MainThreadOnly};
 use objc2_app_kit::{
     NSAlert, NSAlertStyle, NSApplication, NSBackingStoreType, NSColor, NSEvent,
-    NSEventMask, NSFont, NSPanel, NSScreen, NSTextField, NSView,
-    NSVisualEffectBlendingMode, NSVisualEffectMaterial, NSVisualEffectState,
-    NSVisualEffectView, NSWindowStyleMask,
+    NSEventMask, NSEventType, NSFont, NSOpenPanel, NSPanel, NSPasteboard, NSPasteboardItem,
+    NSPasteboardWriting, NSSavePanel, NSScreen,
+    NSScrollView, NSTextAlignment, NSTextField, NSView, NSVisualEffectBlendingMode, NSVisualEffectMaterial,
+    NSVisualEffectState, NSVisualEffectView, NSWindowStyleMask, NSWorkspace,
 };
 use objc2_foundation::{
-    MainThreadMarker, NSPoint, NSRect, NSSize, NSString,
+    MainThreadMarker, NSArray, NSData, NSLocale, NSPoint, NSRect, NSSize, NSString,
 };
 
 use crate::clipboard::SelectionPos;
-use super::MouseEvent;
+use super::{
+    anim_progress, auto_hide_ms, configured_font_for, lifetime_exceeded, min_popup_width,
+    plain_renderer, popup_options, sticky_popup_size, suppress_modifiers, text_align_for,
+    DisplayChange, HoverTarget, InputEvent, MouseEvent, Placement, PopupContent, PopupMaterial,
+    TextAlign, TriggerButton, SELECTION_RECT_DURATION, SIDEBAR_MAX_ENTRIES,
+};
+use crate::hotkey::{self, Hotkey, Key};
+use crate::i18n::tr;
 
 // ---------------------------------------------------------------------------
 // CoreGraphics / CoreFoundation FFI
@@ -33,6 +44,8 @@ type CGEventMask = u64;
 type CGEventType = u32;
 type CGEventFlags = u64;
 type CGKeyCode = u16;
+type AXUIElementRef = *mut c_void;
+type CFTypeRef = *mut c_void;
 
 #[repr(C)]
 #[derive(Clone, Copy)]
@@ -41,17 +54,51 @@ struct CGPoint {
     y: f64,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct CFRange {
+    location: isize,
+    length: isize,
+}
+
 const K_CG_HID_EVENT_TAP: u32 = 0; // kCGHIDEventTap
 const K_CG_HEAD_INSERT_EVENT_TAP: u32 = 0; // kCGHeadInsertEventTap
 const K_CG_EVENT_TAP_OPTION_LISTEN_ONLY: u32 = 1;
 
 const K_CG_EVENT_LEFT_MOUSE_DOWN: CGEventType = 1;
 const K_CG_EVENT_LEFT_MOUSE_UP: CGEventType = 2;
+const K_CG_EVENT_RIGHT_MOUSE_DOWN: CGEventType = 3;
+const K_CG_EVENT_RIGHT_MOUSE_UP: CGEventType = 4;
+const K_CG_EVENT_OTHER_MOUSE_DOWN: CGEventType = 25;
+const K_CG_EVENT_OTHER_MOUSE_UP: CGEventType = 26;
+const K_CG_EVENT_KEY_DOWN: CGEventType = 10;
 const K_CG_EVENT_TAP_DISABLED_BY_TIMEOUT: CGEventType = 0xFFFFFFFE;
 
+const K_CG_EVENT_FLAG_MASK_SHIFT: CGEventFlags = 1 << 17;
+const K_CG_EVENT_FLAG_MASK_CONTROL: CGEventFlags = 1 << 18;
+const K_CG_EVENT_FLAG_MASK_ALTERNATE: CGEventFlags = 1 << 19;
 const K_CG_EVENT_FLAG_MASK_COMMAND: CGEventFlags = 1 << 20;
+const HOTKEY_FLAG_MASK: CGEventFlags = K_CG_EVENT_FLAG_MASK_SHIFT
+    | K_CG_EVENT_FLAG_MASK_CONTROL
+    | K_CG_EVENT_FLAG_MASK_ALTERNATE
+    | K_CG_EVENT_FLAG_MASK_COMMAND;
+
+const K_CG_KEYBOARD_EVENT_AUTOREPEAT: u32 = 8; // kCGKeyboardEventAutorepeat
+const K_CG_KEYBOARD_EVENT_KEYCODE: u32 = 9; // kCGKeyboardEventKeycode
+const K_CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+const K_CF_NUMBER_CF_INDEX_TYPE: u32 = 14; // kCFNumberCFIndexType
+const K_AX_VALUE_CG_POINT_TYPE: u32 = 1; // kAXValueCGPointType
+const K_AX_VALUE_CF_RANGE_TYPE: u32 = 4; // kAXValueCFRangeType
+
+const K_CG_MOUSE_EVENT_BUTTON_NUMBER: u32 = 3; // kCGMouseEventButtonNumber
+const K_CG_MOUSE_EVENT_CLICK_STATE: u32 = 1; // kCGMouseEventClickState
+const K_CG_MOUSE_BUTTON_LEFT: u32 = 0;
+const MIDDLE_BUTTON_NUMBER: i64 = 2;
 
 const KEYCODE_C: CGKeyCode = 8;
+const KEYCODE_V: CGKeyCode = 9;
+const KEYCODE_RETURN: CGKeyCode = 36;
+const KEYCODE_ESCAPE: CGKeyCode = 53;
 
 type CGEventTapCallBack = unsafe extern "C" fn(
     proxy: CGEventTapProxy,
@@ -63,6 +110,36 @@ type CGEventTapCallBack = unsafe extern "C" fn(
 #[link(name = "ApplicationServices", kind = "framework")]
 extern "C" {
     fn AXIsProcessTrusted() -> bool;
+    fn AXUIElementCreateSystemWide() -> AXUIElementRef;
+    fn AXUIElementCopyAttributeValue(
+        element: AXUIElementRef,
+        attribute: CFStringRef,
+        value: *mut CFTypeRef,
+    ) -> i32;
+    fn AXUIElementIsAttributeSettable(
+        element: AXUIElementRef,
+        attribute: CFStringRef,
+        settable: *mut u8,
+    ) -> i32;
+    fn AXUIElementCopyElementAtPosition(
+        application: AXUIElementRef,
+        x: f32,
+        y: f32,
+        element: *mut AXUIElementRef,
+    ) -> i32;
+    fn AXUIElementCopyParameterizedAttributeValue(
+        element: AXUIElementRef,
+        attribute: CFStringRef,
+        parameter: CFTypeRef,
+        value: *mut CFTypeRef,
+    ) -> i32;
+    fn AXValueCreate(value_type: u32, value: *const c_void) -> CFTypeRef;
+    fn AXValueGetValue(value: CFTypeRef, value_type: u32, out: *mut c_void) -> bool;
+}
+
+#[link(name = "Carbon", kind = "framework")]
+extern "C" {
+    fn IsSecureEventInputEnabled() -> u8;
 }
 
 extern "C" {
@@ -80,6 +157,9 @@ extern "C" {
     fn CGEventTapEnable(tap: CFMachPortRef, enable: bool);
 
     fn CGEventGetLocation(event: CGEventRef) -> CGPoint;
+    fn CGEventGetTimestamp(event: CGEventRef) -> u64;
+    fn CGEventGetIntegerValueField(event: CGEventRef, field: u32) -> i64;
+    fn CGEventGetFlags(event: CGEventRef) -> CGEventFlags;
 
     fn CGEventCreateKeyboardEvent(
         source: *const c_void,
@@ -87,6 +167,14 @@ extern "C" {
         key_down: bool,
     ) -> CGEventRef;
     fn CGEventSetFlags(event: CGEventRef, flags: CGEventFlags);
+    fn CGEventCreate(source: *const c_void) -> CGEventRef;
+    fn CGEventCreateMouseEvent(
+        source: *const c_void,
+        mouse_type: CGEventType,
+        position: CGPoint,
+        button: u32,
+    ) -> CGEventRef;
+    fn CGEventSetIntegerValueField(event: CGEventRef, field: u32, value: i64);
     fn CGEventPost(tap: u32, event: CGEventRef);
 
     fn CFMachPortCreateRunLoopSource(
@@ -102,14 +190,41 @@ extern "C" {
     fn CFRunLoopRunInMode(mode: CFStringRef, seconds: f64, return_after: bool) -> i32;
 
     fn CFRelease(cf: *mut c_void);
+    fn CFStringCreateWithCString(
+        alloc: CFAllocatorRef,
+        c_str: *const c_char,
+        encoding: u32,
+    ) -> CFStringRef;
+    fn CFStringGetLength(s: CFStringRef) -> isize;
+    fn CFStringGetMaximumSizeForEncoding(length: isize, encoding: u32) -> isize;
+    fn CFStringGetCString(s: CFStringRef, buf: *mut c_char, size: isize, encoding: u32) -> bool;
+    fn CFStringGetTypeID() -> usize;
+    fn CFGetTypeID(cf: CFTypeRef) -> usize;
+    fn CFBooleanGetTypeID() -> usize;
+    fn CFBooleanGetValue(boolean: CFTypeRef) -> bool;
+    fn CFNumberGetValue(number: CFTypeRef, number_type: u32, out: *mut c_void) -> bool;
 
     static kCFRunLoopDefaultMode: CFStringRef;
 
     fn CGWindowLevelForKey(key: i32) -> i32;
+
+    fn CGDisplayRegisterReconfigurationCallback(
+        callback: CGDisplayReconfigurationCallBack,
+        user_info: *mut c_void,
+    ) -> i32;
 }
 
+type CGDisplayReconfigurationCallBack = unsafe extern "C" fn(u32, u32, *mut c_void);
+const K_CG_DISPLAY_BEGIN_CONFIGURATION_FLAG: u32 = 1;
+
 const K_CG_FLOATING_WINDOW_LEVEL_KEY: i32 = 5;
 
+const NS_ALERT_FIRST_BUTTON_RETURN: isize = 1000;
+const NS_MODAL_RESPONSE_OK: isize = 1;
+
+// Created in the app data folder once the system permission prompt has been shown
+const INPUT_MONITORING_PROMPTED_MARKER: &str = "input-monitoring-prompted";
+
 // ---------------------------------------------------------------------------
 // Atomic state (same pattern as Windows)
 // ---------------------------------------------------------------------------
@@ -120,6 +235,32 @@ static MOUSE_DOWN_X: AtomicI32 = AtomicI32::new(0);
 static MOUSE_DOWN_Y: AtomicI32 = AtomicI32::new(0);
 static MOUSE_UP_X: AtomicI32 = AtomicI32::new(0);
 static MOUSE_UP_Y: AtomicI32 = AtomicI32::new(0);
+// Event timestamps (ns since boot) of the last trigger press/release
+static MOUSE_DOWN_TIME: AtomicU64 = AtomicU64::new(0);
+static MOUSE_UP_TIME: AtomicU64 = AtomicU64::new(0);
+static TRIGGER_BUTTON: AtomicU8 = AtomicU8::new(TriggerButton::Left as u8);
+// A press that started on the popup: AppKit drags the panel, a plain click dismisses it
+static PRESS_IN_POPUP: AtomicBool = AtomicBool::new(false);
+// Which popup the press landed on, by `Popup::id`
+static POPUP_PRESS_ID: AtomicU64 = AtomicU64::new(0);
+// A press on the sidebar (scrolling, reading), ignored like the popup's
+static PRESS_IN_SIDEBAR: AtomicBool = AtomicBool::new(false);
+// The suppress_modifier was held when the trigger button went down
+static PRESS_SUPPRESSED: AtomicBool = AtomicBool::new(false);
+static POPUP_PRESS_X: AtomicI32 = AtomicI32::new(0);
+static POPUP_PRESS_Y: AtomicI32 = AtomicI32::new(0);
+static POPUP_RELEASE: AtomicU8 = AtomicU8::new(RELEASE_NONE);
+// Monitors were added, removed or rearranged since the last poll
+static DISPLAYS_CHANGED: AtomicBool = AtomicBool::new(false);
+
+const RELEASE_NONE: u8 = 0;
+const RELEASE_CLICK: u8 = 1;
+const RELEASE_DRAG: u8 = 2;
+// Pointer movement below this counts as a click on the popup, not a drag
+const DRAG_SLOP_PX: i32 = 3;
+// Registered hotkeys as (id, keycode, modifier flags); id of the last press, 0 = none
+static HOTKEYS: Mutex<Vec<(u32, CGKeyCode, CGEventFlags)>> = Mutex::new(Vec::new());
+static HOTKEY_PENDING: AtomicU32 = AtomicU32::new(0);
 
 // Store tap ref for re-enabling on timeout (AtomicPtr is Send+Sync)
 static TAP_REF: AtomicPtr<c_void> = AtomicPtr::new(std::ptr::null_mut());
@@ -134,25 +275,75 @@ const PHASE_VISIBLE: u8 = 2;
 const PHASE_FADE_OUT: u8 = 3;
 
 const MAX_ALPHA: f64 = 0.92;
-const FADE_IN_MS: f64 = 180.0;
-const FADE_OUT_MS: f64 = 220.0;
-const FADE_OUT_DESELECT_MS: f64 = 120.0;
 const SLIDE_PX: f64 = 10.0;
 const PADDING: f64 = 16.0;
 const MAX_WIDTH: f64 = 640.0;
-const MIN_WIDTH: f64 = 200.0;
 const GAP_ABOVE: f64 = 8.0;
 const CORNER_RADIUS: f64 = 22.0;
 const FONT_SIZE: f64 = 14.0;
+const SECONDARY_FONT_SIZE: f64 = 12.0;
+const SECONDARY_GAP: f64 = 8.0;
 const MARGIN: f64 = 4.0;
 
-static mut POPUP_PANEL: Option<Retained<NSPanel>> = None;
-static mut PHASE: u8 = PHASE_NONE;
-static mut ANIM_START: Option<Instant> = None;
-static mut TARGET_Y: f64 = 0.0;
-static mut DESELECT_CLOSE: bool = false;
-static mut AUTO_HIDE_DEADLINE: Option<Instant> = None;
-static mut POSITIONED_ABOVE: bool = true;
+// Opaque card fallback colors (grayscale, same as the Windows popup)
+const CARD_BG_WHITE: f64 = 0x2A as f64 / 255.0;
+const CARD_BORDER_WHITE: f64 = 0x60 as f64 / 255.0;
+const CARD_TEXT_WHITE: f64 = 0xF0 as f64 / 255.0;
+const CARD_SECONDARY_WHITE: f64 = 0xA8 as f64 / 255.0;
+
+const COUNTDOWN_HEIGHT: f64 = 2.0;
+const COUNTDOWN_BOTTOM: f64 = 5.0;
+
+// debug_overlay outline
+const SELECTION_RECT_BORDER: f64 = 2.0;
+
+const SIDEBAR_WIDTH: f64 = 360.0;
+const SIDEBAR_ENTRY_GAP: f64 = 20.0;
+
+const INPUT_WIDTH: f64 = 420.0;
+const INPUT_FIELD_HEIGHT: f64 = 24.0;
+const INPUT_RESULT_HEIGHT: f64 = 140.0;
+
+/// One popup on screen and its animation/auto-hide state. Up to
+/// `max_popups` of these are kept, oldest first.
+struct Popup {
+    // Stable across removals, unlike the index; 0 is never used
+    id: u64,
+    panel: Retained<NSPanel>,
+    phase: u8,
+    anim_start: Instant,
+    // First shown; unlike hide_deadline, never pushed back by interaction
+    shown_at: Instant,
+    target_y: f64,
+    positioned_above: bool,
+    deselect_close: bool,
+    hide_deadline: Option<Instant>,
+    // Full length of the current auto-hide countdown, for the countdown bar
+    hide_total_secs: f64,
+    countdown_bar: Option<(Retained<NSView>, f64)>,
+    // Frame in Quartz coordinates (top-left origin), for hit-testing in the event tap
+    quartz_rect: NSRect,
+}
+
+static mut POPUPS: Vec<Popup> = Vec::new();
+static mut NEXT_POPUP_ID: u64 = 1;
+
+// debug_overlay outline and when it goes away
+static mut SELECTION_RECT_PANEL: Option<(Retained<NSPanel>, Instant)> = None;
+
+static mut SIDEBAR_PANEL: Option<Retained<NSPanel>> = None;
+static mut SIDEBAR_SCROLL_VIEW: Option<Retained<NSScrollView>> = None;
+static mut SIDEBAR_OPAQUE_CARD: bool = false;
+// (original, translated), newest first
+static mut SIDEBAR_ENTRIES: Vec<(String, String)> = Vec::new();
+static mut SIDEBAR_QUARTZ_RECT: Option<NSRect> = None;
+
+static mut INPUT_PANEL: Option<Retained<NSPanel>> = None;
+static mut INPUT_FIELD: Option<Retained<NSTextField>> = None;
+static mut INPUT_RESULT: Option<Retained<NSTextField>> = None;
+// Field contents at the last poll, to notice edits
+static INPUT_LAST_TEXT: Mutex<String> = Mutex::new(String::new());
+static INPUT_SUBMITTED: AtomicBool = AtomicBool::new(false);
 
 // ---------------------------------------------------------------------------
 // HookHandle (RAII)
@@ -195,10 +386,27 @@ pub fn init_platform() {
     }
 
     unsafe {
-        if !CGPreflightListenEventAccess() {
+        let marker = crate::config::Config::app_dir().join(INPUT_MONITORING_PROMPTED_MARKER);
+        if CGPreflightListenEventAccess() {
+            // Prompt again if access is revoked later
+            let _ = std::fs::remove_file(&marker);
+        } else if marker.exists() {
+            tracing::info!("Input Monitoring still denied, not re-requesting (prompted before)");
+        } else {
             tracing::info!("Requesting Input Monitoring permission...");
             CGRequestListenEventAccess();
+            let _ = std::fs::write(&marker, "");
         }
+        // Same signal as NSApplicationDidChangeScreenParametersNotification,
+        // without needing an Objective-C observer
+        CGDisplayRegisterReconfigurationCallback(display_reconfigured, std::ptr::null_mut());
+    }
+}
+
+unsafe extern "C" fn display_reconfigured(_display: u32, flags: u32, _user_info: *mut c_void) {
+    // Called once before and once after each change; only the latter matters
+    if flags & K_CG_DISPLAY_BEGIN_CONFIGURATION_FLAG == 0 {
+        DISPLAYS_CHANGED.store(true, Ordering::Relaxed);
     }
 }
 
@@ -206,7 +414,8 @@ pub fn init_platform() {
 // install_mouse_hook
 // ---------------------------------------------------------------------------
 
-pub fn install_mouse_hook() -> anyhow::Result<HookHandle> {
+pub fn install_mouse_hook(trigger: TriggerButton) -> anyhow::Result<HookHandle> {
+    TRIGGER_BUTTON.store(trigger as u8, Ordering::Relaxed);
     unsafe {
         if !CGPreflightListenEventAccess() {
             anyhow::bail!(
@@ -216,8 +425,15 @@ pub fn install_mouse_hook() -> anyhow::Result<HookHandle> {
             );
         }
 
-        let events: CGEventMask =
-            (1 << K_CG_EVENT_LEFT_MOUSE_DOWN) | (1 << K_CG_EVENT_LEFT_MOUSE_UP);
+        // Left-down is always watched so clicking elsewhere dismisses the popup
+        let (trigger_down, trigger_up) = trigger_event_types(trigger);
+        let mut events: CGEventMask = (1 << K_CG_EVENT_LEFT_MOUSE_DOWN)
+            | (1 << trigger_down)
+            | (1 << trigger_up);
+        // Only watch the keyboard when a hotkey is actually configured
+        if !HOTKEYS.lock().unwrap().is_empty() {
+            events |= 1 << K_CG_EVENT_KEY_DOWN;
+        }
 
         let tap = CGEventTapCreate(
             K_CG_HID_EVENT_TAP,
@@ -243,7 +459,7 @@ pub fn install_mouse_hook() -> anyhow::Result<HookHandle> {
 
         TAP_REF.store(tap, Ordering::Relaxed);
 
-        tracing::info!("Mouse hook installed — ready!");
+        tracing::info!("Mouse hook installed ({:?} button) — ready!", trigger);
         Ok(HookHandle { tap, source, run_loop })
     }
 }
@@ -252,24 +468,100 @@ pub fn install_mouse_hook() -> anyhow::Result<HookHandle> {
 // Mouse tap callback
 // ---------------------------------------------------------------------------
 
+/// Down/up CGEvent types for the configured trigger button.
+fn trigger_event_types(trigger: TriggerButton) -> (CGEventType, CGEventType) {
+    match trigger {
+        TriggerButton::Left => (K_CG_EVENT_LEFT_MOUSE_DOWN, K_CG_EVENT_LEFT_MOUSE_UP),
+        TriggerButton::Middle => (K_CG_EVENT_OTHER_MOUSE_DOWN, K_CG_EVENT_OTHER_MOUSE_UP),
+        TriggerButton::Right => (K_CG_EVENT_RIGHT_MOUSE_DOWN, K_CG_EVENT_RIGHT_MOUSE_UP),
+    }
+}
+
+/// Whether `event` belongs to the trigger button. "Other" mouse events cover
+/// every extra button, so middle must also match on the button number.
+unsafe fn is_trigger_event(event_type: CGEventType, event: CGEventRef, down: bool) -> bool {
+    let trigger = match TRIGGER_BUTTON.load(Ordering::Relaxed) {
+        b if b == TriggerButton::Middle as u8 => TriggerButton::Middle,
+        b if b == TriggerButton::Right as u8 => TriggerButton::Right,
+        _ => TriggerButton::Left,
+    };
+    let (trigger_down, trigger_up) = trigger_event_types(trigger);
+    let expected = if down { trigger_down } else { trigger_up };
+    if event_type != expected {
+        return false;
+    }
+    trigger != TriggerButton::Middle
+        || CGEventGetIntegerValueField(event, K_CG_MOUSE_EVENT_BUTTON_NUMBER) == MIDDLE_BUTTON_NUMBER
+}
+
 unsafe extern "C" fn mouse_tap_callback(
     _proxy: CGEventTapProxy,
     event_type: CGEventType,
     event: CGEventRef,
     _user_info: *mut c_void,
 ) -> CGEventRef {
+    // Listen-only tap: events are observed, never consumed, so context menus still open.
     match event_type {
-        K_CG_EVENT_LEFT_MOUSE_DOWN => {
+        K_CG_EVENT_LEFT_MOUSE_DOWN | K_CG_EVENT_RIGHT_MOUSE_DOWN | K_CG_EVENT_OTHER_MOUSE_DOWN => {
+            let is_trigger = is_trigger_event(event_type, event, true);
             let loc = CGEventGetLocation(event);
-            MOUSE_DOWN_X.store(loc.x as i32, Ordering::Relaxed);
-            MOUSE_DOWN_Y.store(loc.y as i32, Ordering::Relaxed);
-            MOUSE_CLICK_FLAG.store(true, Ordering::Relaxed);
+            if (is_trigger || event_type == K_CG_EVENT_LEFT_MOUSE_DOWN) && sidebar_contains(loc.x, loc.y) {
+                PRESS_IN_SIDEBAR.store(true, Ordering::Relaxed);
+            } else if let Some(id) =
+                popup_at(loc.x, loc.y).filter(|_| is_trigger || event_type == K_CG_EVENT_LEFT_MOUSE_DOWN)
+            {
+                // Not a selection: the panel is movable by its background
+                PRESS_IN_POPUP.store(true, Ordering::Relaxed);
+                POPUP_PRESS_ID.store(id, Ordering::Relaxed);
+                POPUP_PRESS_X.store(loc.x as i32, Ordering::Relaxed);
+                POPUP_PRESS_Y.store(loc.y as i32, Ordering::Relaxed);
+            } else if is_trigger {
+                PRESS_SUPPRESSED.store(suppress_modifier_held(event), Ordering::Relaxed);
+                MOUSE_DOWN_X.store(loc.x as i32, Ordering::Relaxed);
+                MOUSE_DOWN_Y.store(loc.y as i32, Ordering::Relaxed);
+                MOUSE_DOWN_TIME.store(CGEventGetTimestamp(event), Ordering::Relaxed);
+                MOUSE_CLICK_FLAG.store(true, Ordering::Relaxed);
+            } else if event_type == K_CG_EVENT_LEFT_MOUSE_DOWN {
+                MOUSE_CLICK_FLAG.store(true, Ordering::Relaxed);
+            }
         }
-        K_CG_EVENT_LEFT_MOUSE_UP => {
+        K_CG_EVENT_LEFT_MOUSE_UP | K_CG_EVENT_RIGHT_MOUSE_UP | K_CG_EVENT_OTHER_MOUSE_UP => {
+            let is_trigger = is_trigger_event(event_type, event, false);
             let loc = CGEventGetLocation(event);
-            MOUSE_UP_X.store(loc.x as i32, Ordering::Relaxed);
-            MOUSE_UP_Y.store(loc.y as i32, Ordering::Relaxed);
-            MOUSE_UP_FLAG.store(true, Ordering::Relaxed);
+            if (is_trigger || event_type == K_CG_EVENT_LEFT_MOUSE_UP)
+                && PRESS_IN_SIDEBAR.swap(false, Ordering::Relaxed)
+            {
+                // Released after a press on the sidebar, not a selection
+            } else if (is_trigger || event_type == K_CG_EVENT_LEFT_MOUSE_UP)
+                && PRESS_IN_POPUP.swap(false, Ordering::Relaxed)
+            {
+                let dx = loc.x as i32 - POPUP_PRESS_X.load(Ordering::Relaxed);
+                let dy = loc.y as i32 - POPUP_PRESS_Y.load(Ordering::Relaxed);
+                let release = if dx.abs() > DRAG_SLOP_PX || dy.abs() > DRAG_SLOP_PX {
+                    RELEASE_DRAG
+                } else {
+                    RELEASE_CLICK
+                };
+                POPUP_RELEASE.store(release, Ordering::Relaxed);
+            } else if is_trigger
+                && (PRESS_SUPPRESSED.swap(false, Ordering::Relaxed) || suppress_modifier_held(event))
+            {
+                tracing::debug!("Selection ignored, suppress_modifier held");
+            } else if is_trigger {
+                MOUSE_UP_X.store(loc.x as i32, Ordering::Relaxed);
+                MOUSE_UP_Y.store(loc.y as i32, Ordering::Relaxed);
+                MOUSE_UP_TIME.store(CGEventGetTimestamp(event), Ordering::Relaxed);
+                MOUSE_UP_FLAG.store(true, Ordering::Relaxed);
+            }
+        }
+        K_CG_EVENT_KEY_DOWN if CGEventGetIntegerValueField(event, K_CG_KEYBOARD_EVENT_AUTOREPEAT) == 0 => {
+            let keycode = CGEventGetIntegerValueField(event, K_CG_KEYBOARD_EVENT_KEYCODE) as CGKeyCode;
+            let flags = CGEventGetFlags(event) & HOTKEY_FLAG_MASK;
+            if let Ok(hotkeys) = HOTKEYS.try_lock() {
+                if let Some(&(id, _, _)) = hotkeys.iter().find(|&&(_, k, f)| k == keycode && f == flags) {
+                    HOTKEY_PENDING.store(id, Ordering::Relaxed);
+                }
+            }
         }
         K_CG_EVENT_TAP_DISABLED_BY_TIMEOUT => {
             tracing::warn!("Event tap disabled by timeout, re-enabling");
@@ -283,6 +575,68 @@ unsafe extern "C" fn mouse_tap_callback(
     event
 }
 
+// ---------------------------------------------------------------------------
+// Hotkeys
+// ---------------------------------------------------------------------------
+
+/// `hotkey::MOD_*` bits as event flags.
+fn modifier_flags(modifiers: u8) -> CGEventFlags {
+    let mut flags: CGEventFlags = 0;
+    if modifiers & hotkey::MOD_CTRL != 0 {
+        flags |= K_CG_EVENT_FLAG_MASK_CONTROL;
+    }
+    if modifiers & hotkey::MOD_ALT != 0 {
+        flags |= K_CG_EVENT_FLAG_MASK_ALTERNATE;
+    }
+    if modifiers & hotkey::MOD_SHIFT != 0 {
+        flags |= K_CG_EVENT_FLAG_MASK_SHIFT;
+    }
+    if modifiers & hotkey::MOD_SUPER != 0 {
+        flags |= K_CG_EVENT_FLAG_MASK_COMMAND;
+    }
+    flags
+}
+
+/// Whether `event` was posted with any `suppress_modifier` key down.
+unsafe fn suppress_modifier_held(event: CGEventRef) -> bool {
+    CGEventGetFlags(event) & modifier_flags(suppress_modifiers()) != 0
+}
+
+/// Register a global hotkey; presses arrive as `MouseEvent::Hotkey(id)`.
+///
+/// Hotkeys are matched inside the listen-only event tap, so call this before
+/// `install_mouse_hook`. The keystroke still reaches the frontmost app.
+pub fn register_hotkey(id: u32, hotkey: &Hotkey) -> anyhow::Result<()> {
+    let keycode = hotkey_keycode(hotkey.key);
+    let flags = modifier_flags(hotkey.modifiers);
+
+    let mut hotkeys = HOTKEYS.lock().unwrap();
+    if hotkeys.iter().any(|&(_, k, f)| k == keycode && f == flags) {
+        anyhow::bail!("{:?} is already bound to another action", hotkey);
+    }
+    hotkeys.push((id, keycode, flags));
+    Ok(())
+}
+
+/// ANSI-layout virtual keycodes (Events.h `kVK_*`).
+fn hotkey_keycode(key: Key) -> CGKeyCode {
+    const LETTERS: [CGKeyCode; 26] = [
+        0, 11, 8, 2, 14, 3, 5, 4, 34, 38, 40, 37, 46, 45, 31, 35, 12, 15, 1, 17, 32, 9, 13, 7,
+        16, 6,
+    ];
+    const DIGITS: [CGKeyCode; 10] = [29, 18, 19, 20, 21, 23, 22, 26, 28, 25];
+    const FUNCTION: [CGKeyCode; 12] = [122, 120, 99, 118, 96, 97, 98, 100, 101, 109, 103, 111];
+    match key {
+        Key::Letter(c) => LETTERS[(c - b'A') as usize],
+        Key::Digit(c) => DIGITS[(c - b'0') as usize],
+        Key::Function(n) => FUNCTION[(n - 1) as usize],
+        Key::Space => 49,
+        Key::Enter => 36,
+        Key::Tab => 48,
+        Key::Escape => 53,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // poll_mouse_event
 // ---------------------------------------------------------------------------
@@ -300,24 +654,62 @@ pub fn poll_mouse_event() -> Option<MouseEvent> {
 
     // Drive popup animation
     animate_popup();
+    unsafe { expire_selection_rect() };
+
+    if DISPLAYS_CHANGED.swap(false, Ordering::Relaxed) {
+        on_display_change();
+        if let Some(mtm) = MainThreadMarker::new() {
+            unsafe { redock_sidebar(mtm) };
+        }
+    }
 
-    // Check auto-hide deadline
+    let pressed = POPUP_PRESS_ID.load(Ordering::Relaxed);
+    match POPUP_RELEASE.swap(RELEASE_NONE, Ordering::Relaxed) {
+        RELEASE_CLICK => unsafe {
+            // A plain click dismisses just the popup clicked
+            if let Some(p) = popup_mut(pressed) {
+                begin_fade_out(p, true);
+            }
+        },
+        RELEASE_DRAG => finish_popup_drag(pressed),
+        _ => {}
+    }
+
+    // Check auto-hide deadlines; the one under the pointer starts over
     unsafe {
-        if PHASE == PHASE_VISIBLE {
-            if let Some(deadline) = AUTO_HIDE_DEADLINE {
-                if Instant::now() >= deadline {
-                    begin_fade_out(false);
-                }
+        let pointer = NSEvent::mouseLocation();
+        for p in popups().iter_mut() {
+            let hovered = p.phase == PHASE_VISIBLE
+                && p.hide_deadline.is_some()
+                && rect_contains(p.panel.frame(), pointer);
+            if hovered {
+                extend_auto_hide(p);
+            }
+            update_countdown_bar(p);
+            if p.phase == PHASE_VISIBLE && p.hide_deadline.is_some_and(|d| Instant::now() >= d) {
+                begin_fade_out(p, false);
+            }
+            if p.phase != PHASE_FADE_OUT && lifetime_exceeded(p.shown_at) {
+                begin_fade_out(p, false);
             }
         }
     }
 
+    let hotkey_id = HOTKEY_PENDING.swap(0, Ordering::Relaxed);
+    if hotkey_id != 0 {
+        return Some(MouseEvent::Hotkey(hotkey_id));
+    }
+
     if MOUSE_UP_FLAG.swap(false, Ordering::Relaxed) {
         return Some(MouseEvent::SelectionDone {
             down_x: MOUSE_DOWN_X.load(Ordering::Relaxed),
             down_y: MOUSE_DOWN_Y.load(Ordering::Relaxed),
             up_x: MOUSE_UP_X.load(Ordering::Relaxed),
             up_y: MOUSE_UP_Y.load(Ordering::Relaxed),
+            held_ms: MOUSE_UP_TIME
+                .load(Ordering::Relaxed)
+                .saturating_sub(MOUSE_DOWN_TIME.load(Ordering::Relaxed))
+                / 1_000_000,
         });
     }
 
@@ -337,14 +729,47 @@ pub fn get_double_click_time_ms() -> u64 {
     (interval * 1000.0) as u64
 }
 
+/// 1-based position of the screen containing a point given in Quartz
+/// coordinates, in `NSScreen::screens` order (1 = the menu bar screen).
+pub fn monitor_index_at(x: i32, y: i32) -> Option<usize> {
+    let mtm = MainThreadMarker::new()?;
+    let screens = NSScreen::screens(mtm);
+    if screens.count() == 0 {
+        return None;
+    }
+    let screen_h = screens.objectAtIndex(0).frame().size.height;
+    screen_index_at(&screens, NSPoint::new(x as f64, screen_h - y as f64)).map(|i| i + 1)
+}
+
+/// Index of the screen whose frame contains `point` (AppKit coordinates).
+fn screen_index_at(screens: &NSArray<NSScreen>, point: NSPoint) -> Option<usize> {
+    (0..screens.count()).find(|&i| {
+        let frame = screens.objectAtIndex(i).frame();
+        point.x >= frame.origin.x
+            && point.x < frame.origin.x + frame.size.width
+            && point.y >= frame.origin.y
+            && point.y < frame.origin.y + frame.size.height
+    })
+}
+
 // ---------------------------------------------------------------------------
 // send_copy_command (Cmd+C)
 // ---------------------------------------------------------------------------
 
 pub fn send_copy_command() {
+    send_command_combo(KEYCODE_C);
+}
+
+pub fn send_paste_command() {
+    send_command_combo(KEYCODE_V);
+}
+
+/// Post Cmd+<key>. Flags are set explicitly, so modifiers still held from a
+/// hotkey don't leak into the combo.
+fn send_command_combo(keycode: CGKeyCode) {
     unsafe {
         // Key down
-        let event_down = CGEventCreateKeyboardEvent(std::ptr::null(), KEYCODE_C, true);
+        let event_down = CGEventCreateKeyboardEvent(std::ptr::null(), keycode, true);
         if !event_down.is_null() {
             CGEventSetFlags(event_down, K_CG_EVENT_FLAG_MASK_COMMAND);
             CGEventPost(K_CG_HID_EVENT_TAP, event_down);
@@ -352,7 +777,7 @@ pub fn send_copy_command() {
         }
 
         // Key up
-        let event_up = CGEventCreateKeyboardEvent(std::ptr::null(), KEYCODE_C, false);
+        let event_up = CGEventCreateKeyboardEvent(std::ptr::null(), keycode, false);
         if !event_up.is_null() {
             CGEventSetFlags(event_up, K_CG_EVENT_FLAG_MASK_COMMAND);
             CGEventPost(K_CG_HID_EVENT_TAP, event_up);
@@ -361,6 +786,388 @@ pub fn send_copy_command() {
     }
 }
 
+/// Triple-click at the pointer so the app under it selects the whole
+/// paragraph. Returns the pointer position (Quartz coordinates).
+pub fn select_paragraph_at_cursor() -> (i32, i32) {
+    click_at_cursor(3)
+}
+
+/// Double-click at the pointer so the app under it selects the word, for
+/// `hover_translate`. Returns the pointer position (Quartz coordinates).
+pub fn select_word_at_cursor() -> (i32, i32) {
+    click_at_cursor(2)
+}
+
+/// Pointer position (Quartz coordinates) for `hover_translate`; `None` while
+/// a mouse button is held or the pointer is over the popup or sidebar.
+pub fn hover_point() -> Option<(i32, i32)> {
+    unsafe {
+        if NSEvent::pressedMouseButtons() != 0 {
+            return None;
+        }
+        let current = CGEventCreate(std::ptr::null());
+        if current.is_null() {
+            return None;
+        }
+        let point = CGEventGetLocation(current);
+        CFRelease(current);
+        if popup_at(point.x, point.y).is_some() || sidebar_contains(point.x, point.y) {
+            return None;
+        }
+        Some((point.x as i32, point.y as i32))
+    }
+}
+
+/// Roles whose text a double-click selects.
+const AX_TEXT_ROLES: &[&str] = &["AXTextArea", "AXTextField", "AXStaticText"];
+/// Containers where a double-click opens or activates instead (Finder rows,
+/// links, buttons), even on the text inside them.
+const AX_ACTIVATING_ROLES: &[&str] = &["AXLink", "AXButton", "AXCell", "AXRow", "AXMenuItem"];
+/// Characters read on each side of the pointer to find the word it is on.
+const HOVER_CONTEXT_CHARS: isize = 64;
+
+/// What is at (`x`, `y`) (Quartz coordinates) for `hover_translate`: the word
+/// there when the element exposes its text, otherwise whether a double-click
+/// would select text rather than open something.
+pub fn hover_target(x: i32, y: i32) -> Option<HoverTarget> {
+    unsafe {
+        let system = AXUIElementCreateSystemWide();
+        if system.is_null() {
+            return None;
+        }
+        let mut element: AXUIElementRef = std::ptr::null_mut();
+        let err = AXUIElementCopyElementAtPosition(system, x as f32, y as f32, &mut element);
+        CFRelease(system);
+        if err != 0 || element.is_null() {
+            return None;
+        }
+        let role = ax_role(element);
+        let target = match ax_word_at(element, x, y) {
+            Some(word) => Some(HoverTarget::Word(word)),
+            None if role.as_deref().is_some_and(|r| AX_TEXT_ROLES.contains(&r)) => {
+                let parent_role = ax_attribute(element, c"AXParent").and_then(|parent| {
+                    let role = ax_role(parent);
+                    CFRelease(parent);
+                    role
+                });
+                let activating = parent_role.is_some_and(|r| AX_ACTIVATING_ROLES.contains(&r.as_str()));
+                (!activating).then_some(HoverTarget::SelectableText)
+            }
+            None => None,
+        };
+        CFRelease(element);
+        target
+    }
+}
+
+unsafe fn ax_role(element: AXUIElementRef) -> Option<String> {
+    let value = ax_attribute(element, c"AXRole")?;
+    let role = if CFGetTypeID(value) == CFStringGetTypeID() {
+        cf_string_to_string(value as CFStringRef)
+    } else {
+        None
+    };
+    CFRelease(value);
+    role
+}
+
+/// Parameterized attribute `name` of an Accessibility element, for an
+/// `AXValue` parameter (caller releases the result).
+unsafe fn ax_parameterized_attribute<T>(
+    element: AXUIElementRef,
+    name: &CStr,
+    value_type: u32,
+    parameter: &T,
+) -> Option<CFTypeRef> {
+    let parameter = AXValueCreate(value_type, parameter as *const T as *const c_void);
+    if parameter.is_null() {
+        return None;
+    }
+    let attr = cf_string(name);
+    let mut value: CFTypeRef = std::ptr::null_mut();
+    let err = AXUIElementCopyParameterizedAttributeValue(element, attr, parameter, &mut value);
+    CFRelease(attr as *mut c_void);
+    CFRelease(parameter);
+    if err != 0 || value.is_null() {
+        return None;
+    }
+    Some(value)
+}
+
+/// The word of a text element at (`x`, `y`): the character there from
+/// `AXRangeForPosition`, widened to its word within the text around it.
+unsafe fn ax_word_at(element: AXUIElementRef, x: i32, y: i32) -> Option<String> {
+    let point = CGPoint { x: x as f64, y: y as f64 };
+    let value =
+        ax_parameterized_attribute(element, c"AXRangeForPosition", K_AX_VALUE_CG_POINT_TYPE, &point)?;
+    let mut hit = CFRange { location: -1, length: 0 };
+    let ok = AXValueGetValue(value, K_AX_VALUE_CF_RANGE_TYPE, &mut hit as *mut CFRange as *mut c_void);
+    CFRelease(value);
+    if !ok || hit.location < 0 {
+        return None;
+    }
+
+    let count = ax_attribute(element, c"AXNumberOfCharacters")?;
+    let mut total: isize = 0;
+    let ok = CFNumberGetValue(count, K_CF_NUMBER_CF_INDEX_TYPE, &mut total as *mut isize as *mut c_void);
+    CFRelease(count);
+    if !ok || hit.location >= total {
+        return None;
+    }
+    let start = (hit.location - HOVER_CONTEXT_CHARS).max(0);
+    let end = (hit.location + HOVER_CONTEXT_CHARS).min(total);
+    let around = CFRange { location: start, length: end - start };
+    let value = ax_parameterized_attribute(element, c"AXStringForRange", K_AX_VALUE_CF_RANGE_TYPE, &around)?;
+    let text = if CFGetTypeID(value) == CFStringGetTypeID() {
+        cf_string_to_string(value as CFStringRef)
+    } else {
+        None
+    };
+    CFRelease(value);
+    word_around(&text?, (hit.location - start) as usize)
+}
+
+/// The word in `text` containing the character at `utf16_index` (AX ranges
+/// count UTF-16 units), or `None` when that character isn't part of a word.
+fn word_around(text: &str, utf16_index: usize) -> Option<String> {
+    let is_word = |c: char| c.is_alphanumeric() || matches!(c, '\'' | '’' | '-');
+    let chars: Vec<char> = text.chars().collect();
+    let mut units = 0;
+    let at = chars.iter().position(|c| {
+        units += c.len_utf16();
+        units > utf16_index
+    })?;
+    if !is_word(chars[at]) {
+        return None;
+    }
+    let start = chars[..at].iter().rposition(|&c| !is_word(c)).map_or(0, |i| i + 1);
+    let end = chars[at..].iter().position(|&c| !is_word(c)).map_or(chars.len(), |i| at + i);
+    let word: String = chars[start..end].iter().collect();
+    let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+    (!word.is_empty()).then(|| word.to_string())
+}
+
+fn click_at_cursor(clicks: i64) -> (i32, i32) {
+    unsafe {
+        let current = CGEventCreate(std::ptr::null());
+        if current.is_null() {
+            return (0, 0);
+        }
+        let point = CGEventGetLocation(current);
+        CFRelease(current);
+
+        for click in 1..=clicks {
+            for event_type in [K_CG_EVENT_LEFT_MOUSE_DOWN, K_CG_EVENT_LEFT_MOUSE_UP] {
+                let event = CGEventCreateMouseEvent(std::ptr::null(), event_type, point, K_CG_MOUSE_BUTTON_LEFT);
+                if event.is_null() {
+                    continue;
+                }
+                // No hotkey modifiers, or it becomes a Cmd-click
+                CGEventSetFlags(event, 0);
+                CGEventSetIntegerValueField(event, K_CG_MOUSE_EVENT_CLICK_STATE, click);
+                CGEventPost(K_CG_HID_EVENT_TAP, event);
+                CFRelease(event);
+            }
+        }
+        (point.x as i32, point.y as i32)
+    }
+}
+
+/// True while any app has secure event input enabled (password fields,
+/// Terminal's Secure Keyboard Entry). Cmd+C would only churn the clipboard.
+pub fn is_secure_input_active() -> bool {
+    unsafe { IsSecureEventInputEnabled() != 0 }
+}
+
+/// Attribute `name` of an Accessibility element (caller releases it).
+unsafe fn ax_attribute(element: AXUIElementRef, name: &CStr) -> Option<CFTypeRef> {
+    let attr = cf_string(name);
+    let mut value: CFTypeRef = std::ptr::null_mut();
+    let err = AXUIElementCopyAttributeValue(element, attr, &mut value);
+    CFRelease(attr as *mut c_void);
+    if err != 0 || value.is_null() {
+        return None;
+    }
+    Some(value)
+}
+
+/// Whether the focused window of the frontmost app is in full-screen mode
+/// (its own Space), per its `AXFullScreen` attribute.
+pub fn is_fullscreen_app_active() -> bool {
+    unsafe {
+        let system = AXUIElementCreateSystemWide();
+        if system.is_null() {
+            return false;
+        }
+        let app = ax_attribute(system, c"AXFocusedApplication");
+        CFRelease(system);
+        let Some(app) = app else {
+            return false;
+        };
+        let window = ax_attribute(app, c"AXFocusedWindow");
+        CFRelease(app);
+        let Some(window) = window else {
+            return false;
+        };
+        let value = ax_attribute(window, c"AXFullScreen");
+        CFRelease(window);
+        let Some(value) = value else {
+            return false;
+        };
+        let fullscreen = CFGetTypeID(value) == CFBooleanGetTypeID() && CFBooleanGetValue(value);
+        CFRelease(value);
+        fullscreen
+    }
+}
+
+/// Whether the app runs inside a remote desktop session. macOS has no
+/// equivalent of Windows' check, so `remote_session = "on"` is needed there.
+pub fn is_remote_session() -> bool {
+    false
+}
+
+/// Whether the focused UI element accepts typing, via the Accessibility API
+/// (its `AXValue` is settable). `None` when there is no focused element.
+pub fn is_focused_editable() -> Option<bool> {
+    unsafe {
+        let focused = focused_ui_element()?;
+        let value_attr = cf_string(c"AXValue");
+        let mut settable: u8 = 0;
+        let err = AXUIElementIsAttributeSettable(focused, value_attr, &mut settable);
+        CFRelease(value_attr as *mut c_void);
+        CFRelease(focused);
+        if err != 0 {
+            return None;
+        }
+        Some(settable != 0)
+    }
+}
+
+/// Read the selection through the Accessibility API (`AXSelectedText`) so the
+/// clipboard is left alone. `None` when the app doesn't expose it.
+pub fn read_selected_text() -> Option<String> {
+    unsafe {
+        let focused = focused_ui_element()?;
+        let attr = cf_string(c"AXSelectedText");
+        let mut value: CFTypeRef = std::ptr::null_mut();
+        let err = AXUIElementCopyAttributeValue(focused, attr, &mut value);
+        CFRelease(attr as *mut c_void);
+        CFRelease(focused);
+        if err != 0 || value.is_null() {
+            return None;
+        }
+        let text = if CFGetTypeID(value) == CFStringGetTypeID() {
+            cf_string_to_string(value as CFStringRef)
+        } else {
+            None
+        };
+        CFRelease(value);
+        text.filter(|t| !t.trim().is_empty())
+    }
+}
+
+/// The general pasteboard's `changeCount`, to detect a copy without clearing.
+pub fn clipboard_change_count() -> u64 {
+    NSPasteboard::generalPasteboard().changeCount() as u64
+}
+
+/// One pasteboard item as (type, data) for each type it offers.
+type SavedItem = Vec<(Retained<NSString>, Retained<NSData>)>;
+
+thread_local! {
+    // General pasteboard items saved by `save_clipboard`
+    static SAVED_PASTEBOARD: RefCell<Vec<SavedItem>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Save every item on the general pasteboard in every type it offers, for
+/// `restore_clipboard` on the same thread. Cmd+C can only write to the general
+/// pasteboard, so the copy can't be sent to a private one instead; this keeps
+/// what it overwrites.
+pub fn save_clipboard() {
+    let items = NSPasteboard::generalPasteboard().pasteboardItems();
+    let saved: Vec<SavedItem> = items
+        .iter()
+        .flat_map(|items| items.iter())
+        .map(|item| {
+            item.types()
+                .iter()
+                .filter_map(|ty| item.dataForType(&ty).map(|data| (ty, data)))
+                .collect()
+        })
+        .collect();
+    SAVED_PASTEBOARD.with(|s| *s.borrow_mut() = saved);
+}
+
+/// Put back what `save_clipboard` saved, replacing the pasteboard's contents.
+pub fn restore_clipboard() {
+    let saved = SAVED_PASTEBOARD.with(|s| std::mem::take(&mut *s.borrow_mut()));
+    let items: Vec<Retained<ProtocolObject<dyn NSPasteboardWriting>>> = saved
+        .into_iter()
+        .map(|types| {
+            let item = NSPasteboardItem::new();
+            for (ty, data) in &types {
+                item.setData_forType(data, ty);
+            }
+            ProtocolObject::from_retained(item)
+        })
+        .collect();
+    let pasteboard = NSPasteboard::generalPasteboard();
+    pasteboard.clearContents();
+    if !items.is_empty() && !pasteboard.writeObjects(&NSArray::from_retained_slice(&items)) {
+        tracing::warn!("Failed to restore the previous pasteboard contents");
+    }
+}
+
+/// Raw RTF on the general pasteboard (`public.rtf`), for apps that copy no plain text.
+pub fn read_clipboard_rtf() -> Option<String> {
+    let data = NSPasteboard::generalPasteboard().dataForType(&NSString::from_str("public.rtf"))?;
+    Some(String::from_utf8_lossy(&data.to_vec()).into_owned())
+}
+
+/// The user's first preferred language, e.g. "en-US" or "zh-Hant-TW".
+pub fn system_locale() -> Option<String> {
+    NSLocale::preferredLanguages().firstObject().map(|lang| lang.to_string())
+}
+
+/// Name of the frontmost application, e.g. "Safari".
+pub fn frontmost_app_name() -> Option<String> {
+    let app = NSWorkspace::sharedWorkspace().frontmostApplication()?;
+    app.localizedName().map(|name| name.to_string())
+}
+
+/// The system-wide focused element (caller releases it).
+unsafe fn focused_ui_element() -> Option<AXUIElementRef> {
+    let system = AXUIElementCreateSystemWide();
+    if system.is_null() {
+        return None;
+    }
+    let focused_attr = cf_string(c"AXFocusedUIElement");
+    let mut focused: CFTypeRef = std::ptr::null_mut();
+    let err = AXUIElementCopyAttributeValue(system, focused_attr, &mut focused);
+    CFRelease(focused_attr as *mut c_void);
+    CFRelease(system);
+    if err != 0 || focused.is_null() {
+        return None;
+    }
+    Some(focused)
+}
+
+unsafe fn cf_string_to_string(s: CFStringRef) -> Option<String> {
+    let len = CFStringGetLength(s);
+    let cap = CFStringGetMaximumSizeForEncoding(len, K_CF_STRING_ENCODING_UTF8) + 1;
+    let mut buf = vec![0u8; cap.max(1) as usize];
+    if !CFStringGetCString(s, buf.as_mut_ptr() as *mut c_char, cap, K_CF_STRING_ENCODING_UTF8) {
+        return None;
+    }
+    CStr::from_bytes_until_nul(&buf)
+        .ok()
+        .map(|c| c.to_string_lossy().into_owned())
+}
+
+unsafe fn cf_string(s: &CStr) -> CFStringRef {
+    CFStringCreateWithCString(std::ptr::null(), s.as_ptr(), K_CF_STRING_ENCODING_UTF8)
+}
+
 // ---------------------------------------------------------------------------
 // show_error (NSAlert)
 // ---------------------------------------------------------------------------
@@ -384,6 +1191,23 @@ pub fn open_input_monitoring_settings() {
         .spawn();
 }
 
+/// Explain that Input Monitoring is denied, offering to open its settings pane.
+pub fn show_input_monitoring_denied(msg: &str) {
+    let Some(mtm) = MainThreadMarker::new() else {
+        eprintln!("[Input Monitoring] {}", msg);
+        return;
+    };
+    let alert = NSAlert::new(mtm);
+    alert.setAlertStyle(NSAlertStyle::Warning);
+    alert.setMessageText(&NSString::from_str("Screen Translate — Permission Required"));
+    alert.setInformativeText(&NSString::from_str(msg));
+    alert.addButtonWithTitle(&NSString::from_str("Open System Settings"));
+    alert.addButtonWithTitle(&NSString::from_str("Quit"));
+    if alert.runModal() == NS_ALERT_FIRST_BUTTON_RETURN {
+        open_input_monitoring_settings();
+    }
+}
+
 /// Check if the app has Accessibility permission (needed for Cmd+C simulation).
 pub fn check_accessibility() -> bool {
     unsafe { AXIsProcessTrusted() }
@@ -408,6 +1232,36 @@ pub fn show_info(title: &str, msg: &str) {
     }
 }
 
+/// Ask where to save a settings file, suggesting `default_name`.
+/// `None` if cancelled.
+pub fn choose_save_path(default_name: &str) -> Option<PathBuf> {
+    let mtm = MainThreadMarker::new()?;
+    let panel = NSSavePanel::savePanel(mtm);
+    panel.setNameFieldStringValue(&NSString::from_str(default_name));
+    run_file_panel(&panel, mtm)
+}
+
+/// Ask for a settings file to open. `None` if cancelled.
+pub fn choose_open_path() -> Option<PathBuf> {
+    let mtm = MainThreadMarker::new()?;
+    let panel = NSOpenPanel::openPanel(mtm);
+    panel.setCanChooseFiles(true);
+    panel.setCanChooseDirectories(false);
+    panel.setAllowsMultipleSelection(false);
+    run_file_panel(&panel, mtm)
+}
+
+fn run_file_panel(panel: &NSSavePanel, mtm: MainThreadMarker) -> Option<PathBuf> {
+    // A menu bar app isn't active, so the panel would open behind other windows
+    #[allow(deprecated)]
+    NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+    if panel.runModal() != NS_MODAL_RESPONSE_OK {
+        return None;
+    }
+    let path = panel.URL()?.path()?;
+    Some(PathBuf::from(path.to_string()))
+}
+
 // ---------------------------------------------------------------------------
 // show_popup (NSPanel + Liquid Glass / NSVisualEffectView)
 // ---------------------------------------------------------------------------
@@ -415,8 +1269,10 @@ pub fn show_info(title: &str, msg: &str) {
 pub fn show_popup(
     _original: &str,
     translated: &str,
+    secondary: Option<&str>,
     _duration_secs: u64,
     pos: SelectionPos,
+    content: PopupContent,
 ) {
     let Some(mtm) = MainThreadMarker::new() else {
         tracing::warn!("show_popup called off main thread");
@@ -424,7 +1280,10 @@ pub fn show_popup(
     };
 
     unsafe {
-        destroy_popup();
+        // Make room for the new popup, oldest first
+        while popups().len() >= popup_options().max_popups.max(1) {
+            destroy_popup(popups()[0].id);
+        }
 
         // Get primary screen height for Quartz → AppKit coordinate conversion
         let screens = NSScreen::screens(mtm);
@@ -447,47 +1306,90 @@ pub fn show_popup(
 
         // Find the screen that contains the selection center
         let sel_center_ak = NSPoint::new(sel_center_x, screen_h - sel_center_y_q);
-        let mut target_visible = primary.visibleFrame();
-        let screen_count = screens.count();
-        for i in 0..screen_count {
-            let screen: Retained<NSScreen> = screens.objectAtIndex(i);
-            let frame = screen.frame();
-            if sel_center_ak.x >= frame.origin.x
-                && sel_center_ak.x < frame.origin.x + frame.size.width
-                && sel_center_ak.y >= frame.origin.y
-                && sel_center_ak.y < frame.origin.y + frame.size.height
-            {
-                target_visible = screen.visibleFrame();
-                break;
-            }
-        }
+        let target_visible = match screen_index_at(&screens, sel_center_ak) {
+            Some(i) => screens.objectAtIndex(i).visibleFrame(),
+            None => primary.visibleFrame(),
+        };
 
         // Create the text label to measure its size
         let text_ns = NSString::from_str(translated);
         let label = NSTextField::wrappingLabelWithString(&text_ns, mtm);
-        let font = NSFont::systemFontOfSize(FONT_SIZE);
+        let font = font_for(translated, FONT_SIZE);
         label.setFont(Some(&font));
         label.setTextColor(Some(&NSColor::labelColor()));
+        label.setAlignment(match text_align_for(translated) {
+            TextAlign::Center => NSTextAlignment::Center,
+            TextAlign::End => NSTextAlignment::Right,
+            _ => NSTextAlignment::Left,
+        });
 
         // Constrain width and measure
-        let content_w = (MAX_WIDTH - PADDING * 2.0).max(MIN_WIDTH - PADDING * 2.0);
+        let min_width = min_popup_width(translated, content).min(MAX_WIDTH);
+        let content_w = (MAX_WIDTH - PADDING * 2.0).max(min_width - PADDING * 2.0);
         label.setPreferredMaxLayoutWidth(content_w);
         let fitting = label.fittingSize();
-        let text_w = fitting.width.min(content_w);
+        let mut text_w = fitting.width.min(content_w);
         let text_h = fitting.height;
 
-        let panel_w = (text_w + PADDING * 2.0).clamp(MIN_WIDTH, MAX_WIDTH);
-        let panel_h = text_h + PADDING * 2.0;
+        // Optional second target, smaller and dimmer, stacked below
+        let secondary_label = secondary.map(|text| {
+            let label = NSTextField::wrappingLabelWithString(&NSString::from_str(text), mtm);
+            label.setFont(Some(&font_for(text, SECONDARY_FONT_SIZE)));
+            label.setTextColor(Some(&NSColor::secondaryLabelColor()));
+            label.setAlignment(match text_align_for(text) {
+                TextAlign::Center => NSTextAlignment::Center,
+                TextAlign::End => NSTextAlignment::Right,
+                _ => NSTextAlignment::Left,
+            });
+            label.setPreferredMaxLayoutWidth(content_w);
+            label
+        });
+        let secondary_h = match &secondary_label {
+            Some(label) => {
+                let fitting = label.fittingSize();
+                text_w = text_w.max(fitting.width.min(content_w));
+                fitting.height
+            }
+            None => 0.0,
+        };
+        let stacked_h = if secondary_label.is_some() {
+            text_h + SECONDARY_GAP + secondary_h
+        } else {
+            text_h
+        };
+
+        let (panel_w, panel_h) = sticky_popup_size(
+            (text_w + PADDING * 2.0).clamp(min_width, MAX_WIDTH),
+            stacked_h + PADDING * 2.0,
+        );
 
         // Position: prefer above selection, fallback below
-        let mut x = sel_center_x - panel_w / 2.0;
-        let above = sel_top_ak - panel_h - GAP_ABOVE >= target_visible.origin.y + MARGIN;
-        POSITIONED_ABOVE = above;
-        let mut y = if above {
-            sel_top_ak - panel_h - GAP_ABOVE
+        let options = popup_options();
+        let (mut x, mut y, above) = if content == PopupContent::Status {
+            (
+                target_visible.origin.x + (target_visible.size.width - panel_w) / 2.0,
+                target_visible.origin.y + (target_visible.size.height - panel_h) / 2.0,
+                false,
+            )
+        } else if options.placement == Placement::Cursor {
+            // Top edge below the pointer; AppKit's y grows upwards
+            let (dx, dy) = options.cursor_offset;
+            let cursor_ak_y = screen_h - pos.up_y as f64;
+            (
+                pos.up_x as f64 - panel_w / 2.0 + dx as f64,
+                cursor_ak_y - dy as f64 - panel_h,
+                false,
+            )
         } else {
-            sel_bottom_ak + GAP_ABOVE
+            let above = sel_top_ak - panel_h - GAP_ABOVE >= target_visible.origin.y + MARGIN;
+            let y = if above {
+                sel_top_ak - panel_h - GAP_ABOVE
+            } else {
+                sel_bottom_ak + GAP_ABOVE
+            };
+            (sel_center_x - panel_w / 2.0, y, above)
         };
+        y = stack_clear_of_popups(x, y, panel_w, panel_h, above);
 
         // Clamp to visible frame
         let vis_right = target_visible.origin.x + target_visible.size.width;
@@ -505,10 +1407,11 @@ pub fn show_popup(
             y = target_visible.origin.y + MARGIN;
         }
 
-        TARGET_Y = y;
+        let quartz_rect = to_quartz_rect(NSRect::new(NSPoint::new(x, y), NSSize::new(panel_w, panel_h)), screen_h);
 
         // Start position for slide animation
-        let start_y = if above { y - SLIDE_PX } else { y + SLIDE_PX };
+        let slide_px = if options.slide { SLIDE_PX } else { 0.0 };
+        let start_y = if above { y - slide_px } else { y + slide_px };
 
         let content_rect = NSRect::new(
             NSPoint::new(x, start_y),
@@ -528,88 +1431,263 @@ pub fn show_popup(
 
         let floating_level = CGWindowLevelForKey(K_CG_FLOATING_WINDOW_LEVEL_KEY);
         panel.setLevel(floating_level as isize);
-        panel.setOpaque(false);
-        panel.setBackgroundColor(Some(&NSColor::clearColor()));
-        panel.setHasShadow(true);
+        set_card_backdrop(&panel);
         panel.setHidesOnDeactivate(false);
-        panel.setAlphaValue(0.0); // start invisible for fade-in
+        // Drag anywhere on the card to move it; the panel never becomes key
+        panel.setMovableByWindowBackground(true);
+        panel.setAlphaValue(if options.fade { 0.0 } else { card_alpha() }); // start invisible for fade-in, if any
 
         // Create the background view (Liquid Glass or NSVisualEffectView fallback)
-        let bg_view = create_background_view(panel_w, panel_h, mtm);
+        let (bg_view, opaque_card) = create_background_view(panel_w, panel_h, mtm);
+        if opaque_card {
+            // labelColor is black in light mode, unreadable on the dark card
+            label.setTextColor(Some(&NSColor::colorWithWhite_alpha(CARD_TEXT_WHITE, 1.0)));
+        }
 
-        // Position the label inside the background view
+        // Position the label inside the background view, pinned to the top
+        // (a sticky size can leave the panel taller than the text). It spans
+        // the full content width so center/end alignment has room to act.
         label.setFrame(NSRect::new(
-            NSPoint::new(PADDING, PADDING),
-            NSSize::new(text_w, text_h),
+            NSPoint::new(PADDING, panel_h - PADDING - text_h),
+            NSSize::new(panel_w - PADDING * 2.0, text_h),
         ));
         bg_view.addSubview(&label);
 
+        let mut countdown_bar = None;
+        if popup_options().countdown_bar {
+            let full_w = panel_w - CORNER_RADIUS * 2.0;
+            let bar = NSView::initWithFrame(
+                NSView::alloc(mtm),
+                NSRect::new(
+                    NSPoint::new(CORNER_RADIUS, COUNTDOWN_BOTTOM),
+                    NSSize::new(full_w, COUNTDOWN_HEIGHT),
+                ),
+            );
+            bar.setWantsLayer(true);
+            if let Some(layer) = bar.layer() {
+                let color = if opaque_card {
+                    NSColor::colorWithWhite_alpha(CARD_SECONDARY_WHITE, 1.0)
+                } else {
+                    NSColor::secondaryLabelColor()
+                };
+                let cg_color: *mut c_void = msg_send![&color, CGColor];
+                if !cg_color.is_null() {
+                    let _: () = msg_send![&layer, setBackgroundColor: cg_color];
+                }
+                let _: () = msg_send![&layer, setCornerRadius: COUNTDOWN_HEIGHT / 2.0];
+            }
+            bg_view.addSubview(&bar);
+            countdown_bar = Some((bar, full_w));
+        }
+
+        if let Some(secondary_label) = &secondary_label {
+            if opaque_card {
+                secondary_label
+                    .setTextColor(Some(&NSColor::colorWithWhite_alpha(CARD_SECONDARY_WHITE, 1.0)));
+            }
+            secondary_label.setFrame(NSRect::new(
+                NSPoint::new(PADDING, panel_h - PADDING - text_h - SECONDARY_GAP - secondary_h),
+                NSSize::new(panel_w - PADDING * 2.0, secondary_h),
+            ));
+            bg_view.addSubview(secondary_label);
+        }
+
         panel.setContentView(Some(&bg_view));
         panel.orderFrontRegardless();
 
         // Set up animation state
-        PHASE = PHASE_FADE_IN;
-        ANIM_START = Some(Instant::now());
+        let now = Instant::now();
+        let mut popup = Popup {
+            id: NEXT_POPUP_ID,
+            panel,
+            phase: PHASE_FADE_IN,
+            anim_start: now,
+            shown_at: now,
+            target_y: y,
+            positioned_above: above,
+            deselect_close: false,
+            hide_deadline: None,
+            hide_total_secs: 0.0,
+            countdown_bar,
+            quartz_rect,
+        };
+        NEXT_POPUP_ID += 1;
 
         // Auto-hide deadline: reading time based on char count
-        let total_chars = translated.chars().count();
-        let reading_secs = (total_chars as f64 / 15.0).clamp(2.0, 20.0);
-        let total_ms = FADE_IN_MS + reading_secs * 1000.0 + 3000.0;
-        AUTO_HIDE_DEADLINE =
-            Some(Instant::now() + std::time::Duration::from_millis(total_ms as u64));
+        let total_ms = auto_hide_ms(translated, content);
+        set_auto_hide(&mut popup, std::time::Duration::from_millis(total_ms as u64));
+
+        popups().push(popup);
+    }
+}
+
+/// Show `text` briefly in a popup centered on the screen under the pointer.
+pub fn show_status(text: &str) {
+    let (x, y) = unsafe {
+        let current = CGEventCreate(std::ptr::null());
+        if current.is_null() {
+            (0, 0)
+        } else {
+            let point = CGEventGetLocation(current);
+            CFRelease(current);
+            (point.x as i32, point.y as i32)
+        }
+    };
+    let pos = SelectionPos { down_x: x, down_y: y, up_x: x, up_y: y };
+    show_popup("", text, None, 0, pos, PopupContent::Status);
+}
 
-        POPUP_PANEL = Some(panel);
+/// With `max_popups`, move a new popup at `y` (AppKit coordinates) off the
+/// popups still up, past each one it overlaps, away from the selection.
+unsafe fn stack_clear_of_popups(x: f64, mut y: f64, w: f64, h: f64, above: bool) -> f64 {
+    // Each step clears one popup, so one pass per popup is enough
+    for _ in 0..popups().len() {
+        let overlapped = popups().iter().map(|p| (p.panel.frame(), p.target_y)).find(|(f, top)| {
+            x < f.origin.x + f.size.width && x + w > f.origin.x && y < top + f.size.height && y + h > *top
+        });
+        let Some((frame, other_y)) = overlapped else {
+            break;
+        };
+        y = if above { other_y - h - GAP_ABOVE } else { other_y + frame.size.height + GAP_ABOVE };
     }
+    y
+}
+
+/// The font mapped to the script of `text` in `popup_font_by_script`, else
+/// the system font (its cascade list already covers CJK and other scripts).
+fn font_for(text: &str, size: f64) -> Retained<NSFont> {
+    configured_font_for(text)
+        .and_then(|name| {
+            let font = NSFont::fontWithName_size(&NSString::from_str(&name), size);
+            if font.is_none() {
+                tracing::warn!("Font \"{}\" from popup_font_by_script not found", name);
+            }
+            font
+        })
+        .unwrap_or_else(|| NSFont::systemFontOfSize(size))
 }
 
 // ---------------------------------------------------------------------------
-// Background view: Liquid Glass (macOS 26+) or NSVisualEffectView fallback
+// Background view: Liquid Glass (macOS 26+), NSVisualEffectView, or opaque card
 // ---------------------------------------------------------------------------
 
+/// Returns the view plus whether it is the opaque dark card (which needs light text).
 unsafe fn create_background_view(
     width: f64,
     height: f64,
     mtm: MainThreadMarker,
-) -> Retained<NSView> {
+) -> (Retained<NSView>, bool) {
     let frame = NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(width, height));
+    // The plain renderer goes straight to the opaque card, without rounded corners
+    let plain = plain_renderer();
 
     // Try NSGlassEffectView (macOS 26 Tahoe+)
-    {
+    if !plain {
         let name = c"NSGlassEffectView";
         if let Some(glass_cls) = AnyClass::get(name) {
             let view: *mut NSView = msg_send![glass_cls, alloc];
             let view: *mut NSView = msg_send![view, initWithFrame: frame];
             if !view.is_null() {
                 let _: () = msg_send![view, setCornerRadius: CORNER_RADIUS];
-                return Retained::from_raw(view).unwrap();
+                log_background_path("NSGlassEffectView");
+                return (Retained::from_raw(view).unwrap(), false);
             }
         }
     }
 
-    // Fallback: NSVisualEffectView with HUD material
-    let effect_view =
-        NSVisualEffectView::initWithFrame(NSVisualEffectView::alloc(mtm), frame);
-    effect_view.setMaterial(NSVisualEffectMaterial::HUDWindow);
-    effect_view.setBlendingMode(NSVisualEffectBlendingMode::BehindWindow);
-    effect_view.setState(NSVisualEffectState::Active);
-    effect_view.setWantsLayer(true);
+    // Fallback: NSVisualEffectView with the configured material (HUD by default)
+    if !plain && AnyClass::get(c"NSVisualEffectView").is_some() {
+        let effect_view =
+            NSVisualEffectView::initWithFrame(NSVisualEffectView::alloc(mtm), frame);
+        effect_view.setMaterial(effect_material(popup_options().material));
+        effect_view.setBlendingMode(NSVisualEffectBlendingMode::BehindWindow);
+        effect_view.setState(NSVisualEffectState::Active);
+        effect_view.setWantsLayer(true);
+
+        // Round corners and subtle border via CALayer. Without a backing layer
+        // (headless sessions) the blur never composites, so fall through.
+        if let Some(layer) = effect_view.layer() {
+            let _: () = msg_send![&layer, setCornerRadius: CORNER_RADIUS];
+            let _: () = msg_send![&layer, setMasksToBounds: true];
+            let _: () = msg_send![&layer, setBorderWidth: 0.5f64];
+
+            // White border at 0.2 alpha
+            let border_color = NSColor::colorWithWhite_alpha(1.0, 0.2);
+            let cg_color: *mut c_void = msg_send![&border_color, CGColor];
+            if !cg_color.is_null() {
+                let _: () = msg_send![&layer, setBorderColor: cg_color];
+            }
+
+            log_background_path("NSVisualEffectView");
+            // Upcast NSVisualEffectView to NSView
+            return (Retained::into_super(effect_view), false);
+        }
+    }
 
-    // Round corners and subtle border via CALayer
-    if let Some(layer) = effect_view.layer() {
-        let _: () = msg_send![&layer, setCornerRadius: CORNER_RADIUS];
+    // Last resort: plain NSView with an opaque dark card (matches the Windows popup),
+    // so the popup is never invisible on top of the clear panel background.
+    let view = NSView::initWithFrame(NSView::alloc(mtm), frame);
+    view.setWantsLayer(true);
+    if let Some(layer) = view.layer() {
+        let radius = if plain { 0.0 } else { CORNER_RADIUS };
+        let _: () = msg_send![&layer, setCornerRadius: radius];
         let _: () = msg_send![&layer, setMasksToBounds: true];
-        let _: () = msg_send![&layer, setBorderWidth: 0.5f64];
+        let _: () = msg_send![&layer, setBorderWidth: 1.0f64];
 
-        // White border at 0.2 alpha
-        let border_color = NSColor::colorWithWhite_alpha(1.0, 0.2);
-        let cg_color: *mut c_void = msg_send![&border_color, CGColor];
-        if !cg_color.is_null() {
-            let _: () = msg_send![&layer, setBorderColor: cg_color];
+        let bg_color = NSColor::colorWithWhite_alpha(CARD_BG_WHITE, 1.0);
+        let cg_bg: *mut c_void = msg_send![&bg_color, CGColor];
+        if !cg_bg.is_null() {
+            let _: () = msg_send![&layer, setBackgroundColor: cg_bg];
         }
+        let border_color = NSColor::colorWithWhite_alpha(CARD_BORDER_WHITE, 1.0);
+        let cg_border: *mut c_void = msg_send![&border_color, CGColor];
+        if !cg_border.is_null() {
+            let _: () = msg_send![&layer, setBorderColor: cg_border];
+        }
+    }
+    log_background_path("opaque NSView card");
+    (view, true)
+}
+
+/// Transparent, shadowed panel behind the styled card; an opaque panel in the
+/// card color for the plain renderer, which must not depend on compositing.
+unsafe fn set_card_backdrop(panel: &NSPanel) {
+    if plain_renderer() {
+        panel.setOpaque(true);
+        panel.setBackgroundColor(Some(&NSColor::colorWithWhite_alpha(CARD_BG_WHITE, 1.0)));
+        panel.setHasShadow(false);
+    } else {
+        panel.setOpaque(false);
+        panel.setBackgroundColor(Some(&NSColor::clearColor()));
+        panel.setHasShadow(true);
+    }
+}
+
+/// Resting opacity of the popup and sidebar; plain ones are fully opaque.
+fn card_alpha() -> f64 {
+    if plain_renderer() { 1.0 } else { MAX_ALPHA }
+}
+
+fn effect_material(material: PopupMaterial) -> NSVisualEffectMaterial {
+    match material {
+        PopupMaterial::Hud => NSVisualEffectMaterial::HUDWindow,
+        PopupMaterial::Popover => NSVisualEffectMaterial::Popover,
+        PopupMaterial::Menu => NSVisualEffectMaterial::Menu,
+        PopupMaterial::Sidebar => NSVisualEffectMaterial::Sidebar,
+        PopupMaterial::Sheet => NSVisualEffectMaterial::Sheet,
+        PopupMaterial::Tooltip => NSVisualEffectMaterial::ToolTip,
+        PopupMaterial::Titlebar => NSVisualEffectMaterial::Titlebar,
+        PopupMaterial::WindowBackground => NSVisualEffectMaterial::WindowBackground,
+        PopupMaterial::UnderWindow => NSVisualEffectMaterial::UnderWindowBackground,
     }
+}
 
-    // Upcast NSVisualEffectView to NSView
-    Retained::into_super(effect_view)
+/// Log the background path once per launch (it doesn't change between popups).
+fn log_background_path(path: &str) {
+    static LOGGED: AtomicBool = AtomicBool::new(false);
+    if !LOGGED.swap(true, Ordering::Relaxed) {
+        tracing::info!("Popup background: {}", path);
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -618,63 +1696,67 @@ unsafe fn create_background_view(
 
 fn animate_popup() {
     unsafe {
-        let panel = match (*std::ptr::addr_of!(POPUP_PANEL)).as_ref() {
-            Some(p) => p,
-            None => return,
-        };
-
-        let elapsed = match ANIM_START {
-            Some(s) => s.elapsed().as_secs_f64() * 1000.0,
-            None => return,
-        };
-
-        match PHASE {
-            PHASE_FADE_IN => {
-                let t = (elapsed / FADE_IN_MS).min(1.0);
-                let ease = ease_out_cubic(t);
-
-                panel.setAlphaValue(ease * MAX_ALPHA);
-
-                // Slide toward target
-                let offset = (1.0 - ease) * SLIDE_PX;
-                let slide_y = if POSITIONED_ABOVE {
-                    TARGET_Y - offset
-                } else {
-                    TARGET_Y + offset
-                };
-                let mut frame = panel.frame();
-                frame.origin.y = slide_y;
-                panel.setFrame_display(frame, true);
-
-                if t >= 1.0 {
-                    PHASE = PHASE_VISIBLE;
-                    panel.setAlphaValue(MAX_ALPHA);
-                    frame.origin.y = TARGET_Y;
-                    panel.setFrame_display(frame, false);
+        let opts = popup_options();
+        let slide_px = if opts.slide { SLIDE_PX } else { 0.0 };
+        let fade = |visible: f64| if opts.fade { visible * card_alpha() } else { card_alpha() };
+        let mut finished = Vec::new();
+
+        for p in popups().iter_mut() {
+            let elapsed = p.anim_start.elapsed().as_secs_f64() * 1000.0;
+            let panel = &p.panel;
+
+            match p.phase {
+                PHASE_FADE_IN => {
+                    let t = anim_progress(elapsed, opts.fade_in_ms);
+                    let ease = ease_out_cubic(t);
+
+                    panel.setAlphaValue(fade(ease));
+
+                    // Slide toward target
+                    let offset = (1.0 - ease) * slide_px;
+                    let slide_y = if p.positioned_above {
+                        p.target_y - offset
+                    } else {
+                        p.target_y + offset
+                    };
+                    let mut frame = panel.frame();
+                    frame.origin.y = slide_y;
+                    panel.setFrame_display(frame, true);
+
+                    if t >= 1.0 {
+                        p.phase = PHASE_VISIBLE;
+                        panel.setAlphaValue(card_alpha());
+                        frame.origin.y = p.target_y;
+                        panel.setFrame_display(frame, false);
+                    }
                 }
-            }
-            PHASE_FADE_OUT => {
-                let duration = if DESELECT_CLOSE {
-                    FADE_OUT_DESELECT_MS
-                } else {
-                    FADE_OUT_MS
-                };
-                let t = (elapsed / duration).min(1.0);
-                let ease = ease_in_cubic(t);
-
-                panel.setAlphaValue((1.0 - ease) * MAX_ALPHA);
-
-                // Slide slightly upward while fading
-                let offset = ease * (SLIDE_PX / 2.0);
-                let mut frame = panel.frame();
-                frame.origin.y = TARGET_Y + offset;
-                panel.setFrame_display(frame, false);
+                PHASE_FADE_OUT => {
+                    let duration = if p.deselect_close {
+                        opts.fade_out_deselect_ms
+                    } else {
+                        opts.fade_out_ms
+                    };
+                    let t = anim_progress(elapsed, duration);
+                    let ease = ease_in_cubic(t);
+
+                    panel.setAlphaValue(fade(1.0 - ease));
+
+                    // Slide slightly upward while fading
+                    let offset = ease * (slide_px / 2.0);
+                    let mut frame = panel.frame();
+                    frame.origin.y = p.target_y + offset;
+                    panel.setFrame_display(frame, false);
 
-                if t >= 1.0 {
-                    destroy_popup();
+                    if t >= 1.0 {
+                        finished.push(p.id);
+                    }
                 }
+                _ => {}
             }
-            _ => {}
+        }
+
+        for id in finished {
+            destroy_popup(id);
         }
     }
 }
@@ -688,38 +1770,599 @@ fn ease_in_cubic(t: f64) -> f64 {
     t * t * t
 }
 
+/// `frame` (AppKit coordinates) as a Quartz-space rect.
+fn to_quartz_rect(frame: NSRect, screen_h: f64) -> NSRect {
+    NSRect::new(
+        NSPoint::new(frame.origin.x, screen_h - frame.origin.y - frame.size.height),
+        frame.size,
+    )
+}
+
+fn rect_contains(r: NSRect, pt: NSPoint) -> bool {
+    pt.x >= r.origin.x
+        && pt.x < r.origin.x + r.size.width
+        && pt.y >= r.origin.y
+        && pt.y < r.origin.y + r.size.height
+}
+
+#[allow(clippy::deref_addrof)]
+unsafe fn popups() -> &'static mut Vec<Popup> {
+    &mut *(&raw mut POPUPS)
+}
+
+unsafe fn popup_mut(id: u64) -> Option<&'static mut Popup> {
+    popups().iter_mut().find(|p| p.id == id)
+}
+
+/// The topmost popup at a Quartz-space point.
+unsafe fn popup_at(x: f64, y: f64) -> Option<u64> {
+    popups()
+        .iter()
+        .rev()
+        .find(|p| {
+            let r = p.quartz_rect;
+            x >= r.origin.x
+                && x < r.origin.x + r.size.width
+                && y >= r.origin.y
+                && y < r.origin.y + r.size.height
+        })
+        .map(|p| p.id)
+}
+
+/// AppKit has moved the panel; keep it where it was dropped, fully visible,
+/// and restart the auto-hide countdown from there.
+fn finish_popup_drag(id: u64) {
+    let Some(mtm) = MainThreadMarker::new() else {
+        return;
+    };
+    unsafe {
+        let Some(p) = popup_mut(id) else {
+            return;
+        };
+        let frame = p.panel.frame();
+        p.target_y = frame.origin.y;
+        p.phase = PHASE_VISIBLE;
+        p.panel.setAlphaValue(card_alpha());
+        extend_auto_hide(p);
+
+        let screens = NSScreen::screens(mtm);
+        if screens.count() > 0 {
+            let primary: Retained<NSScreen> = screens.objectAtIndex(0);
+            p.quartz_rect = to_quartz_rect(frame, primary.frame().size.height);
+        }
+    }
+}
+
+/// Monitors changed while popups are up: keep each inside a screen's visible
+/// area, or dismiss it if the screen it was on is gone.
+fn on_display_change() {
+    let Some(mtm) = MainThreadMarker::new() else {
+        return;
+    };
+    unsafe {
+        let screens = NSScreen::screens(mtm);
+        if screens.count() == 0 {
+            return;
+        }
+        for p in popups().iter_mut().filter(|p| p.phase != PHASE_FADE_OUT) {
+            keep_on_screen(p, &screens);
+        }
+    }
+}
+
+unsafe fn keep_on_screen(p: &mut Popup, screens: &NSArray<NSScreen>) {
+    let panel = &p.panel;
+    let mut frame = panel.frame();
+    frame.origin.y = p.target_y;
+    let center = NSPoint::new(
+        frame.origin.x + frame.size.width / 2.0,
+        frame.origin.y + frame.size.height / 2.0,
+    );
+
+    let mut visible = None;
+    for i in 0..screens.count() {
+        let screen: Retained<NSScreen> = screens.objectAtIndex(i);
+        let sf = screen.frame();
+        if center.x >= sf.origin.x
+            && center.x < sf.origin.x + sf.size.width
+            && center.y >= sf.origin.y
+            && center.y < sf.origin.y + sf.size.height
+        {
+            visible = Some(screen.visibleFrame());
+            break;
+        }
+    }
+    let primary: Retained<NSScreen> = screens.objectAtIndex(0);
+    let visible = match visible {
+        Some(v) => v,
+        None if popup_options().display_change == DisplayChange::Dismiss => {
+            tracing::info!("Popup's display disconnected, dismissing");
+            begin_fade_out(p, true);
+            return;
+        }
+        None => primary.visibleFrame(),
+    };
+
+    let max_x = visible.origin.x + visible.size.width - frame.size.width - MARGIN;
+    let max_y = visible.origin.y + visible.size.height - frame.size.height - MARGIN;
+    frame.origin.x = frame.origin.x.min(max_x).max(visible.origin.x + MARGIN);
+    frame.origin.y = frame.origin.y.min(max_y).max(visible.origin.y + MARGIN);
+    p.target_y = frame.origin.y;
+    panel.setFrame_display(frame, true);
+    p.quartz_rect = to_quartz_rect(frame, primary.frame().size.height);
+}
+
+/// Outline the rectangle between a selection's press and release points
+/// (`debug_overlay`), replacing the previous outline. It can't be clicked and
+/// goes away after `SELECTION_RECT_DURATION`.
+pub fn show_selection_rect(pos: SelectionPos) {
+    let Some(mtm) = MainThreadMarker::new() else {
+        return;
+    };
+    unsafe {
+        if let Some((panel, _)) = (*std::ptr::addr_of_mut!(SELECTION_RECT_PANEL)).take() {
+            panel.orderOut(None);
+        }
+        let screens = NSScreen::screens(mtm);
+        if screens.count() == 0 {
+            return;
+        }
+        // Quartz (top-left origin) → AppKit (bottom-left origin)
+        let screen_h = screens.objectAtIndex(0).frame().size.height;
+        let bottom_q = pos.down_y.max(pos.up_y) as f64;
+        let frame = NSRect::new(
+            NSPoint::new(
+                pos.down_x.min(pos.up_x) as f64 - SELECTION_RECT_BORDER,
+                screen_h - bottom_q - SELECTION_RECT_BORDER,
+            ),
+            NSSize::new(
+                (pos.down_x - pos.up_x).abs() as f64 + SELECTION_RECT_BORDER * 2.0,
+                (pos.down_y - pos.up_y).abs() as f64 + SELECTION_RECT_BORDER * 2.0,
+            ),
+        );
+
+        let panel = NSPanel::initWithContentRect_styleMask_backing_defer(
+            NSPanel::alloc(mtm),
+            frame,
+            NSWindowStyleMask::Borderless | NSWindowStyleMask::NonactivatingPanel,
+            NSBackingStoreType::Buffered,
+            false,
+        );
+        panel.setLevel(CGWindowLevelForKey(K_CG_FLOATING_WINDOW_LEVEL_KEY) as isize);
+        panel.setOpaque(false);
+        panel.setBackgroundColor(Some(&NSColor::clearColor()));
+        panel.setHasShadow(false);
+        panel.setIgnoresMouseEvents(true);
+
+        let view = NSView::initWithFrame(
+            NSView::alloc(mtm),
+            NSRect::new(NSPoint::new(0.0, 0.0), frame.size),
+        );
+        view.setWantsLayer(true);
+        if let Some(layer) = view.layer() {
+            let _: () = msg_send![&layer, setBorderWidth: SELECTION_RECT_BORDER];
+            let cg_color: *mut c_void = msg_send![&NSColor::systemRedColor(), CGColor];
+            if !cg_color.is_null() {
+                let _: () = msg_send![&layer, setBorderColor: cg_color];
+            }
+        }
+        panel.setContentView(Some(&view));
+        panel.orderFrontRegardless();
+        SELECTION_RECT_PANEL = Some((panel, Instant::now() + SELECTION_RECT_DURATION));
+    }
+}
+
+unsafe fn expire_selection_rect() {
+    let slot = &mut *std::ptr::addr_of_mut!(SELECTION_RECT_PANEL);
+    if let Some((panel, _)) = slot.take_if(|(_, until)| Instant::now() >= *until) {
+        panel.orderOut(None);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Sidebar: one docked panel collecting translations, newest at top
+// ---------------------------------------------------------------------------
+
+/// Append a translation to the docked sidebar, creating it on first use.
+/// The sidebar stays up until the app exits.
+pub fn show_in_sidebar(original: &str, translated: &str) {
+    let Some(mtm) = MainThreadMarker::new() else {
+        tracing::warn!("show_in_sidebar called off main thread");
+        return;
+    };
+
+    unsafe {
+        #[allow(clippy::deref_addrof)]
+        let entries = &mut *(&raw mut SIDEBAR_ENTRIES);
+        entries.insert(0, (original.to_string(), translated.to_string()));
+        entries.truncate(SIDEBAR_MAX_ENTRIES);
+
+        if (*std::ptr::addr_of!(SIDEBAR_PANEL)).is_none() && !create_sidebar(mtm) {
+            return;
+        }
+        layout_sidebar(mtm);
+    }
+}
+
+unsafe fn create_sidebar(mtm: MainThreadMarker) -> bool {
+    let screens = NSScreen::screens(mtm);
+    if screens.count() == 0 {
+        return false;
+    }
+    let primary: Retained<NSScreen> = screens.objectAtIndex(0);
+    let frame = sidebar_frame(primary.visibleFrame());
+
+    let panel = NSPanel::initWithContentRect_styleMask_backing_defer(
+        NSPanel::alloc(mtm),
+        frame,
+        NSWindowStyleMask::Borderless | NSWindowStyleMask::NonactivatingPanel,
+        NSBackingStoreType::Buffered,
+        false,
+    );
+    panel.setLevel(CGWindowLevelForKey(K_CG_FLOATING_WINDOW_LEVEL_KEY) as isize);
+    set_card_backdrop(&panel);
+    panel.setHidesOnDeactivate(false);
+    panel.setAlphaValue(card_alpha());
+
+    let (bg_view, opaque_card) = create_background_view(frame.size.width, frame.size.height, mtm);
+    let scroll = NSScrollView::initWithFrame(
+        NSScrollView::alloc(mtm),
+        NSRect::new(
+            NSPoint::new(0.0, PADDING),
+            NSSize::new(frame.size.width, frame.size.height - PADDING * 2.0),
+        ),
+    );
+    scroll.setDrawsBackground(false);
+    scroll.setHasVerticalScroller(true);
+    scroll.setAutohidesScrollers(true);
+    bg_view.addSubview(&scroll);
+
+    panel.setContentView(Some(&bg_view));
+    panel.orderFrontRegardless();
+
+    cache_sidebar_rect(frame, primary.frame().size.height);
+    SIDEBAR_OPAQUE_CARD = opaque_card;
+    SIDEBAR_SCROLL_VIEW = Some(scroll);
+    SIDEBAR_PANEL = Some(panel);
+    true
+}
+
+/// Full height of `visible`, along its right edge.
+fn sidebar_frame(visible: NSRect) -> NSRect {
+    NSRect::new(
+        NSPoint::new(
+            visible.origin.x + visible.size.width - SIDEBAR_WIDTH - MARGIN,
+            visible.origin.y + MARGIN,
+        ),
+        NSSize::new(SIDEBAR_WIDTH, visible.size.height - MARGIN * 2.0),
+    )
+}
+
+/// Rebuild the scrolled stack of entries and scroll back to the newest.
+unsafe fn layout_sidebar(mtm: MainThreadMarker) {
+    let Some(scroll) = (*std::ptr::addr_of!(SIDEBAR_SCROLL_VIEW)).as_ref() else {
+        return;
+    };
+    let width = scroll.contentSize().width;
+    let content_w = width - PADDING * 2.0;
+    let (text_white, secondary_white) = if SIDEBAR_OPAQUE_CARD {
+        (
+            Some(NSColor::colorWithWhite_alpha(CARD_TEXT_WHITE, 1.0)),
+            Some(NSColor::colorWithWhite_alpha(CARD_SECONDARY_WHITE, 1.0)),
+        )
+    } else {
+        (None, None)
+    };
+
+    let make_label = |text: &str, size: f64, color: Retained<NSColor>| {
+        let label = NSTextField::wrappingLabelWithString(&NSString::from_str(text), mtm);
+        label.setFont(Some(&font_for(text, size)));
+        label.setTextColor(Some(&color));
+        label.setAlignment(match text_align_for(text) {
+            TextAlign::Center => NSTextAlignment::Center,
+            TextAlign::End => NSTextAlignment::Right,
+            _ => NSTextAlignment::Left,
+        });
+        label.setPreferredMaxLayoutWidth(content_w);
+        let height = label.fittingSize().height;
+        (label, height)
+    };
+
+    // Measure top-down, then place bottom-up: the document view isn't flipped
+    let mut labels = Vec::new();
+    let mut total_h = 0.0;
+    #[allow(clippy::deref_addrof)]
+    let entries = &*(&raw const SIDEBAR_ENTRIES);
+    for (i, (original, translated)) in entries.iter().enumerate() {
+        if i > 0 {
+            total_h += SIDEBAR_ENTRY_GAP;
+        }
+        let translated = make_label(
+            translated,
+            FONT_SIZE,
+            text_white.clone().unwrap_or_else(NSColor::labelColor),
+        );
+        total_h += translated.1 + SECONDARY_GAP;
+        let original = make_label(
+            original,
+            SECONDARY_FONT_SIZE,
+            secondary_white.clone().unwrap_or_else(NSColor::secondaryLabelColor),
+        );
+        total_h += original.1;
+        labels.push(translated);
+        labels.push(original);
+    }
+
+    let doc_h = total_h.max(scroll.contentSize().height);
+    let doc = NSView::initWithFrame(
+        NSView::alloc(mtm),
+        NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(width, doc_h)),
+    );
+    let mut top = doc_h;
+    for (i, (label, height)) in labels.iter().enumerate() {
+        if i > 0 {
+            // Translation and its original alternate
+            top -= if i % 2 == 1 { SECONDARY_GAP } else { SIDEBAR_ENTRY_GAP };
+        }
+        label.setFrame(NSRect::new(
+            NSPoint::new(PADDING, top - height),
+            NSSize::new(content_w, *height),
+        ));
+        doc.addSubview(label);
+        top -= height;
+    }
+
+    scroll.setDocumentView(Some(&doc));
+    let _: () = msg_send![&doc, scrollPoint: NSPoint::new(0.0, doc_h)];
+}
+
+/// Monitors changed: dock the sidebar to the primary screen again.
+unsafe fn redock_sidebar(mtm: MainThreadMarker) {
+    let Some(panel) = (*std::ptr::addr_of!(SIDEBAR_PANEL)).as_ref() else {
+        return;
+    };
+    let screens = NSScreen::screens(mtm);
+    if screens.count() == 0 {
+        return;
+    }
+    let primary: Retained<NSScreen> = screens.objectAtIndex(0);
+    let frame = sidebar_frame(primary.visibleFrame());
+    panel.setFrame_display(frame, true);
+    if let Some(content) = panel.contentView() {
+        content.setFrameSize(frame.size);
+    }
+    if let Some(scroll) = (*std::ptr::addr_of!(SIDEBAR_SCROLL_VIEW)).as_ref() {
+        scroll.setFrameSize(NSSize::new(frame.size.width, frame.size.height - PADDING * 2.0));
+    }
+    cache_sidebar_rect(frame, primary.frame().size.height);
+    layout_sidebar(mtm);
+}
+
+/// Cache `frame` (AppKit coordinates) as the sidebar's Quartz-space rect.
+unsafe fn cache_sidebar_rect(frame: NSRect, screen_h: f64) {
+    SIDEBAR_QUARTZ_RECT = Some(NSRect::new(
+        NSPoint::new(frame.origin.x, screen_h - frame.origin.y - frame.size.height),
+        frame.size,
+    ));
+}
+
+/// Whether a Quartz-space point falls inside the sidebar.
+unsafe fn sidebar_contains(x: f64, y: f64) -> bool {
+    match SIDEBAR_QUARTZ_RECT {
+        Some(r) => {
+            x >= r.origin.x
+                && x < r.origin.x + r.size.width
+                && y >= r.origin.y
+                && y < r.origin.y + r.size.height
+        }
+        None => false,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// "Translate Text" window: a text field, translated as you type
+// ---------------------------------------------------------------------------
+
+/// Open the "Translate Text" window, or bring it back to the front.
+pub fn open_input_window() {
+    let Some(mtm) = MainThreadMarker::new() else {
+        tracing::warn!("open_input_window called off main thread");
+        return;
+    };
+
+    unsafe {
+        if let Some(panel) = (*std::ptr::addr_of!(INPUT_PANEL)).as_ref() {
+            panel.makeKeyAndOrderFront(None);
+            return;
+        }
+        let screens = NSScreen::screens(mtm);
+        if screens.count() == 0 {
+            return;
+        }
+        let visible = screens.objectAtIndex(0).visibleFrame();
+        let content_h = PADDING + INPUT_FIELD_HEIGHT + PADDING / 2.0 + INPUT_RESULT_HEIGHT + PADDING;
+        let frame = NSRect::new(
+            NSPoint::new(
+                visible.origin.x + (visible.size.width - INPUT_WIDTH) / 2.0,
+                visible.origin.y + visible.size.height * 2.0 / 3.0 - content_h / 2.0,
+            ),
+            NSSize::new(INPUT_WIDTH, content_h),
+        );
+
+        // Non-activating so typing here doesn't pull the app in front of the
+        // one being read, but titled so it can take keyboard focus
+        let panel = NSPanel::initWithContentRect_styleMask_backing_defer(
+            NSPanel::alloc(mtm),
+            frame,
+            NSWindowStyleMask::Titled | NSWindowStyleMask::Closable | NSWindowStyleMask::NonactivatingPanel,
+            NSBackingStoreType::Buffered,
+            false,
+        );
+        panel.setTitle(&NSString::from_str(tr("input.title")));
+        panel.setLevel(CGWindowLevelForKey(K_CG_FLOATING_WINDOW_LEVEL_KEY) as isize);
+        panel.setHidesOnDeactivate(false);
+        // Kept in INPUT_PANEL; closing must not free it underneath us
+        panel.setReleasedWhenClosed(false);
+
+        let field = NSTextField::textFieldWithString(&NSString::from_str(""), mtm);
+        field.setFrame(NSRect::new(
+            NSPoint::new(PADDING, content_h - PADDING - INPUT_FIELD_HEIGHT),
+            NSSize::new(INPUT_WIDTH - PADDING * 2.0, INPUT_FIELD_HEIGHT),
+        ));
+        field.setFont(Some(&NSFont::systemFontOfSize(FONT_SIZE)));
+        field.setPlaceholderString(Some(&NSString::from_str(tr("input.placeholder"))));
+
+        let result = NSTextField::wrappingLabelWithString(&NSString::from_str(""), mtm);
+        result.setFrame(NSRect::new(
+            NSPoint::new(PADDING, PADDING),
+            NSSize::new(INPUT_WIDTH - PADDING * 2.0, INPUT_RESULT_HEIGHT),
+        ));
+        result.setFont(Some(&NSFont::systemFontOfSize(FONT_SIZE)));
+        result.setSelectable(true);
+
+        if let Some(content) = panel.contentView() {
+            content.addSubview(&field);
+            content.addSubview(&result);
+        }
+        panel.makeKeyAndOrderFront(None);
+        panel.makeFirstResponder(Some(&field));
+
+        INPUT_LAST_TEXT.lock().unwrap().clear();
+        INPUT_FIELD = Some(field);
+        INPUT_RESULT = Some(result);
+        INPUT_PANEL = Some(panel);
+    }
+}
+
+/// What changed in the "Translate Text" field since the last call.
+pub fn poll_input_event() -> Option<InputEvent> {
+    unsafe {
+        let panel = (*std::ptr::addr_of!(INPUT_PANEL)).as_ref()?;
+        if !panel.isVisible() {
+            // Closed with the title bar button
+            close_input_window();
+            return None;
+        }
+        let text = (*std::ptr::addr_of!(INPUT_FIELD)).as_ref()?.stringValue().to_string();
+        let mut last = INPUT_LAST_TEXT.lock().unwrap();
+        if INPUT_SUBMITTED.swap(false, Ordering::Relaxed) {
+            *last = text.clone();
+            return Some(InputEvent::Submitted(text));
+        }
+        if *last == text {
+            return None;
+        }
+        *last = text.clone();
+        Some(InputEvent::Changed(text))
+    }
+}
+
+/// Show `text` under the "Translate Text" field, if the window is open.
+pub fn set_input_result(text: &str) {
+    unsafe {
+        if let Some(result) = (*std::ptr::addr_of!(INPUT_RESULT)).as_ref() {
+            result.setStringValue(&NSString::from_str(text));
+        }
+    }
+}
+
+/// Enter translates right away and Escape closes the window. Returns
+/// whether `event` was handled and must not be sent on.
+unsafe fn handle_input_key(event: &NSEvent) -> bool {
+    let Some(panel) = (*std::ptr::addr_of!(INPUT_PANEL)).as_ref() else {
+        return false;
+    };
+    if event.r#type() != NSEventType::KeyDown || !panel.isKeyWindow() {
+        return false;
+    }
+    match event.keyCode() {
+        KEYCODE_RETURN => INPUT_SUBMITTED.store(true, Ordering::Relaxed),
+        KEYCODE_ESCAPE => close_input_window(),
+        _ => return false,
+    }
+    true
+}
+
+unsafe fn close_input_window() {
+    if let Some(panel) = (*std::ptr::addr_of_mut!(INPUT_PANEL)).take() {
+        panel.orderOut(None);
+    }
+    INPUT_FIELD = None;
+    INPUT_RESULT = None;
+}
+
 // ---------------------------------------------------------------------------
 // on_click_away / destroy_popup
 // ---------------------------------------------------------------------------
 
 pub fn on_click_away() {
     unsafe {
-        if (*std::ptr::addr_of!(POPUP_PANEL)).is_none() || PHASE == PHASE_FADE_OUT {
-            return;
+        for p in popups().iter_mut() {
+            begin_fade_out(p, true);
         }
-        begin_fade_out(true);
     }
 }
 
-unsafe fn begin_fade_out(is_deselect: bool) {
-    if PHASE == PHASE_FADE_OUT || PHASE == PHASE_NONE {
+/// Auto-hide delay after the user hovers over or moves the popup.
+fn interaction_hide_delay() -> std::time::Duration {
+    std::time::Duration::from_secs_f64(popup_options().interaction_extend_ms / 1000.0)
+}
+
+/// After the user hovers over or moves the popup, leave it at least
+/// `interaction_extend_ms` before hiding; a later deadline is kept.
+fn extend_auto_hide(p: &mut Popup) {
+    let after = interaction_hide_delay();
+    if p.hide_deadline.is_some_and(|deadline| deadline >= Instant::now() + after) {
         return;
     }
-    PHASE = PHASE_FADE_OUT;
-    DESELECT_CLOSE = is_deselect;
-    ANIM_START = Some(Instant::now());
-    AUTO_HIDE_DEADLINE = None;
+    set_auto_hide(p, after);
 }
 
-fn destroy_popup() {
+/// Restart the auto-hide countdown with `after` to go.
+fn set_auto_hide(p: &mut Popup, after: std::time::Duration) {
+    p.hide_deadline = Some(Instant::now() + after);
+    p.hide_total_secs = after.as_secs_f64();
+}
+
+/// Shrink the countdown bar to the share of auto-hide time left. It stays
+/// as it is once the countdown stops (fading out).
+fn update_countdown_bar(p: &Popup) {
+    let (Some((bar, full_w)), Some(deadline)) = (&p.countdown_bar, p.hide_deadline) else {
+        return;
+    };
+    let left = deadline.saturating_duration_since(Instant::now()).as_secs_f64();
+    let mut frame = bar.frame();
+    frame.size.width = full_w * (left / p.hide_total_secs.max(0.001)).clamp(0.0, 1.0);
+    bar.setFrame(frame);
+}
+
+fn begin_fade_out(p: &mut Popup, is_deselect: bool) {
+    if p.phase == PHASE_FADE_OUT || p.phase == PHASE_NONE {
+        return;
+    }
+    p.phase = PHASE_FADE_OUT;
+    p.deselect_close = is_deselect;
+    p.anim_start = Instant::now();
+    p.hide_deadline = None;
+}
+
+/// Whether a popup is on screen and not already on its way out.
+pub fn is_popup_visible() -> bool {
+    unsafe { popups().iter().any(|p| p.phase != PHASE_FADE_OUT) }
+}
+
+/// Remove every popup immediately, skipping the fade-out.
+pub fn hide_popup_now() {
     unsafe {
-        if let Some(panel) = (*std::ptr::addr_of_mut!(POPUP_PANEL)).take() {
-            panel.orderOut(None);
+        while let Some(p) = popups().last() {
+            destroy_popup(p.id);
         }
-        PHASE = PHASE_NONE;
-        ANIM_START = None;
-        DESELECT_CLOSE = false;
-        AUTO_HIDE_DEADLINE = None;
+    }
+}
+
+unsafe fn destroy_popup(id: u64) {
+    if let Some(i) = popups().iter().position(|p| p.id == id) {
+        popups().remove(i).panel.orderOut(None);
     }
 }
 
@@ -738,6 +2381,9 @@ fn pump_app_events(mtm: MainThreadMarker) {
             true,
         );
         let Some(event) = event else { break };
+        if unsafe { handle_input_key(&event) } {
+            continue;
+        }
         app.sendEvent(&event);
     }
 }
@@ -1,10 +1,520 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::text::{self, Script};
+
 #[allow(dead_code)]
 pub enum MouseEvent {
-    SelectionDone { down_x: i32, down_y: i32, up_x: i32, up_y: i32 },
+    /// `held_ms` is how long the trigger button was held, from the event timestamps.
+    SelectionDone { down_x: i32, down_y: i32, up_x: i32, up_y: i32, held_ms: u64 },
     Click,
+    /// A hotkey registered with `register_hotkey` was pressed (carries its id).
+    Hotkey(u32),
     Quit,
 }
 
+/// Typing in the "Translate Text" window.
+pub enum InputEvent {
+    /// The text changed; translated once typing pauses.
+    Changed(String),
+    /// Enter was pressed: translate now.
+    Submitted(String),
+}
+
+/// Mouse button whose press/release pair is treated as a selection gesture.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum TriggerButton {
+    Left = 0,
+    Middle = 1,
+    Right = 2,
+}
+
+impl TriggerButton {
+    /// Parse the `trigger_button` config value ("left", "middle", "right").
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "left" => Some(Self::Left),
+            "middle" => Some(Self::Middle),
+            "right" => Some(Self::Right),
+            _ => None,
+        }
+    }
+}
+
+// `hotkey::MOD_*` bits of the `suppress_modifier`, 0 = none. An atomic, as
+// the mouse hook reads it on every press and release.
+static SUPPRESS_MODIFIERS: AtomicU8 = AtomicU8::new(0);
+
+/// Selections made while any of these `hotkey::MOD_*` modifiers is held are
+/// ignored.
+pub fn set_suppress_modifiers(modifiers: u8) {
+    SUPPRESS_MODIFIERS.store(modifiers, Ordering::Relaxed);
+}
+
+fn suppress_modifiers() -> u8 {
+    SUPPRESS_MODIFIERS.load(Ordering::Relaxed)
+}
+
+/// The OS language as a translation language code, from `system_locale`:
+/// "de" for "de-AT", "zt" for Traditional Chinese. `None` if unreadable.
+pub fn system_language() -> Option<String> {
+    let tag = system_locale()?.replace('_', "-").to_ascii_lowercase();
+    let mut parts = tag.split('-');
+    let lang = parts
+        .next()
+        .filter(|lang| lang.len() == 2 && lang.chars().all(|c| c.is_ascii_alphabetic()))?;
+    if lang == "zh" && parts.any(|part| matches!(part, "hant" | "tw" | "hk" | "mo")) {
+        return Some("zt".into());
+    }
+    Some(lang.to_string())
+}
+
+/// Blur material for the macOS popup (maps to `NSVisualEffectMaterial`).
+#[cfg(target_os = "macos")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PopupMaterial {
+    Hud,
+    Popover,
+    Menu,
+    Sidebar,
+    Sheet,
+    Tooltip,
+    Titlebar,
+    WindowBackground,
+    UnderWindow,
+}
+
+#[cfg(target_os = "macos")]
+impl PopupMaterial {
+    /// Parse the `popup_material` config value, e.g. "hud" or "popover".
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "hud" => Some(Self::Hud),
+            "popover" => Some(Self::Popover),
+            "menu" => Some(Self::Menu),
+            "sidebar" => Some(Self::Sidebar),
+            "sheet" => Some(Self::Sheet),
+            "tooltip" => Some(Self::Tooltip),
+            "titlebar" => Some(Self::Titlebar),
+            "window" => Some(Self::WindowBackground),
+            "under_window" => Some(Self::UnderWindow),
+            _ => None,
+        }
+    }
+}
+
+/// How popup windows are drawn.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PopupRenderer {
+    /// Translucent rounded card with blur and fade/slide animations.
+    Styled,
+    /// Opaque, square, non-layered window in solid colors that appears and
+    /// disappears at once: the lowest common denominator that still renders
+    /// over RDP/VNC, without a compositor, or in a future Linux backend.
+    Plain,
+}
+
+impl PopupRenderer {
+    /// Parse the `popup_renderer` config value ("styled", "plain").
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "styled" => Some(Self::Styled),
+            "plain" => Some(Self::Plain),
+            _ => None,
+        }
+    }
+}
+
+/// Font smoothing for Windows popup text (maps to the `CreateFontW` quality).
+#[cfg(target_os = "windows")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextRendering {
+    ClearType,
+    /// Grayscale, without ClearType's colored fringes.
+    Antialiased,
+    /// Whatever the system font smoothing setting is.
+    Default,
+}
+
+#[cfg(target_os = "windows")]
+impl TextRendering {
+    /// Parse the `popup_text_rendering` config value ("cleartype",
+    /// "antialiased", "default").
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "cleartype" => Some(Self::ClearType),
+            "antialiased" => Some(Self::Antialiased),
+            "default" => Some(Self::Default),
+            _ => None,
+        }
+    }
+}
+
+/// Horizontal alignment of the popup text. The popup UI itself is left-to-right,
+/// so `Start` is the left edge and `End` the right edge.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextAlign {
+    Start,
+    Center,
+    End,
+    /// `End` for right-to-left translations (Arabic, Hebrew, ...), otherwise `Start`.
+    Auto,
+}
+
+impl TextAlign {
+    /// Parse the `popup_text_align` config value ("start", "center", "end", "auto").
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "start" | "left" => Some(Self::Start),
+            "center" | "centre" => Some(Self::Center),
+            "end" | "right" => Some(Self::End),
+            "auto" => Some(Self::Auto),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the first strongly directional character of `text` is from a
+/// right-to-left script. The translation is in the target language, so this
+/// follows the target language's directionality without a language table.
+fn is_rtl_text(text: &str) -> bool {
+    const RTL_RANGES: [(u32, u32); 3] = [(0x0590, 0x08FF), (0xFB1D, 0xFDFF), (0xFE70, 0xFEFF)];
+    text.chars()
+        .find(|c| c.is_alphabetic())
+        .is_some_and(|c| RTL_RANGES.iter().any(|&(lo, hi)| (lo..=hi).contains(&(c as u32))))
+}
+
+/// Resolve the configured alignment for `text`; never returns `Auto`.
+fn text_align_for(text: &str) -> TextAlign {
+    match popup_options().text_align {
+        TextAlign::Auto if is_rtl_text(text) => TextAlign::End,
+        TextAlign::Auto => TextAlign::Start,
+        align => align,
+    }
+}
+
+/// What to do with a visible popup when monitors are added, removed or rearranged.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DisplayChange {
+    /// Dismiss it if its monitor is gone, otherwise keep it inside the work area.
+    Dismiss,
+    /// Always move it onto the nearest remaining monitor.
+    Move,
+}
+
+impl DisplayChange {
+    /// Parse the `popup_display_change` config value ("dismiss", "move").
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "dismiss" => Some(Self::Dismiss),
+            "move" => Some(Self::Move),
+            _ => None,
+        }
+    }
+}
+
+/// Where the popup appears.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Placement {
+    /// Centered above the selection, or below it when there's no room.
+    Selection,
+    /// Hanging below the pointer where the selection ended, shifted by
+    /// `cursor_offset`.
+    Cursor,
+}
+
+impl Placement {
+    /// Parse the `popup_placement` config value ("selection", "cursor").
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "selection" => Some(Self::Selection),
+            "cursor" => Some(Self::Cursor),
+            _ => None,
+        }
+    }
+}
+
+/// How translations are presented.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DisplayMode {
+    /// A transient popup next to each selection.
+    Popup,
+    /// One persistent panel docked to the screen edge that collects them all.
+    Sidebar,
+}
+
+impl DisplayMode {
+    /// Parse the `display_mode` config value ("popup", "sidebar").
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "popup" => Some(Self::Popup),
+            "sidebar" => Some(Self::Sidebar),
+            _ => None,
+        }
+    }
+}
+
+static SCRIPT_FONTS: RwLock<Vec<(Script, String)>> = RwLock::new(Vec::new());
+
+/// Parse `popup_font_by_script` ("han:Microsoft YaHei,hangul:Malgun Gothic")
+/// into script → font family pairs, warning about and skipping bad entries.
+pub fn parse_script_fonts(value: &str) -> Vec<(Script, String)> {
+    value
+        .split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .filter_map(|entry| {
+            let parsed = entry
+                .split_once(':')
+                .filter(|(_, font)| !font.trim().is_empty())
+                .and_then(|(script, font)| Some((Script::from_config(script)?, font.trim().to_string())));
+            if parsed.is_none() {
+                tracing::warn!("Invalid entry \"{}\" in popup_font_by_script, ignoring", entry.trim());
+            }
+            parsed
+        })
+        .collect()
+}
+
+pub fn set_script_fonts(fonts: Vec<(Script, String)>) {
+    *SCRIPT_FONTS.write().unwrap() = fonts;
+}
+
+/// Font family the user mapped to the script `text` is mostly written in.
+fn configured_font_for(text: &str) -> Option<String> {
+    let script = text::dominant_script(text)?;
+    SCRIPT_FONTS
+        .read()
+        .unwrap()
+        .iter()
+        .find(|(s, _)| *s == script)
+        .map(|(_, font)| font.clone())
+}
+
+/// How long the `debug_overlay` selection outline stays up.
+const SELECTION_RECT_DURATION: Duration = Duration::from_millis(1200);
+
+/// Oldest sidebar entries beyond this are dropped.
+const SIDEBAR_MAX_ENTRIES: usize = 100;
+
+/// Parse a `#RRGGBB` (or `RRGGBB`) color into its components.
+#[cfg(target_os = "windows")]
+pub fn parse_hex_color(value: &str) -> Option<[u8; 3]> {
+    let hex = value.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Popup behaviour shared by both backends, applied once from config at startup.
+#[derive(Clone, Copy, Debug)]
+pub struct PopupOptions {
+    pub fade_in_ms: f64,
+    pub fade_out_ms: f64,
+    /// Faster fade used when the popup is dismissed by clicking elsewhere.
+    pub fade_out_deselect_ms: f64,
+    /// Animate opacity while showing/hiding; otherwise it stays fully opaque.
+    pub fade: bool,
+    /// Slide into place while showing/hiding; otherwise the position is fixed.
+    pub slide: bool,
+    /// Windows draws its own card.
+    #[cfg(target_os = "macos")]
+    pub material: PopupMaterial,
+    /// `Plain` also turns off fade and slide.
+    pub renderer: PopupRenderer,
+    /// RGB tint blended into the card background and border.
+    #[cfg(target_os = "windows")]
+    pub accent: Option<[u8; 3]>,
+    #[cfg(target_os = "windows")]
+    pub text_rendering: TextRendering,
+    /// Translations with at most this many characters count as "short" (0 = off).
+    pub short_text_chars: usize,
+    /// Minimum time a short translation stays up before auto-hiding.
+    pub short_min_display_secs: f64,
+    /// Reuse the previous popup size for similar-length content shown in quick succession.
+    pub sticky_size: bool,
+    /// Thin bar along the bottom edge shrinking until auto-hide.
+    pub countdown_bar: bool,
+    pub text_align: TextAlign,
+    pub display_change: DisplayChange,
+    pub placement: Placement,
+    /// Shift from the pointer for `Placement::Cursor`, in logical pixels
+    /// (positive = right/down).
+    pub cursor_offset: (i32, i32),
+    /// Hard cap on time on screen since the popup appeared, whatever resets
+    /// the auto-hide timer (0 = no cap).
+    pub max_lifetime_secs: f64,
+    /// Auto-hide time left after hovering, scrolling or moving the popup.
+    pub interaction_extend_ms: f64,
+    /// Popups kept on screen at once before the oldest is dropped.
+    pub max_popups: usize,
+    /// Narrowest popup, in logical pixels; see `min_popup_width`.
+    pub min_width: f64,
+    pub word_min_width: f64,
+    pub error_min_width: f64,
+    /// Grow the popup to show the original text under the translation once
+    /// the pointer is over it.
+    #[cfg(target_os = "windows")]
+    pub original_on_hover: bool,
+}
+
+impl PopupOptions {
+    pub const DEFAULT: Self = Self {
+        fade_in_ms: 180.0,
+        fade_out_ms: 220.0,
+        fade_out_deselect_ms: 120.0,
+        fade: true,
+        slide: true,
+        #[cfg(target_os = "macos")]
+        material: PopupMaterial::Hud,
+        renderer: PopupRenderer::Styled,
+        #[cfg(target_os = "windows")]
+        accent: None,
+        #[cfg(target_os = "windows")]
+        text_rendering: TextRendering::ClearType,
+        short_text_chars: 3,
+        short_min_display_secs: 4.0,
+        sticky_size: false,
+        countdown_bar: false,
+        text_align: TextAlign::Auto,
+        display_change: DisplayChange::Dismiss,
+        placement: Placement::Selection,
+        cursor_offset: (0, 20),
+        max_lifetime_secs: 0.0,
+        interaction_extend_ms: 8000.0,
+        max_popups: 1,
+        min_width: 200.0,
+        word_min_width: 260.0,
+        error_min_width: 320.0,
+        #[cfg(target_os = "windows")]
+        original_on_hover: false,
+    };
+}
+
+impl Default for PopupOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+static POPUP_OPTIONS: RwLock<PopupOptions> = RwLock::new(PopupOptions::DEFAULT);
+
+pub fn set_popup_options(options: PopupOptions) {
+    *POPUP_OPTIONS.write().unwrap() = options;
+}
+
+pub fn popup_options() -> PopupOptions {
+    *POPUP_OPTIONS.read().unwrap()
+}
+
+/// Whether popup windows are drawn with `PopupRenderer::Plain`.
+fn plain_renderer() -> bool {
+    popup_options().renderer == PopupRenderer::Plain
+}
+
+/// Whether `text` is short enough to get the larger popup and longer display time.
+fn is_short_translation(text: &str) -> bool {
+    let limit = popup_options().short_text_chars;
+    limit > 0 && text.trim().chars().count() <= limit
+}
+
+/// What `hover_translate` found under the pointer.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum HoverTarget {
+    /// The word there, read through the accessibility API.
+    Word(String),
+    /// Text that couldn't be read directly, in a control where a double-click
+    /// selects a word rather than opening or activating something.
+    SelectableText,
+}
+
+/// What a popup is showing, which decides how narrow it may get.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PopupContent {
+    Translation,
+    /// A failed translation; its message needs room to wrap.
+    Error,
+    /// A short status message (`show_status`), centered on the monitor and
+    /// up for `STATUS_SECS` whatever its length.
+    Status,
+}
+
+/// How long a status message stays up before hiding.
+const STATUS_SECS: f64 = 1.5;
+
+/// Narrowest the popup for `text` may be, in logical pixels: wider for
+/// errors, and for short translations so a single word isn't a sliver.
+fn min_popup_width(text: &str, content: PopupContent) -> f64 {
+    let options = popup_options();
+    match content {
+        PopupContent::Error => options.error_min_width,
+        PopupContent::Translation if is_short_translation(text) => options.word_min_width,
+        PopupContent::Translation | PopupContent::Status => options.min_width,
+    }
+}
+
+/// Time from showing a popup until it starts hiding, in milliseconds.
+fn auto_hide_ms(text: &str, content: PopupContent) -> f64 {
+    let fade_in_ms = popup_options().fade_in_ms;
+    if content == PopupContent::Status {
+        return fade_in_ms + STATUS_SECS * 1000.0;
+    }
+    fade_in_ms + reading_secs(text) * 1000.0 + 3000.0
+}
+
+/// Time to leave the popup up: ~15 chars/sec reading speed, 2-20s range,
+/// with a higher floor for short translations that are easy to miss.
+fn reading_secs(text: &str) -> f64 {
+    let min_secs = if is_short_translation(text) {
+        popup_options().short_min_display_secs.clamp(2.0, 20.0)
+    } else {
+        2.0
+    };
+    (text.chars().count() as f64 / 15.0).clamp(min_secs, 20.0)
+}
+
+/// How long after a popup appears its size may be reused by the next one.
+const STICKY_SIZE_WINDOW: Duration = Duration::from_secs(4);
+
+static LAST_POPUP_SIZE: Mutex<Option<(f64, f64, Instant)>> = Mutex::new(None);
+
+/// With `sticky_size`, keep the previous popup size when it was shown recently,
+/// the new content fits inside it and isn't much smaller (so a one-word popup
+/// doesn't inherit a paragraph-sized card). Otherwise the size is used as is.
+fn sticky_popup_size(width: f64, height: f64) -> (f64, f64) {
+    let mut last = LAST_POPUP_SIZE.lock().unwrap();
+    let mut size = (width, height);
+    if popup_options().sticky_size {
+        if let Some((w, h, shown)) = *last {
+            let fits = width <= w && height <= h;
+            let similar = width >= w * 0.6 && height >= h * 0.5;
+            if fits && similar && shown.elapsed() < STICKY_SIZE_WINDOW {
+                size = (w, h);
+            }
+        }
+    }
+    *last = Some((size.0, size.1, Instant::now()));
+    size
+}
+
+/// Whether a popup shown at `shown_at` has used up `max_lifetime_secs`.
+fn lifetime_exceeded(shown_at: Instant) -> bool {
+    let max = popup_options().max_lifetime_secs;
+    max > 0.0 && shown_at.elapsed().as_secs_f64() >= max
+}
+
+/// Animation progress in 0..=1; a zero duration finishes immediately.
+fn anim_progress(elapsed_ms: f64, duration_ms: f64) -> f64 {
+    if duration_ms <= 0.0 {
+        1.0
+    } else {
+        (elapsed_ms / duration_ms).min(1.0)
+    }
+}
+
 #[cfg(target_os = "windows")]
 mod windows;
 #[cfg(target_os = "windows")]
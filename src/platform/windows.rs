@@ -1,17 +1,52 @@
+use std::cell::Cell;
+use std::ffi::c_void;
+use std::path::PathBuf;
 use std::ptr;
-use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
-use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, AtomicU8, Ordering};
+use std::sync::{Mutex, OnceLock};
 use std::time::Instant;
 
+use windows_sys::core::{BSTR, GUID, HRESULT};
 use windows_sys::Win32::Foundation::*;
+use windows_sys::Win32::Globalization::GetUserDefaultLocaleName;
 use windows_sys::Win32::Graphics::Gdi::*;
+use windows_sys::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED,
+};
+use windows_sys::Win32::System::DataExchange::{
+    CloseClipboard, EmptyClipboard, EnumClipboardFormats, GetClipboardData,
+    GetClipboardSequenceNumber, OpenClipboard, RegisterClipboardFormatW, SetClipboardData,
+};
 use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows_sys::Win32::System::Memory::{
+    GlobalAlloc, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE,
+};
+use windows_sys::Win32::System::SystemServices::SS_NOPREFIX;
+use windows_sys::Win32::System::Threading::{
+    OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+    PROCESS_QUERY_LIMITED_INFORMATION,
+};
+use windows_sys::Win32::UI::Controls::Dialogs::{
+    GetOpenFileNameW, GetSaveFileNameW, OFN_FILEMUSTEXIST, OFN_NOCHANGEDIR, OFN_OVERWRITEPROMPT,
+    OFN_PATHMUSTEXIST, OPENFILENAMEW,
+};
 use windows_sys::Win32::UI::HiDpi::*;
 use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+use windows_sys::Win32::UI::Shell::{
+    SHQueryUserNotificationState, QUNS_BUSY, QUNS_PRESENTATION_MODE, QUNS_RUNNING_D3D_FULL_SCREEN,
+};
 use windows_sys::Win32::UI::WindowsAndMessaging::*;
 
 use crate::clipboard::SelectionPos;
-use super::MouseEvent;
+use super::{
+    anim_progress, auto_hide_ms, configured_font_for, is_rtl_text, lifetime_exceeded,
+    min_popup_width, plain_renderer, popup_options, sticky_popup_size, suppress_modifiers,
+    text_align_for, DisplayChange, HoverTarget, MouseEvent, InputEvent, Placement, PopupContent,
+    TextAlign, TextRendering, TriggerButton, SELECTION_RECT_DURATION, SIDEBAR_MAX_ENTRIES,
+};
+use crate::hotkey::{self, Hotkey, Key};
+use crate::i18n::tr;
+use crate::text::{self, Script};
 
 static MOUSE_UP_FLAG: AtomicBool = AtomicBool::new(false);
 static MOUSE_CLICK_FLAG: AtomicBool = AtomicBool::new(false);
@@ -19,10 +54,18 @@ static MOUSE_DOWN_X: AtomicI32 = AtomicI32::new(0);
 static MOUSE_DOWN_Y: AtomicI32 = AtomicI32::new(0);
 static MOUSE_UP_X: AtomicI32 = AtomicI32::new(0);
 static MOUSE_UP_Y: AtomicI32 = AtomicI32::new(0);
-static POPUP_RECT_LEFT: AtomicI32 = AtomicI32::new(0);
-static POPUP_RECT_TOP: AtomicI32 = AtomicI32::new(0);
-static POPUP_RECT_RIGHT: AtomicI32 = AtomicI32::new(0);
-static POPUP_RECT_BOTTOM: AtomicI32 = AtomicI32::new(0);
+// Message timestamps (ms since boot, wrapping) of the last trigger press/release
+static MOUSE_DOWN_TIME: AtomicU32 = AtomicU32::new(0);
+static MOUSE_UP_TIME: AtomicU32 = AtomicU32::new(0);
+static SIDEBAR_RECT: ScreenRect = ScreenRect::new();
+static INPUT_RECT: ScreenRect = ScreenRect::new();
+static TRIGGER_BUTTON: AtomicU8 = AtomicU8::new(TriggerButton::Left as u8);
+// The trigger button went down on the popup (a drag, not a selection)
+static PRESS_IN_POPUP: AtomicBool = AtomicBool::new(false);
+// The suppress_modifier was held when the trigger button went down
+static PRESS_SUPPRESSED: AtomicBool = AtomicBool::new(false);
+// Clipboard contents saved by `save_clipboard`, as (format, bytes)
+static SAVED_CLIPBOARD: Mutex<Vec<(u32, Vec<u8>)>> = Mutex::new(Vec::new());
 
 #[repr(C)]
 #[allow(clippy::upper_case_acronyms)]
@@ -30,7 +73,7 @@ struct MSLLHOOKSTRUCT {
     pt: POINT,
     mouse_data: u32,
     _flags: u32,
-    _time: u32,
+    time: u32,
     _extra_info: usize,
 }
 
@@ -38,25 +81,40 @@ struct MSLLHOOKSTRUCT {
 const BASE_PADDING: i32 = 16;
 const BASE_MAX_WIDTH: i32 = 640;
 const BASE_FONT_TRANSLATED: i32 = -16;
+const BASE_FONT_SECONDARY: i32 = -13;
+const BASE_SECONDARY_GAP: i32 = 8;
 const BASE_CORNER_RADIUS: i32 = 22;
 const BASE_GAP_ABOVE: i32 = 8;
 const BASE_SLIDE_PX: i32 = 10;
-const BASE_MIN_WIDTH: i32 = 200;
 const BASE_MAX_HEIGHT: i32 = 400;
 const BASE_SCROLL_LINE: i32 = 40;
+const BASE_SIDEBAR_WIDTH: i32 = 360;
+const BASE_SIDEBAR_ENTRY_GAP: i32 = 20;
+const BASE_INPUT_WIDTH: i32 = 420;
+const BASE_INPUT_FIELD_HEIGHT: i32 = 28;
+const BASE_INPUT_RESULT_HEIGHT: i32 = 140;
 
 const BG_COLOR: u32 = 0x002A2A2A;
 const BORDER_SHADOW: u32 = 0x00181818;
 const BORDER_HIGHLIGHT: u32 = 0x00606060;
 const BORDER_HIGHLIGHT_INNER: u32 = 0x00404040;
 const TRANSLATED_COLOR: u32 = 0x00F0F0F0;
+const SECONDARY_COLOR: u32 = 0x00A8A8A8;
+// How much of `popup_accent` is mixed into the card (0 = none, 1 = solid accent)
+const ACCENT_BG_MIX: f64 = 0.18;
+const ACCENT_BORDER_MIX: f64 = 0.55;
 const SCROLLBAR_COLOR: u32 = 0x00808080;
 const SCROLLBAR_WIDTH: i32 = 4;
+const COUNTDOWN_COLOR: u32 = 0x00909090;
+const BASE_COUNTDOWN_HEIGHT: i32 = 2;
+const BASE_COUNTDOWN_BOTTOM: i32 = 5;
+// Space between popups stacked with `max_popups`
+const BASE_STACK_GAP: i32 = 8;
+// debug_overlay outline
+const SELECTION_RECT_COLOR: u32 = 0x003030FF;
+const BASE_SELECTION_RECT_BORDER: i32 = 2;
 
 const MAX_ALPHA: u8 = 230;
-const FADE_IN_MS: f64 = 180.0;
-const FADE_OUT_MS: f64 = 220.0;
-const FADE_OUT_DESELECT_MS: f64 = 120.0;
 const ANIM_TIMER: usize = 100;
 const ANIM_FRAME_MS: u32 = 16;
 const HIDE_TIMER: usize = 101;
@@ -67,36 +125,152 @@ const PHASE_VISIBLE: u8 = 2;
 const PHASE_FADE_OUT: u8 = 3;
 
 const WM_POPUP_SCROLL: u32 = WM_USER + 1;
+const WM_POPUP_HOVER: u32 = WM_USER + 2;
+
+/// One popup window and its animation and auto-hide state.
+struct Popup {
+    hwnd: HWND,
+    translated: String,
+    secondary: Option<String>,
+    // Source text, revealed by `original_on_hover`
+    original: String,
+    // Where the original starts within the content once revealed
+    original_top: Option<i32>,
+    // Height of the primary translation; the secondary line is drawn below it
+    primary_height: i32,
+    content_height: i32,
+    scroll_offset: i32,
+    phase: u8,
+    anim_start: Instant,
+    // First shown; unlike the hide timer, never reset by scrolling or dragging
+    shown_at: Instant,
+    // When the running hide timer fires and its full length, for the countdown bar
+    hide_deadline: Option<Instant>,
+    hide_total_ms: u32,
+    target_x: i32,
+    target_y: i32,
+    deselect_close: bool,
+    drag_origin: (i32, i32),
+    // Screen rect, for hit-testing in the mouse hook
+    rect: RECT,
+    // DPI scale of the monitor it was laid out for
+    scale: f64,
+}
 
 // popup state (main thread only)
 static CLASS_NAME: OnceLock<Vec<u16>> = OnceLock::new();
-static mut POPUP_HWND: HWND = ptr::null_mut();
-static mut TRANSLATED_TEXT: Option<String> = None;
-static mut PHASE: u8 = PHASE_NONE;
-static mut ANIM_START: Option<Instant> = None;
-static mut TARGET_X: i32 = 0;
-static mut TARGET_Y: i32 = 0;
-static mut CLOSE_SCHEDULED: bool = false;
+// Open popups, oldest first; more than one with `max_popups`
+static mut POPUPS: Vec<Popup> = Vec::new();
 static mut DPI_SCALE: f64 = 1.0;
-static mut DESELECT_CLOSE: bool = false;
-static mut SCROLL_OFFSET: i32 = 0;
-static mut CONTENT_HEIGHT: i32 = 0;
+
+static SIDEBAR_CLASS_NAME: OnceLock<Vec<u16>> = OnceLock::new();
+static mut SIDEBAR_HWND: HWND = ptr::null_mut();
+// Newest first
+static mut SIDEBAR_ENTRIES: Vec<SidebarEntry> = Vec::new();
+static mut SIDEBAR_SCROLL: i32 = 0;
+static mut SIDEBAR_CONTENT_HEIGHT: i32 = 0;
+
+static SELECTION_RECT_CLASS_NAME: OnceLock<Vec<u16>> = OnceLock::new();
+static mut SELECTION_RECT_HWND: HWND = ptr::null_mut();
+
+static INPUT_CLASS_NAME: OnceLock<Vec<u16>> = OnceLock::new();
+static mut INPUT_HWND: HWND = ptr::null_mut();
+static mut INPUT_EDIT: HWND = ptr::null_mut();
+static mut INPUT_RESULT: HWND = ptr::null_mut();
+static mut INPUT_FONT: HFONT = ptr::null_mut();
+static INPUT_CHANGED: AtomicBool = AtomicBool::new(false);
+static INPUT_SUBMITTED: AtomicBool = AtomicBool::new(false);
 
 fn s(v: i32) -> i32 {
     unsafe { (v as f64 * DPI_SCALE).round() as i32 }
 }
 
-fn update_popup_rect_cache() {
-    unsafe {
-        if !POPUP_HWND.is_null() {
-            let mut r: RECT = std::mem::zeroed();
-            GetWindowRect(POPUP_HWND, &mut r);
-            POPUP_RECT_LEFT.store(r.left, Ordering::Relaxed);
-            POPUP_RECT_TOP.store(r.top, Ordering::Relaxed);
-            POPUP_RECT_RIGHT.store(r.right, Ordering::Relaxed);
-            POPUP_RECT_BOTTOM.store(r.bottom, Ordering::Relaxed);
+/// Makes `s()` use another scale, e.g. a popup's own monitor's, until
+/// dropped. Everything runs on the main thread, so swapping the global is safe.
+struct ScaleGuard(f64);
+
+impl ScaleGuard {
+    unsafe fn new(scale: f64) -> Self {
+        let previous = DPI_SCALE;
+        DPI_SCALE = scale;
+        Self(previous)
+    }
+}
+
+impl Drop for ScaleGuard {
+    fn drop(&mut self) {
+        unsafe { DPI_SCALE = self.0; }
+    }
+}
+
+/// DPI scale of a monitor; the startup scale if it can't be read.
+unsafe fn monitor_scale(hmon: HMONITOR) -> f64 {
+    let (mut dpi_x, mut dpi_y) = (0u32, 0u32);
+    if GetDpiForMonitor(hmon, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) < 0 || dpi_x == 0 {
+        return DPI_SCALE;
+    }
+    dpi_x as f64 / 96.0
+}
+
+#[allow(clippy::deref_addrof)]
+unsafe fn popups() -> &'static mut Vec<Popup> {
+    &mut *(&raw mut POPUPS)
+}
+
+/// State of the popup window `hwnd`; `None` while it is being created or
+/// after it was destroyed.
+unsafe fn popup_mut(hwnd: HWND) -> Option<&'static mut Popup> {
+    popups().iter_mut().find(|p| p.hwnd == hwnd)
+}
+
+/// The topmost popup at a screen point, using the cached rects.
+unsafe fn popup_at(x: i32, y: i32) -> Option<&'static Popup> {
+    popups()
+        .iter()
+        .rev()
+        .find(|p| x >= p.rect.left && x < p.rect.right && y >= p.rect.top && y < p.rect.bottom)
+}
+
+unsafe fn update_popup_rect_cache(p: &mut Popup) {
+    GetWindowRect(p.hwnd, &mut p.rect);
+}
+
+/// Screen rect of one of our windows, cached for hit-testing in the mouse
+/// hook. Empty while the window doesn't exist.
+struct ScreenRect {
+    left: AtomicI32,
+    top: AtomicI32,
+    right: AtomicI32,
+    bottom: AtomicI32,
+}
+
+impl ScreenRect {
+    const fn new() -> Self {
+        Self {
+            left: AtomicI32::new(0),
+            top: AtomicI32::new(0),
+            right: AtomicI32::new(0),
+            bottom: AtomicI32::new(0),
         }
     }
+
+    fn set(&self, r: &RECT) {
+        self.left.store(r.left, Ordering::Relaxed);
+        self.top.store(r.top, Ordering::Relaxed);
+        self.right.store(r.right, Ordering::Relaxed);
+        self.bottom.store(r.bottom, Ordering::Relaxed);
+    }
+
+    fn clear(&self) {
+        self.set(&RECT { left: 0, top: 0, right: 0, bottom: 0 });
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.left.load(Ordering::Relaxed)
+            && x < self.right.load(Ordering::Relaxed)
+            && y >= self.top.load(Ordering::Relaxed)
+            && y < self.bottom.load(Ordering::Relaxed)
+    }
 }
 
 pub struct HookHandle {
@@ -138,20 +312,461 @@ pub fn init_platform() {
             hIconSm: ptr::null_mut(),
         };
         RegisterClassExW(&wc);
+
+        let sidebar_class = SIDEBAR_CLASS_NAME.get_or_init(|| to_wide("ClipTransSidebar"));
+        let wc = WNDCLASSEXW {
+            lpfnWndProc: Some(sidebar_wnd_proc),
+            lpszClassName: sidebar_class.as_ptr(),
+            ..wc
+        };
+        RegisterClassExW(&wc);
+
+        let input_class = INPUT_CLASS_NAME.get_or_init(|| to_wide("ClipTransInput"));
+        let wc = WNDCLASSEXW {
+            lpfnWndProc: Some(input_wnd_proc),
+            lpszClassName: input_class.as_ptr(),
+            hbrBackground: GetSysColorBrush(COLOR_WINDOW),
+            ..wc
+        };
+        RegisterClassExW(&wc);
+
+        let rect_class = SELECTION_RECT_CLASS_NAME.get_or_init(|| to_wide("ClipTransSelectionRect"));
+        let wc = WNDCLASSEXW {
+            lpfnWndProc: Some(selection_rect_wnd_proc),
+            lpszClassName: rect_class.as_ptr(),
+            hbrBackground: CreateSolidBrush(SELECTION_RECT_COLOR),
+            ..wc
+        };
+        RegisterClassExW(&wc);
     }
 }
 
-pub fn install_mouse_hook() -> anyhow::Result<HookHandle> {
+pub fn install_mouse_hook(trigger: TriggerButton) -> anyhow::Result<HookHandle> {
+    TRIGGER_BUTTON.store(trigger as u8, Ordering::Relaxed);
     let hook = unsafe {
         SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_hook_proc), ptr::null_mut(), 0)
     };
     if hook.is_null() {
         anyhow::bail!("Failed to install mouse hook");
     }
-    tracing::info!("Mouse hook installed — ready!");
+    tracing::info!("Mouse hook installed ({:?} button) — ready!", trigger);
     Ok(HookHandle { hook })
 }
 
+/// Register a system-wide hotkey; presses arrive as `MouseEvent::Hotkey(id)`.
+/// Must be called on the thread that calls `poll_mouse_event`.
+pub fn register_hotkey(id: u32, hotkey: &Hotkey) -> anyhow::Result<()> {
+    let mut mods = MOD_NOREPEAT;
+    if hotkey.modifiers & hotkey::MOD_CTRL != 0 {
+        mods |= MOD_CONTROL;
+    }
+    if hotkey.modifiers & hotkey::MOD_ALT != 0 {
+        mods |= MOD_ALT;
+    }
+    if hotkey.modifiers & hotkey::MOD_SHIFT != 0 {
+        mods |= MOD_SHIFT;
+    }
+    if hotkey.modifiers & hotkey::MOD_SUPER != 0 {
+        mods |= MOD_WIN;
+    }
+    let vk = match hotkey.key {
+        Key::Letter(c) | Key::Digit(c) => c as VIRTUAL_KEY,
+        Key::Function(n) => VK_F1 + (n as VIRTUAL_KEY - 1),
+        Key::Space => VK_SPACE,
+        Key::Enter => VK_RETURN,
+        Key::Tab => VK_TAB,
+        Key::Escape => VK_ESCAPE,
+    };
+    if unsafe { RegisterHotKey(ptr::null_mut(), id as i32, mods, vk as u32) } == 0 {
+        anyhow::bail!("{:?} is already registered by another application", hotkey);
+    }
+    Ok(())
+}
+
+/// Whether the focused control accepts typing. `None` when it can't be told
+/// (custom-drawn editors in browsers, Electron, etc.).
+pub fn is_focused_editable() -> Option<bool> {
+    unsafe {
+        let mut info: GUITHREADINFO = std::mem::zeroed();
+        info.cbSize = std::mem::size_of::<GUITHREADINFO>() as u32;
+        if GetGUIThreadInfo(0, &mut info) == 0 || info.hwndFocus.is_null() {
+            return None;
+        }
+
+        let mut class_buf = [0u16; 64];
+        let len = GetClassNameW(info.hwndFocus, class_buf.as_mut_ptr(), class_buf.len() as i32);
+        let class = String::from_utf16_lossy(&class_buf[..len.max(0) as usize]).to_ascii_lowercase();
+        if class.contains("edit") {
+            let style = GetWindowLongW(info.hwndFocus, GWL_STYLE);
+            return Some(style & ES_READONLY == 0);
+        }
+        if !info.hwndCaret.is_null() {
+            return Some(true);
+        }
+        None
+    }
+}
+
+// UI Automation, used to read the selection without touching the clipboard.
+// windows-sys has no COM wrappers, so the few methods needed are called
+// through their vtable slots (IUnknown occupies slots 0-2).
+const CLSID_CUIAUTOMATION: GUID = GUID::from_u128(0xff48dba4_60ef_4201_aa87_54103eef594e);
+const IID_IUIAUTOMATION: GUID = GUID::from_u128(0x30cbe57d_d9d0_452a_ab13_7ac5ac4825ee);
+const IID_IUIAUTOMATION_TEXT_PATTERN: GUID =
+    GUID::from_u128(0x32eba289_3583_42c9_9c59_3b6d9a1e9b6a);
+const UIA_TEXT_PATTERN_ID: i32 = 10014;
+const UIA_MAX_TEXT_LEN: i32 = 64 * 1024;
+
+const UIA_TEXT_UNIT_WORD: i32 = 1;
+const UIA_MAX_WORD_LEN: i32 = 256;
+/// Control types whose text a double-click selects (Edit, Text, Document).
+const UIA_TEXT_CONTROL_TYPES: [i32; 3] = [50004, 50020, 50030];
+/// Containers where a double-click opens or activates instead, even on the
+/// text inside them (Button, Hyperlink, ListItem, MenuItem, TabItem,
+/// TreeItem, DataItem): Explorer files, desktop icons, links.
+const UIA_ACTIVATING_CONTROL_TYPES: [i32; 7] = [50000, 50005, 50007, 50011, 50019, 50024, 50029];
+
+const SLOT_RELEASE: usize = 2;
+const SLOT_AUTOMATION_ELEMENT_FROM_POINT: usize = 7;
+const SLOT_AUTOMATION_GET_FOCUSED_ELEMENT: usize = 8;
+const SLOT_AUTOMATION_GET_CONTROL_VIEW_WALKER: usize = 14;
+const SLOT_TREE_WALKER_GET_PARENT_ELEMENT: usize = 3;
+const SLOT_ELEMENT_GET_CURRENT_PATTERN_AS: usize = 14;
+const SLOT_ELEMENT_GET_CURRENT_CONTROL_TYPE: usize = 21;
+const SLOT_TEXT_PATTERN_RANGE_FROM_POINT: usize = 3;
+const SLOT_TEXT_PATTERN_GET_SELECTION: usize = 5;
+const SLOT_RANGE_ARRAY_GET_LENGTH: usize = 3;
+const SLOT_RANGE_ARRAY_GET_ELEMENT: usize = 4;
+const SLOT_TEXT_RANGE_EXPAND_TO_ENCLOSING_UNIT: usize = 6;
+const SLOT_TEXT_RANGE_GET_TEXT: usize = 12;
+
+type ComPtr = *mut c_void;
+
+thread_local! {
+    // One IUIAutomation per thread (COM is initialized per thread)
+    static UIA_AUTOMATION: Cell<ComPtr> = const { Cell::new(ptr::null_mut()) };
+}
+
+/// Fetch a method pointer from a COM object's vtable.
+unsafe fn com_method<F: Copy>(obj: ComPtr, slot: usize) -> F {
+    let vtbl = *(obj as *const *const usize);
+    std::mem::transmute_copy(&*vtbl.add(slot))
+}
+
+unsafe fn com_release(obj: ComPtr) {
+    if !obj.is_null() {
+        let release: unsafe extern "system" fn(ComPtr) -> u32 = com_method(obj, SLOT_RELEASE);
+        release(obj);
+    }
+}
+
+unsafe fn uia_automation() -> Option<ComPtr> {
+    UIA_AUTOMATION.with(|cell| {
+        if cell.get().is_null() {
+            CoInitializeEx(ptr::null(), COINIT_MULTITHREADED as u32);
+            let mut obj: ComPtr = ptr::null_mut();
+            let hr = CoCreateInstance(
+                &CLSID_CUIAUTOMATION,
+                ptr::null_mut(),
+                CLSCTX_INPROC_SERVER,
+                &IID_IUIAUTOMATION,
+                &mut obj,
+            );
+            if hr < 0 || obj.is_null() {
+                tracing::debug!("UI Automation unavailable (0x{:08X})", hr);
+                return None;
+            }
+            cell.set(obj);
+        }
+        Some(cell.get())
+    })
+}
+
+/// Read the focused control's selection through UI Automation's TextPattern.
+/// Returns `None` when the control doesn't expose one or nothing is selected,
+/// in which case the caller falls back to simulating Ctrl+C.
+pub fn read_selected_text() -> Option<String> {
+    unsafe {
+        let automation = uia_automation()?;
+
+        let get_focused: unsafe extern "system" fn(ComPtr, *mut ComPtr) -> HRESULT =
+            com_method(automation, SLOT_AUTOMATION_GET_FOCUSED_ELEMENT);
+        let mut element: ComPtr = ptr::null_mut();
+        if get_focused(automation, &mut element) < 0 || element.is_null() {
+            return None;
+        }
+
+        let pattern = uia_text_pattern(element);
+        com_release(element);
+        let pattern = pattern?;
+
+        let get_selection: unsafe extern "system" fn(ComPtr, *mut ComPtr) -> HRESULT =
+            com_method(pattern, SLOT_TEXT_PATTERN_GET_SELECTION);
+        let mut ranges: ComPtr = ptr::null_mut();
+        let hr = get_selection(pattern, &mut ranges);
+        com_release(pattern);
+        if hr < 0 || ranges.is_null() {
+            return None;
+        }
+
+        let get_length: unsafe extern "system" fn(ComPtr, *mut i32) -> HRESULT =
+            com_method(ranges, SLOT_RANGE_ARRAY_GET_LENGTH);
+        let get_element: unsafe extern "system" fn(ComPtr, i32, *mut ComPtr) -> HRESULT =
+            com_method(ranges, SLOT_RANGE_ARRAY_GET_ELEMENT);
+        let mut count = 0i32;
+        get_length(ranges, &mut count);
+
+        let mut parts = Vec::new();
+        for i in 0..count {
+            let mut range: ComPtr = ptr::null_mut();
+            if get_element(ranges, i, &mut range) < 0 || range.is_null() {
+                continue;
+            }
+            parts.extend(uia_range_text(range, UIA_MAX_TEXT_LEN));
+            com_release(range);
+        }
+        com_release(ranges);
+
+        let text = parts.join("\n");
+        (!text.trim().is_empty()).then_some(text)
+    }
+}
+
+unsafe fn uia_text_pattern(element: ComPtr) -> Option<ComPtr> {
+    let get_pattern_as: unsafe extern "system" fn(ComPtr, i32, *const GUID, *mut ComPtr) -> HRESULT =
+        com_method(element, SLOT_ELEMENT_GET_CURRENT_PATTERN_AS);
+    let mut pattern: ComPtr = ptr::null_mut();
+    let hr = get_pattern_as(element, UIA_TEXT_PATTERN_ID, &IID_IUIAUTOMATION_TEXT_PATTERN, &mut pattern);
+    (hr >= 0 && !pattern.is_null()).then_some(pattern)
+}
+
+/// Text of a UI Automation text range, up to `max_len` characters.
+unsafe fn uia_range_text(range: ComPtr, max_len: i32) -> Option<String> {
+    let get_text: unsafe extern "system" fn(ComPtr, i32, *mut BSTR) -> HRESULT =
+        com_method(range, SLOT_TEXT_RANGE_GET_TEXT);
+    let mut text: BSTR = ptr::null();
+    if get_text(range, max_len, &mut text) < 0 || text.is_null() {
+        return None;
+    }
+    let len = SysStringLen(text) as usize;
+    let result = String::from_utf16_lossy(std::slice::from_raw_parts(text, len));
+    SysFreeString(text);
+    Some(result)
+}
+
+unsafe fn uia_control_type(element: ComPtr) -> Option<i32> {
+    let get_control_type: unsafe extern "system" fn(ComPtr, *mut i32) -> HRESULT =
+        com_method(element, SLOT_ELEMENT_GET_CURRENT_CONTROL_TYPE);
+    let mut control_type = 0;
+    (get_control_type(element, &mut control_type) >= 0).then_some(control_type)
+}
+
+/// What is at (`x`, `y`) for `hover_translate`: the word there when the
+/// element under it has a TextPattern, otherwise whether a double-click would
+/// select text rather than open or activate something.
+pub fn hover_target(x: i32, y: i32) -> Option<HoverTarget> {
+    unsafe {
+        let automation = uia_automation()?;
+        let element_from_point: unsafe extern "system" fn(ComPtr, POINT, *mut ComPtr) -> HRESULT =
+            com_method(automation, SLOT_AUTOMATION_ELEMENT_FROM_POINT);
+        let pt = POINT { x, y };
+        let mut element: ComPtr = ptr::null_mut();
+        if element_from_point(automation, pt, &mut element) < 0 || element.is_null() {
+            return None;
+        }
+        let target = match uia_word_at(element, pt) {
+            Some(word) => Some(HoverTarget::Word(word)),
+            None => uia_is_selectable_text(automation, element).then_some(HoverTarget::SelectableText),
+        };
+        com_release(element);
+        target
+    }
+}
+
+/// The word of `element`'s text at `pt`, through TextPattern.RangeFromPoint.
+unsafe fn uia_word_at(element: ComPtr, pt: POINT) -> Option<String> {
+    let pattern = uia_text_pattern(element)?;
+    let range_from_point: unsafe extern "system" fn(ComPtr, POINT, *mut ComPtr) -> HRESULT =
+        com_method(pattern, SLOT_TEXT_PATTERN_RANGE_FROM_POINT);
+    let mut range: ComPtr = ptr::null_mut();
+    let hr = range_from_point(pattern, pt, &mut range);
+    com_release(pattern);
+    if hr < 0 || range.is_null() {
+        return None;
+    }
+    let expand: unsafe extern "system" fn(ComPtr, i32) -> HRESULT =
+        com_method(range, SLOT_TEXT_RANGE_EXPAND_TO_ENCLOSING_UNIT);
+    let word = if expand(range, UIA_TEXT_UNIT_WORD) >= 0 {
+        uia_range_text(range, UIA_MAX_WORD_LEN)
+    } else {
+        None
+    };
+    com_release(range);
+    let word = word?.trim().to_string();
+    word.chars().any(char::is_alphanumeric).then_some(word)
+}
+
+/// Whether `element` is text a double-click selects: a text control type,
+/// not inside a list item, link or button where it would open instead.
+unsafe fn uia_is_selectable_text(automation: ComPtr, element: ComPtr) -> bool {
+    if !uia_control_type(element).is_some_and(|t| UIA_TEXT_CONTROL_TYPES.contains(&t)) {
+        return false;
+    }
+    let get_walker: unsafe extern "system" fn(ComPtr, *mut ComPtr) -> HRESULT =
+        com_method(automation, SLOT_AUTOMATION_GET_CONTROL_VIEW_WALKER);
+    let mut walker: ComPtr = ptr::null_mut();
+    if get_walker(automation, &mut walker) < 0 || walker.is_null() {
+        return false;
+    }
+    let get_parent: unsafe extern "system" fn(ComPtr, ComPtr, *mut ComPtr) -> HRESULT =
+        com_method(walker, SLOT_TREE_WALKER_GET_PARENT_ELEMENT);
+    let mut parent: ComPtr = ptr::null_mut();
+    let hr = get_parent(walker, element, &mut parent);
+    com_release(walker);
+    if hr < 0 || parent.is_null() {
+        return true;
+    }
+    let activating = uia_control_type(parent).is_some_and(|t| UIA_ACTIVATING_CONTROL_TYPES.contains(&t));
+    com_release(parent);
+    !activating
+}
+
+/// Counter bumped by every clipboard change, to detect a copy without clearing.
+pub fn clipboard_change_count() -> u64 {
+    unsafe { GetClipboardSequenceNumber() as u64 }
+}
+
+/// Save every clipboard format held in global memory (text, rich text, DIB
+/// images, file lists, ...) for `restore_clipboard`. GDI handle formats are
+/// left out; their data is covered by the DIB and text formats Windows keeps
+/// alongside them.
+pub fn save_clipboard() {
+    // Formats whose handle isn't global memory, and private ones we can't copy
+    const CF_BITMAP: u32 = 2;
+    const CF_METAFILEPICT: u32 = 3;
+    const CF_PALETTE: u32 = 9;
+    const CF_ENHMETAFILE: u32 = 14;
+    const CF_OWNERDISPLAY: u32 = 0x80;
+    const CF_DSPBITMAP: u32 = 0x82;
+    const CF_DSPMETAFILEPICT: u32 = 0x83;
+    const CF_DSPENHMETAFILE: u32 = 0x8E;
+    const SKIPPED: [u32; 8] = [
+        CF_BITMAP, CF_METAFILEPICT, CF_PALETTE, CF_ENHMETAFILE, CF_OWNERDISPLAY, CF_DSPBITMAP,
+        CF_DSPMETAFILEPICT, CF_DSPENHMETAFILE,
+    ];
+    const PRIVATE_AND_GDI: std::ops::RangeInclusive<u32> = 0x200..=0x3FF;
+
+    let mut saved = SAVED_CLIPBOARD.lock().unwrap();
+    saved.clear();
+    unsafe {
+        if OpenClipboard(ptr::null_mut()) == 0 {
+            tracing::debug!("Clipboard busy, not saved");
+            return;
+        }
+        let mut format = EnumClipboardFormats(0);
+        while format != 0 {
+            if !SKIPPED.contains(&format) && !PRIVATE_AND_GDI.contains(&format) {
+                let handle = GetClipboardData(format);
+                let data = if handle.is_null() { ptr::null_mut() } else { GlobalLock(handle) };
+                if !data.is_null() {
+                    let bytes = std::slice::from_raw_parts(data as *const u8, GlobalSize(handle));
+                    saved.push((format, bytes.to_vec()));
+                    GlobalUnlock(handle);
+                }
+            }
+            format = EnumClipboardFormats(format);
+        }
+        CloseClipboard();
+    }
+}
+
+/// Put back what `save_clipboard` saved, replacing the clipboard's contents.
+pub fn restore_clipboard() {
+    let saved = std::mem::take(&mut *SAVED_CLIPBOARD.lock().unwrap());
+    unsafe {
+        if OpenClipboard(ptr::null_mut()) == 0 {
+            tracing::warn!("Clipboard busy, previous contents not restored");
+            return;
+        }
+        EmptyClipboard();
+        for (format, bytes) in saved {
+            let handle = GlobalAlloc(GMEM_MOVEABLE, bytes.len());
+            if handle.is_null() {
+                continue;
+            }
+            let data = GlobalLock(handle);
+            if data.is_null() {
+                GlobalFree(handle);
+                continue;
+            }
+            ptr::copy_nonoverlapping(bytes.as_ptr(), data as *mut u8, bytes.len());
+            GlobalUnlock(handle);
+            // The clipboard owns the memory once this succeeds
+            if SetClipboardData(format, handle).is_null() {
+                GlobalFree(handle);
+            }
+        }
+        CloseClipboard();
+    }
+}
+
+/// Raw RTF on the clipboard ("Rich Text Format"), for apps that copy no plain text.
+pub fn read_clipboard_rtf() -> Option<String> {
+    unsafe {
+        let format = RegisterClipboardFormatW(to_wide("Rich Text Format").as_ptr());
+        if format == 0 || OpenClipboard(ptr::null_mut()) == 0 {
+            return None;
+        }
+        let handle = GetClipboardData(format);
+        let ptr = if handle.is_null() { ptr::null_mut() } else { GlobalLock(handle) };
+        let rtf = if ptr.is_null() {
+            None
+        } else {
+            let bytes = std::slice::from_raw_parts(ptr as *const u8, GlobalSize(handle));
+            let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+            let rtf = String::from_utf8_lossy(&bytes[..end]).into_owned();
+            GlobalUnlock(handle);
+            Some(rtf)
+        };
+        CloseClipboard();
+        rtf
+    }
+}
+
+/// The user's locale name, e.g. "en-US".
+pub fn system_locale() -> Option<String> {
+    // LOCALE_NAME_MAX_LENGTH
+    let mut buf = [0u16; 85];
+    let len = unsafe { GetUserDefaultLocaleName(buf.as_mut_ptr(), buf.len() as i32) };
+    // The length includes the terminating NUL
+    (len > 1).then(|| String::from_utf16_lossy(&buf[..len as usize - 1]))
+}
+
+/// Executable name of the foreground app, e.g. "excel.exe".
+pub fn frontmost_app_name() -> Option<String> {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_null() {
+            return None;
+        }
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, &mut pid);
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if process.is_null() {
+            return None;
+        }
+        let mut buf = [0u16; 260];
+        let mut len = buf.len() as u32;
+        let ok = QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, buf.as_mut_ptr(), &mut len);
+        CloseHandle(process);
+        if ok == 0 {
+            return None;
+        }
+        let path = String::from_utf16_lossy(&buf[..len as usize]);
+        path.rsplit('\\').next().map(str::to_string)
+    }
+}
+
 pub fn poll_mouse_event() -> Option<MouseEvent> {
     unsafe {
         let mut msg: MSG = std::mem::zeroed();
@@ -159,6 +774,12 @@ pub fn poll_mouse_event() -> Option<MouseEvent> {
             if msg.message == WM_QUIT {
                 return Some(MouseEvent::Quit);
             }
+            if msg.message == WM_HOTKEY {
+                return Some(MouseEvent::Hotkey(msg.wParam as u32));
+            }
+            if handle_input_key(&msg) {
+                continue;
+            }
             TranslateMessage(&msg);
             DispatchMessageW(&msg);
         }
@@ -170,6 +791,9 @@ pub fn poll_mouse_event() -> Option<MouseEvent> {
             down_y: MOUSE_DOWN_Y.load(Ordering::Relaxed),
             up_x: MOUSE_UP_X.load(Ordering::Relaxed),
             up_y: MOUSE_UP_Y.load(Ordering::Relaxed),
+            held_ms: MOUSE_UP_TIME
+                .load(Ordering::Relaxed)
+                .wrapping_sub(MOUSE_DOWN_TIME.load(Ordering::Relaxed)) as u64,
         });
     }
 
@@ -184,17 +808,62 @@ pub fn get_double_click_time_ms() -> u64 {
     (unsafe { GetDoubleClickTime() }) as u64
 }
 
+/// 1-based position of the monitor containing (or nearest to) a screen point,
+/// in the order Windows enumerates displays.
+pub fn monitor_index_at(x: i32, y: i32) -> Option<usize> {
+    unsafe extern "system" fn collect(
+        hmon: HMONITOR,
+        _hdc: HDC,
+        _rect: *mut RECT,
+        data: LPARAM,
+    ) -> windows_sys::core::BOOL {
+        (*(data as *mut Vec<HMONITOR>)).push(hmon);
+        1
+    }
+
+    unsafe {
+        let target = MonitorFromPoint(POINT { x, y }, MONITOR_DEFAULTTONEAREST);
+        let mut monitors: Vec<HMONITOR> = Vec::new();
+        EnumDisplayMonitors(ptr::null_mut(), ptr::null(), Some(collect), &mut monitors as *mut _ as LPARAM);
+        monitors.iter().position(|&m| m == target).map(|i| i + 1)
+    }
+}
+
 pub fn send_copy_command() {
+    send_ctrl_combo(VK_C);
+}
+
+pub fn send_paste_command() {
+    send_ctrl_combo(VK_V);
+}
+
+/// Wait briefly for hotkey modifiers to be released, otherwise a simulated
+/// Ctrl+C would reach the app as e.g. Ctrl+Shift+C.
+fn wait_for_modifiers_released() {
+    let deadline = Instant::now() + std::time::Duration::from_millis(1000);
+    let held = || {
+        [VK_SHIFT, VK_CONTROL, VK_MENU, VK_LWIN, VK_RWIN]
+            .iter()
+            .any(|&vk| unsafe { GetAsyncKeyState(vk as i32) } < 0)
+    };
+    while held() && Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+}
+
+fn send_ctrl_combo(vk: VIRTUAL_KEY) {
+    wait_for_modifiers_released();
+
     let mut inputs: [INPUT; 4] = unsafe { std::mem::zeroed() };
 
     inputs[0].r#type = INPUT_KEYBOARD;
     inputs[0].Anonymous.ki.wVk = VK_CONTROL;
 
     inputs[1].r#type = INPUT_KEYBOARD;
-    inputs[1].Anonymous.ki.wVk = VK_C;
+    inputs[1].Anonymous.ki.wVk = vk;
 
     inputs[2].r#type = INPUT_KEYBOARD;
-    inputs[2].Anonymous.ki.wVk = VK_C;
+    inputs[2].Anonymous.ki.wVk = vk;
     inputs[2].Anonymous.ki.dwFlags = KEYEVENTF_KEYUP;
 
     inputs[3].r#type = INPUT_KEYBOARD;
@@ -206,43 +875,143 @@ pub fn send_copy_command() {
     }
 }
 
+/// Triple-click at the pointer so the app under it selects the whole
+/// paragraph. Returns the pointer position.
+pub fn select_paragraph_at_cursor() -> (i32, i32) {
+    click_at_cursor(3)
+}
+
+/// Double-click at the pointer so the app under it selects the word, for
+/// `hover_translate`. Returns the pointer position.
+pub fn select_word_at_cursor() -> (i32, i32) {
+    click_at_cursor(2)
+}
+
+fn click_at_cursor(clicks: usize) -> (i32, i32) {
+    // A held hotkey modifier would turn these into Ctrl/Shift-clicks
+    wait_for_modifiers_released();
+
+    let mut pt = POINT { x: 0, y: 0 };
+    unsafe { GetCursorPos(&mut pt) };
+
+    let mut inputs: Vec<INPUT> = vec![unsafe { std::mem::zeroed() }; clicks * 2];
+    for (i, input) in inputs.iter_mut().enumerate() {
+        input.r#type = INPUT_MOUSE;
+        input.Anonymous.mi.dwFlags = if i % 2 == 0 { MOUSEEVENTF_LEFTDOWN } else { MOUSEEVENTF_LEFTUP };
+    }
+    unsafe {
+        SendInput(inputs.len() as u32, inputs.as_ptr(), std::mem::size_of::<INPUT>() as i32);
+    }
+    (pt.x, pt.y)
+}
+
+/// Pointer position for `hover_translate`; `None` while a mouse button is
+/// held or the pointer is over the popup, sidebar or Translate Text window.
+pub fn hover_point() -> Option<(i32, i32)> {
+    unsafe {
+        if [VK_LBUTTON, VK_RBUTTON, VK_MBUTTON].iter().any(|&vk| GetAsyncKeyState(vk as i32) < 0) {
+            return None;
+        }
+        let mut pt = POINT { x: 0, y: 0 };
+        if GetCursorPos(&mut pt) == 0 {
+            return None;
+        }
+        let over_own = popup_at(pt.x, pt.y).is_some()
+            || SIDEBAR_RECT.contains(pt.x, pt.y)
+            || INPUT_RECT.contains(pt.x, pt.y);
+        (!over_own).then_some((pt.x, pt.y))
+    }
+}
+
+/// True when the focused control is a password edit box (`ES_PASSWORD`).
+/// Copying from it is blocked by Windows anyway, so the grab is skipped.
+pub fn is_secure_input_active() -> bool {
+    unsafe {
+        let mut info: GUITHREADINFO = std::mem::zeroed();
+        info.cbSize = std::mem::size_of::<GUITHREADINFO>() as u32;
+        if GetGUIThreadInfo(0, &mut info) == 0 || info.hwndFocus.is_null() {
+            return false;
+        }
+
+        let mut class_buf = [0u16; 64];
+        let len = GetClassNameW(info.hwndFocus, class_buf.as_mut_ptr(), class_buf.len() as i32);
+        if len <= 0 {
+            return false;
+        }
+        let class = String::from_utf16_lossy(&class_buf[..len as usize]).to_ascii_lowercase();
+        // "Edit" plus the RichEdit family all honour ES_PASSWORD
+        if !class.contains("edit") {
+            return false;
+        }
+
+        let style = GetWindowLongW(info.hwndFocus, GWL_STYLE);
+        style & ES_PASSWORD != 0
+    }
+}
+
+/// Whether a full-screen app (game, video, slideshow) is in front, as the
+/// shell decides it for holding back notifications. Unlike comparing window
+/// and monitor rects, a maximized window with an auto-hide taskbar doesn't count.
+pub fn is_fullscreen_app_active() -> bool {
+    let mut state = 0;
+    if unsafe { SHQueryUserNotificationState(&mut state) } < 0 {
+        return false;
+    }
+    matches!(state, QUNS_BUSY | QUNS_RUNNING_D3D_FULL_SCREEN | QUNS_PRESENTATION_MODE)
+}
+
+/// Whether the app runs inside a Remote Desktop session, where the
+/// clipboard is synced over the network.
+pub fn is_remote_session() -> bool {
+    unsafe { GetSystemMetrics(SM_REMOTESESSION) != 0 }
+}
+
 pub fn show_popup(
-    _original: &str,
+    original: &str,
     translated: &str,
+    secondary: Option<&str>,
     _duration_secs: u64,
     pos: SelectionPos,
+    content: PopupContent,
 ) {
     unsafe {
-        destroy_popup();
+        // Make room for the new popup, oldest first
+        while popups().len() >= popup_options().max_popups.max(1) {
+            destroy_popup(popups()[0].hwnd);
+        }
 
-        TRANSLATED_TEXT = Some(translated.into());
+        // Use the monitor where the selection center is located, and size
+        // everything for its DPI rather than the one the app started on
+        let sel_top = pos.down_y.min(pos.up_y);
+        let sel_bottom = pos.down_y.max(pos.up_y);
+        let sel_center_x = (pos.down_x + pos.up_x) / 2;
+        let center_pt = POINT { x: sel_center_x, y: (sel_top + sel_bottom) / 2 };
+        let hmon = MonitorFromPoint(center_pt, MONITOR_DEFAULTTONEAREST);
+        let scale = monitor_scale(hmon);
+        let _scale = ScaleGuard::new(scale);
 
         let hi = GetModuleHandleW(ptr::null());
         let cls = CLASS_NAME.get().unwrap();
 
         let padding = s(BASE_PADDING);
         let max_w = s(BASE_MAX_WIDTH);
-        let min_w = s(BASE_MIN_WIDTH);
+        let min_w = s(min_popup_width(translated, content).round() as i32).min(max_w);
         let gap_above = s(BASE_GAP_ABOVE);
         let corner_r = s(BASE_CORNER_RADIUS);
-        let slide_px = s(BASE_SLIDE_PX);
+        let slide_px = if popup_options().slide { s(BASE_SLIDE_PX) } else { 0 };
 
         let hdc = GetDC(ptr::null_mut());
         let cw = max_w - padding * 2;
 
         let h_trans = measure_text(hdc, translated, s(BASE_FONT_TRANSLATED), true, cw);
+        let h_secondary = secondary
+            .map(|t| s(BASE_SECONDARY_GAP) + measure_text(hdc, t, s(BASE_FONT_SECONDARY), false, cw))
+            .unwrap_or(0);
         ReleaseDC(ptr::null_mut(), hdc);
 
         let w = (cw + padding * 2).max(min_w);
-        let full_h = padding + h_trans + padding;
-
-        let sel_top = pos.down_y.min(pos.up_y);
-        let sel_bottom = pos.down_y.max(pos.up_y);
-        let sel_center_x = (pos.down_x + pos.up_x) / 2;
+        let full_h = padding + h_trans + h_secondary + padding;
 
-        // Use the monitor where the selection center is located
-        let center_pt = POINT { x: sel_center_x, y: (sel_top + sel_bottom) / 2 };
-        let hmon = MonitorFromPoint(center_pt, MONITOR_DEFAULTTONEAREST);
         let mut mi: MONITORINFO = std::mem::zeroed();
         mi.cbSize = std::mem::size_of::<MONITORINFO>() as u32;
         GetMonitorInfoW(hmon, &mut mi);
@@ -253,29 +1022,35 @@ pub fn show_popup(
         let mon_bottom = work.bottom;
 
         let max_h = s(BASE_MAX_HEIGHT).min((mon_bottom - mon_top) * 3 / 5);
-        let h = full_h.min(max_h);
-        CONTENT_HEIGHT = full_h;
-        SCROLL_OFFSET = 0;
-
-        let mut x = sel_center_x - w / 2;
-        let above = sel_top - h - gap_above >= mon_top + 4;
-        let mut y = if above {
-            sel_top - h - gap_above
+        let (w, h) = sticky_popup_size(w as f64, full_h.min(max_h) as f64);
+        let (w, h) = (w as i32, h as i32);
+
+        let options = popup_options();
+        let (mut x, mut y, above) = if content == PopupContent::Status {
+            ((mon_left + mon_right - w) / 2, (mon_top + mon_bottom - h) / 2, false)
+        } else if options.placement == Placement::Cursor {
+            let (dx, dy) = options.cursor_offset;
+            (pos.up_x - w / 2 + s(dx), pos.up_y + s(dy), false)
         } else {
-            sel_bottom + gap_above
+            let above = sel_top - h - gap_above >= mon_top + 4;
+            let y = if above {
+                sel_top - h - gap_above
+            } else {
+                sel_bottom + gap_above
+            };
+            (sel_center_x - w / 2, y, above)
         };
 
         if x + w > mon_right - 4 { x = mon_right - w - 4; }
         if x < mon_left + 4 { x = mon_left + 4; }
+        y = stack_clear_of_popups(x, y, w, h, above);
         if y + h > mon_bottom - 4 { y = mon_bottom - h - 4; }
         if y < mon_top + 4 { y = mon_top + 4; }
 
-        TARGET_X = x;
-        TARGET_Y = y;
         let start_y = if above { y + slide_px } else { y - slide_px };
 
         let hwnd = CreateWindowExW(
-            WS_EX_TOPMOST | WS_EX_TOOLWINDOW | WS_EX_NOACTIVATE | WS_EX_LAYERED,
+            card_ex_style(),
             cls.as_ptr(),
             ptr::null(),
             WS_POPUP,
@@ -289,35 +1064,104 @@ pub fn show_popup(
             return;
         }
 
-        let rgn = CreateRoundRectRgn(0, 0, w, h, corner_r * 2, corner_r * 2);
-        SetWindowRgn(hwnd, rgn, 0);
-
-        SetLayeredWindowAttributes(hwnd, 0, 0, LWA_ALPHA);
+        if !plain_renderer() {
+            let rgn = CreateRoundRectRgn(0, 0, w, h, corner_r * 2, corner_r * 2);
+            SetWindowRgn(hwnd, rgn, 0);
+        }
 
-        POPUP_HWND = hwnd;
-        PHASE = PHASE_FADE_IN;
-        ANIM_START = Some(Instant::now());
+        let start_alpha = if popup_options().fade { 0 } else { MAX_ALPHA };
+        set_card_alpha(hwnd, start_alpha);
+
+        let now = Instant::now();
+        popups().push(Popup {
+            hwnd,
+            translated: translated.into(),
+            secondary: secondary.map(Into::into),
+            original: original.into(),
+            original_top: None,
+            primary_height: h_trans,
+            content_height: full_h,
+            scroll_offset: 0,
+            phase: PHASE_FADE_IN,
+            anim_start: now,
+            shown_at: now,
+            hide_deadline: None,
+            hide_total_ms: 0,
+            target_x: x,
+            target_y: y,
+            deselect_close: false,
+            drag_origin: (x, y),
+            rect: RECT { left: x, top: y, right: x + w, bottom: y + h },
+            scale,
+        });
 
         ShowWindow(hwnd, SW_SHOWNOACTIVATE);
-        update_popup_rect_cache();
+        let Some(p) = popup_mut(hwnd) else {
+            return;
+        };
+        update_popup_rect_cache(p);
 
         SetTimer(hwnd, ANIM_TIMER, ANIM_FRAME_MS, None);
 
-        // auto-hide: ~15 chars/sec reading speed, 2-20s range
-        let total_chars = translated.chars().count();
-        let reading_secs = (total_chars as f64 / 15.0).clamp(2.0, 20.0);
-        let reading_ms = (reading_secs * 1000.0) as u32;
-        let auto_hide_ms = FADE_IN_MS as u32 + reading_ms + 3000;
-        SetTimer(hwnd, HIDE_TIMER, auto_hide_ms, None);
+        start_hide_timer(p, auto_hide_ms(translated, content) as u32);
+    }
+}
+
+/// Show `text` briefly in a popup centered on the monitor under the pointer.
+pub fn show_status(text: &str) {
+    let mut pt = POINT { x: 0, y: 0 };
+    unsafe { GetCursorPos(&mut pt) };
+    let pos = SelectionPos { down_x: pt.x, down_y: pt.y, up_x: pt.x, up_y: pt.y };
+    show_popup("", text, None, 0, pos, PopupContent::Status);
+}
+
+/// With `max_popups`, move a new popup at `y` off the popups still up,
+/// past each one it overlaps: upwards when it was placed above the selection.
+unsafe fn stack_clear_of_popups(x: i32, mut y: i32, w: i32, h: i32, upwards: bool) -> i32 {
+    let gap = s(BASE_STACK_GAP);
+    // Each step clears one popup, so one pass per popup is enough
+    for _ in 0..popups().len() {
+        let overlapped = popups()
+            .iter()
+            .map(|p| p.rect)
+            .find(|r| x < r.right && x + w > r.left && y < r.bottom && y + h > r.top);
+        let Some(r) = overlapped else {
+            break;
+        };
+        y = if upwards { r.top - h - gap } else { r.bottom + gap };
     }
+    y
 }
 
 pub fn on_click_away() {
     unsafe {
-        if POPUP_HWND.is_null() || PHASE == PHASE_FADE_OUT || CLOSE_SCHEDULED {
-            return;
+        for p in popups().iter_mut() {
+            begin_fade_out(p, true);
         }
-        begin_fade_out(POPUP_HWND, true);
+    }
+}
+
+/// Whether any `suppress_modifier` key is down right now.
+fn suppress_modifier_held() -> bool {
+    let mods = suppress_modifiers();
+    let keys: &[(u8, &[VIRTUAL_KEY])] = &[
+        (hotkey::MOD_CTRL, &[VK_CONTROL]),
+        (hotkey::MOD_ALT, &[VK_MENU]),
+        (hotkey::MOD_SHIFT, &[VK_SHIFT]),
+        (hotkey::MOD_SUPER, &[VK_LWIN, VK_RWIN]),
+    ];
+    keys.iter()
+        .filter(|(m, _)| mods & m != 0)
+        .flat_map(|(_, vks)| vks.iter())
+        .any(|&vk| unsafe { GetAsyncKeyState(vk as i32) } < 0)
+}
+
+/// Down/up window messages for the configured trigger button.
+fn trigger_messages() -> (u32, u32) {
+    match TRIGGER_BUTTON.load(Ordering::Relaxed) {
+        b if b == TriggerButton::Middle as u8 => (WM_MBUTTONDOWN, WM_MBUTTONUP),
+        b if b == TriggerButton::Right as u8 => (WM_RBUTTONDOWN, WM_RBUTTONUP),
+        _ => (WM_LBUTTONDOWN, WM_LBUTTONUP),
     }
 }
 
@@ -328,30 +1172,60 @@ unsafe extern "system" fn mouse_hook_proc(
 ) -> LRESULT {
     if code >= 0 {
         let info = &*(lparam as *const MSLLHOOKSTRUCT);
+        let (trigger_down, trigger_up) = trigger_messages();
+        // Presses on the popup move it (or dismiss it, see WM_EXITSIZEMOVE)
+        // instead of counting as a selection or a click elsewhere.
+        // The sidebar and the Translate Text window are treated the same
+        // way, minus the dragging.
+        let popup = popup_at(info.pt.x, info.pt.y);
+        let in_popup = popup.is_some()
+            || SIDEBAR_RECT.contains(info.pt.x, info.pt.y)
+            || INPUT_RECT.contains(info.pt.x, info.pt.y);
+        // Never consume button messages — right-click context menus must still work.
         match wparam as u32 {
-            WM_LBUTTONDOWN => {
-                MOUSE_DOWN_X.store(info.pt.x, Ordering::Relaxed);
-                MOUSE_DOWN_Y.store(info.pt.y, Ordering::Relaxed);
+            msg if msg == trigger_down => {
+                if in_popup {
+                    PRESS_IN_POPUP.store(true, Ordering::Relaxed);
+                } else {
+                    PRESS_SUPPRESSED.store(suppress_modifier_held(), Ordering::Relaxed);
+                    MOUSE_DOWN_X.store(info.pt.x, Ordering::Relaxed);
+                    MOUSE_DOWN_Y.store(info.pt.y, Ordering::Relaxed);
+                    MOUSE_DOWN_TIME.store(info.time, Ordering::Relaxed);
+                    MOUSE_CLICK_FLAG.store(true, Ordering::Relaxed);
+                }
+            }
+            msg if msg == trigger_up => {
+                if PRESS_IN_POPUP.swap(false, Ordering::Relaxed) {
+                    // A drag of the popup, handled by WM_EXITSIZEMOVE
+                } else if PRESS_SUPPRESSED.swap(false, Ordering::Relaxed) || suppress_modifier_held() {
+                    tracing::debug!("Selection ignored, suppress_modifier held");
+                } else {
+                    MOUSE_UP_X.store(info.pt.x, Ordering::Relaxed);
+                    MOUSE_UP_Y.store(info.pt.y, Ordering::Relaxed);
+                    MOUSE_UP_TIME.store(info.time, Ordering::Relaxed);
+                    MOUSE_UP_FLAG.store(true, Ordering::Relaxed);
+                }
+            }
+            WM_LBUTTONDOWN if !in_popup => {
+                // Left click still dismisses the popup when another button triggers selection
                 MOUSE_CLICK_FLAG.store(true, Ordering::Relaxed);
             }
-            WM_LBUTTONUP => {
-                MOUSE_UP_X.store(info.pt.x, Ordering::Relaxed);
-                MOUSE_UP_Y.store(info.pt.y, Ordering::Relaxed);
-                MOUSE_UP_FLAG.store(true, Ordering::Relaxed);
+            WM_MOUSEMOVE => {
+                // Not mid-drag: the move loop owns the popup then
+                if let Some(p) = popup.filter(|_| !PRESS_IN_POPUP.load(Ordering::Relaxed)) {
+                    PostMessageW(p.hwnd, WM_POPUP_HOVER, 0, 0);
+                }
             }
             WM_MOUSEWHEEL => {
-                if !POPUP_HWND.is_null() && CONTENT_HEIGHT > 0 {
-                    let left = POPUP_RECT_LEFT.load(Ordering::Relaxed);
-                    let top = POPUP_RECT_TOP.load(Ordering::Relaxed);
-                    let right = POPUP_RECT_RIGHT.load(Ordering::Relaxed);
-                    let bottom = POPUP_RECT_BOTTOM.load(Ordering::Relaxed);
-                    if info.pt.x >= left && info.pt.x < right
-                        && info.pt.y >= top && info.pt.y < bottom
-                    {
-                        let delta = (info.mouse_data >> 16) as i16 as isize;
-                        PostMessageW(POPUP_HWND, WM_POPUP_SCROLL, delta as usize, 0);
-                        return 1; // consume so background doesn't scroll
-                    }
+                if let Some(p) = popup.filter(|p| p.content_height > 0) {
+                    let delta = (info.mouse_data >> 16) as i16 as isize;
+                    PostMessageW(p.hwnd, WM_POPUP_SCROLL, delta as usize, 0);
+                    return 1; // consume so background doesn't scroll
+                }
+                if SIDEBAR_RECT.contains(info.pt.x, info.pt.y) {
+                    let delta = (info.mouse_data >> 16) as i16 as isize;
+                    PostMessageW(SIDEBAR_HWND, WM_POPUP_SCROLL, delta as usize, 0);
+                    return 1;
                 }
             }
             _ => {}
@@ -360,76 +1234,134 @@ unsafe extern "system" fn mouse_hook_proc(
     CallNextHookEx(ptr::null_mut(), code, wparam, lparam)
 }
 
-unsafe fn anim_tick(hwnd: HWND) {
-    let elapsed = match ANIM_START {
-        Some(s) => s.elapsed().as_secs_f64() * 1000.0,
-        None => return,
-    };
+unsafe fn anim_tick(p: &mut Popup) {
+    let hwnd = p.hwnd;
+    let elapsed = p.anim_start.elapsed().as_secs_f64() * 1000.0;
 
-    let slide_px = s(BASE_SLIDE_PX);
+    let opts = popup_options();
+    let slide_px = if opts.slide { s(BASE_SLIDE_PX) } else { 0 };
+    let fade = |visible: f64| if opts.fade { (visible * MAX_ALPHA as f64) as u8 } else { MAX_ALPHA };
 
-    match PHASE {
+    if p.phase != PHASE_FADE_OUT && lifetime_exceeded(p.shown_at) {
+        begin_fade_out(p, false);
+        return;
+    }
+
+    match p.phase {
         PHASE_FADE_IN => {
-            let t = (elapsed / FADE_IN_MS).min(1.0);
+            let t = anim_progress(elapsed, opts.fade_in_ms);
             let ease = ease_out_cubic(t);
 
-            let alpha = (ease * MAX_ALPHA as f64) as u8;
-            SetLayeredWindowAttributes(hwnd, 0, alpha, LWA_ALPHA);
+            set_card_alpha(hwnd, fade(ease));
 
             let offset = ((1.0 - ease) * slide_px as f64) as i32;
             SetWindowPos(
                 hwnd, ptr::null_mut(),
-                TARGET_X, TARGET_Y + offset, 0, 0,
+                p.target_x, p.target_y + offset, 0, 0,
                 SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE | SWP_NOREDRAW,
             );
             InvalidateRect(hwnd, ptr::null(), 0);
 
             if t >= 1.0 {
-                PHASE = PHASE_VISIBLE;
-                SetLayeredWindowAttributes(hwnd, 0, MAX_ALPHA, LWA_ALPHA);
+                p.phase = PHASE_VISIBLE;
+                set_card_alpha(hwnd, MAX_ALPHA);
                 SetWindowPos(
                     hwnd, ptr::null_mut(),
-                    TARGET_X, TARGET_Y, 0, 0,
+                    p.target_x, p.target_y, 0, 0,
                     SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE,
                 );
             }
         }
         PHASE_FADE_OUT => {
-            let fade_duration = if DESELECT_CLOSE { FADE_OUT_DESELECT_MS } else { FADE_OUT_MS };
-            let t = (elapsed / fade_duration).min(1.0);
+            let fade_duration = if p.deselect_close { opts.fade_out_deselect_ms } else { opts.fade_out_ms };
+            let t = anim_progress(elapsed, fade_duration);
             let ease = ease_in_cubic(t);
 
-            let alpha = ((1.0 - ease) * MAX_ALPHA as f64) as u8;
-            SetLayeredWindowAttributes(hwnd, 0, alpha, LWA_ALPHA);
+            set_card_alpha(hwnd, fade(1.0 - ease));
 
             let offset = (ease * (slide_px / 2) as f64) as i32;
             SetWindowPos(
                 hwnd, ptr::null_mut(),
-                TARGET_X, TARGET_Y - offset, 0, 0,
+                p.target_x, p.target_y - offset, 0, 0,
                 SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE | SWP_NOREDRAW,
             );
 
             if t >= 1.0 {
-                PHASE = PHASE_NONE;
-                KillTimer(hwnd, ANIM_TIMER);
-                destroy_popup();
+                destroy_popup(hwnd);
+            }
+        }
+        PHASE_VISIBLE => {
+            if opts.countdown_bar && p.hide_deadline.is_some() {
+                InvalidateRect(hwnd, ptr::null(), 0);
             }
         }
-        PHASE_VISIBLE => {}
         _ => {
             KillTimer(hwnd, ANIM_TIMER);
         }
     }
 }
 
-unsafe fn begin_fade_out(hwnd: HWND, is_deselect: bool) {
-    if PHASE == PHASE_FADE_OUT || PHASE == PHASE_NONE {
+/// Extended style for the popup and sidebar: layered (translucent) unless
+/// the plain renderer is in use.
+fn card_ex_style() -> u32 {
+    let style = WS_EX_TOPMOST | WS_EX_TOOLWINDOW | WS_EX_NOACTIVATE;
+    if plain_renderer() { style } else { style | WS_EX_LAYERED }
+}
+
+/// Set the opacity of a window created with `card_ex_style`; plain windows
+/// aren't layered and always stay opaque.
+unsafe fn set_card_alpha(hwnd: HWND, alpha: u8) {
+    if !plain_renderer() {
+        SetLayeredWindowAttributes(hwnd, 0, alpha, LWA_ALPHA);
+    }
+}
+
+unsafe fn begin_fade_out(p: &mut Popup, is_deselect: bool) {
+    if p.phase == PHASE_FADE_OUT || p.phase == PHASE_NONE {
         return;
     }
-    PHASE = PHASE_FADE_OUT;
-    DESELECT_CLOSE = is_deselect;
-    ANIM_START = Some(Instant::now());
-    KillTimer(hwnd, HIDE_TIMER);
+    p.phase = PHASE_FADE_OUT;
+    p.deselect_close = is_deselect;
+    p.anim_start = Instant::now();
+    KillTimer(p.hwnd, HIDE_TIMER);
+}
+
+/// (Re)start the auto-hide timer, remembering its deadline for the countdown bar.
+unsafe fn start_hide_timer(p: &mut Popup, ms: u32) {
+    SetTimer(p.hwnd, HIDE_TIMER, ms, None);
+    p.hide_deadline = Some(Instant::now() + std::time::Duration::from_millis(ms as u64));
+    p.hide_total_ms = ms;
+}
+
+/// Auto-hide delay after the user hovers, scrolls or moves the popup.
+fn interaction_hide_ms() -> u32 {
+    popup_options().interaction_extend_ms as u32
+}
+
+/// Smallest push back worth restarting the hide timer for, so moving the
+/// pointer over the popup doesn't reset it on every WM_MOUSEMOVE.
+const HIDE_EXTEND_STEP: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// After the user hovers, scrolls or moves the popup, leave it at least
+/// `interaction_extend_ms` before hiding; a later deadline is kept.
+unsafe fn extend_hide_timer(p: &mut Popup) {
+    let ms = interaction_hide_ms();
+    let extended = Instant::now() + std::time::Duration::from_millis(ms as u64);
+    if p.hide_deadline.is_some_and(|deadline| deadline + HIDE_EXTEND_STEP >= extended) {
+        return;
+    }
+    start_hide_timer(p, ms);
+}
+
+/// Cancel any fade and show the popup fully at its resting position.
+unsafe fn settle_visible(p: &mut Popup) {
+    p.phase = PHASE_VISIBLE;
+    set_card_alpha(p.hwnd, MAX_ALPHA);
+    SetWindowPos(
+        p.hwnd, ptr::null_mut(),
+        p.target_x, p.target_y, 0, 0,
+        SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE,
+    );
 }
 
 unsafe extern "system" fn wnd_proc(
@@ -438,22 +1370,54 @@ unsafe extern "system" fn wnd_proc(
     wp: WPARAM,
     lp: LPARAM,
 ) -> LRESULT {
+    // No state yet while CreateWindowExW runs, and none left once destroyed
+    let Some(p) = popup_mut(hwnd) else {
+        return DefWindowProcW(hwnd, msg, wp, lp);
+    };
+    let _scale = ScaleGuard::new(p.scale);
     match msg {
         WM_PAINT => {
-            paint(hwnd);
+            paint(p);
             0
         }
         WM_TIMER => {
             match wp {
-                ANIM_TIMER => anim_tick(hwnd),
-                HIDE_TIMER => begin_fade_out(hwnd, false),
+                ANIM_TIMER => anim_tick(p),
+                HIDE_TIMER => begin_fade_out(p, false),
                 _ => {}
             }
             0
         }
-        WM_LBUTTONDOWN => {
-            begin_fade_out(hwnd, true);
-            0
+        // The whole card is a drag handle; the text isn't selectable
+        WM_NCHITTEST => HTCAPTION as LRESULT,
+        WM_MOUSEACTIVATE => MA_NOACTIVATE as LRESULT,
+        WM_ENTERSIZEMOVE => {
+            // Hold still while dragged: no slide, no fade, no auto-hide
+            KillTimer(hwnd, HIDE_TIMER);
+            p.hide_deadline = None;
+            if p.phase != PHASE_VISIBLE {
+                settle_visible(p);
+            }
+            p.drag_origin = (p.target_x, p.target_y);
+            0
+        }
+        WM_EXITSIZEMOVE => {
+            let mut r: RECT = std::mem::zeroed();
+            GetWindowRect(hwnd, &mut r);
+            if (r.left, r.top) == p.drag_origin {
+                // Plain click, dismiss like before
+                begin_fade_out(p, true);
+            } else {
+                p.target_x = r.left;
+                p.target_y = r.top;
+                update_popup_rect_cache(p);
+                extend_hide_timer(p);
+            }
+            0
+        }
+        WM_DISPLAYCHANGE => {
+            on_display_change(p);
+            0
         }
         WM_POPUP_SCROLL => {
             let delta = wp as i16 as i32;
@@ -461,27 +1425,128 @@ unsafe extern "system" fn wnd_proc(
             let pixels = -(delta as f64 / 120.0 * scroll_step as f64) as i32;
             let mut rc: RECT = std::mem::zeroed();
             GetClientRect(hwnd, &mut rc);
-            let max_scroll = (CONTENT_HEIGHT - rc.bottom).max(0);
-            SCROLL_OFFSET = (SCROLL_OFFSET + pixels).clamp(0, max_scroll);
+            let max_scroll = (p.content_height - rc.bottom).max(0);
+            p.scroll_offset = (p.scroll_offset + pixels).clamp(0, max_scroll);
             InvalidateRect(hwnd, ptr::null(), 0);
-            if PHASE == PHASE_FADE_OUT {
-                PHASE = PHASE_VISIBLE;
-                SetLayeredWindowAttributes(hwnd, 0, MAX_ALPHA, LWA_ALPHA);
-                SetWindowPos(
-                    hwnd, ptr::null_mut(),
-                    TARGET_X, TARGET_Y, 0, 0,
-                    SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE,
-                );
+            if p.phase == PHASE_FADE_OUT {
+                // Its hide timer is gone; any deadline left is stale
+                settle_visible(p);
+                start_hide_timer(p, interaction_hide_ms());
+            } else {
+                extend_hide_timer(p);
+            }
+            0
+        }
+        WM_POPUP_HOVER => {
+            if popup_options().original_on_hover {
+                reveal_original(p);
+            }
+            if p.phase == PHASE_VISIBLE {
+                extend_hide_timer(p);
             }
-            KillTimer(hwnd, HIDE_TIMER);
-            SetTimer(hwnd, HIDE_TIMER, 8000, None);
             0
         }
         _ => DefWindowProcW(hwnd, msg, wp, lp),
     }
 }
 
-unsafe fn paint(hwnd: HWND) {
+/// `original_on_hover`: grow the popup to fit the original text under the
+/// translation, moving it up if it would run off the bottom of its monitor.
+unsafe fn reveal_original(p: &mut Popup) {
+    if p.original_top.is_some() || p.original.trim().is_empty() || p.phase == PHASE_NONE {
+        return;
+    }
+    let hwnd = p.hwnd;
+    let mut r: RECT = std::mem::zeroed();
+    GetWindowRect(hwnd, &mut r);
+    let (w, old_h) = (r.right - r.left, r.bottom - r.top);
+
+    // Same text width as `paint`
+    let padding = s(BASE_PADDING);
+    let cw = w - (padding + 2) * 2;
+    let hdc = GetDC(ptr::null_mut());
+    let h_original = measure_text(hdc, &p.original, s(BASE_FONT_SECONDARY), false, cw);
+    ReleaseDC(ptr::null_mut(), hdc);
+
+    let top = p.content_height - padding + s(BASE_SECONDARY_GAP);
+    p.original_top = Some(top);
+    p.content_height = top + h_original + padding;
+
+    let hmon = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+    let mut mi: MONITORINFO = std::mem::zeroed();
+    mi.cbSize = std::mem::size_of::<MONITORINFO>() as u32;
+    GetMonitorInfoW(hmon, &mut mi);
+    let work = mi.rcWork;
+    let max_h = s(BASE_MAX_HEIGHT).min((work.bottom - work.top) * 3 / 5);
+    let h = p.content_height.min(max_h).max(old_h);
+
+    if p.phase == PHASE_FADE_OUT {
+        settle_visible(p);
+    }
+    p.target_y = p.target_y.min(work.bottom - h - 4).max(work.top + 4);
+    SetWindowPos(
+        hwnd, ptr::null_mut(),
+        p.target_x, p.target_y, w, h,
+        SWP_NOZORDER | SWP_NOACTIVATE,
+    );
+    if !plain_renderer() {
+        let corner_r = s(BASE_CORNER_RADIUS);
+        let rgn = CreateRoundRectRgn(0, 0, w, h, corner_r * 2, corner_r * 2);
+        SetWindowRgn(hwnd, rgn, 1);
+    }
+    update_popup_rect_cache(p);
+    InvalidateRect(hwnd, ptr::null(), 0);
+    extend_hide_timer(p);
+}
+
+/// Monitors were added, removed or rearranged while the popup is up: keep it
+/// on a monitor's work area, or dismiss it if its own monitor went away.
+unsafe fn on_display_change(p: &mut Popup) {
+    if p.phase == PHASE_NONE || p.phase == PHASE_FADE_OUT {
+        return;
+    }
+    let hwnd = p.hwnd;
+    let mut hmon = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONULL);
+    if hmon.is_null() {
+        if popup_options().display_change == DisplayChange::Dismiss {
+            tracing::info!("Popup's monitor disconnected, dismissing");
+            begin_fade_out(p, true);
+            return;
+        }
+        hmon = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+    }
+    let mut mi: MONITORINFO = std::mem::zeroed();
+    mi.cbSize = std::mem::size_of::<MONITORINFO>() as u32;
+    GetMonitorInfoW(hmon, &mut mi);
+    let work = mi.rcWork;
+
+    let mut r: RECT = std::mem::zeroed();
+    GetWindowRect(hwnd, &mut r);
+    let (w, h) = (r.right - r.left, r.bottom - r.top);
+    p.target_x = p.target_x.min(work.right - w - 4).max(work.left + 4);
+    p.target_y = p.target_y.min(work.bottom - h - 4).max(work.top + 4);
+    SetWindowPos(
+        hwnd, ptr::null_mut(),
+        p.target_x, p.target_y, 0, 0,
+        SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE,
+    );
+    update_popup_rect_cache(p);
+}
+
+/// Mix an RGB accent into a GDI COLORREF (0x00BBGGRR).
+fn blend_colorref(base: u32, accent: [u8; 3], amount: f64) -> u32 {
+    let mix = |base_channel: u32, accent_channel: u8| {
+        let b = (base_channel & 0xFF) as f64;
+        (b + (accent_channel as f64 - b) * amount).round() as u32
+    };
+    let r = mix(base, accent[0]);
+    let g = mix(base >> 8, accent[1]);
+    let b = mix(base >> 16, accent[2]);
+    (b << 16) | (g << 8) | r
+}
+
+unsafe fn paint(p: &Popup) {
+    let hwnd = p.hwnd;
     let mut ps: PAINTSTRUCT = std::mem::zeroed();
     let hdc = BeginPaint(hwnd, &mut ps);
 
@@ -496,65 +1561,133 @@ unsafe fn paint(hwnd: HWND) {
 
     let padding = s(BASE_PADDING);
 
-    let bg = CreateSolidBrush(BG_COLOR);
+    let accent = popup_options().accent;
+    let tint = |base: u32, amount: f64| match accent {
+        Some(rgb) => blend_colorref(base, rgb, amount),
+        None => base,
+    };
+
+    let bg = CreateSolidBrush(tint(BG_COLOR, ACCENT_BG_MIX));
     let fill_rc = RECT { left: 0, top: 0, right: w, bottom: h };
     FillRect(mem_dc, &fill_rc, bg);
     DeleteObject(bg);
 
-    let corner_r = s(BASE_CORNER_RADIUS);
     let null_brush = GetStockObject(NULL_BRUSH);
     let saved_brush = SelectObject(mem_dc, null_brush);
 
-    let pen1 = CreatePen(PS_SOLID, 1, BORDER_SHADOW);
-    let saved_pen = SelectObject(mem_dc, pen1);
-    RoundRect(mem_dc, 0, 0, w, h, corner_r * 2, corner_r * 2);
-
-    let pen2 = CreatePen(PS_SOLID, 1, BORDER_HIGHLIGHT);
-    SelectObject(mem_dc, pen2);
-    DeleteObject(pen1);
-    RoundRect(mem_dc, 1, 1, w - 1, h - 1, (corner_r - 1) * 2, (corner_r - 1) * 2);
-
-    let pen3 = CreatePen(PS_SOLID, 1, BORDER_HIGHLIGHT_INNER);
-    SelectObject(mem_dc, pen3);
-    DeleteObject(pen2);
-    RoundRect(mem_dc, 2, 2, w - 2, h - 2, (corner_r - 2) * 2, (corner_r - 2) * 2);
-
-    SelectObject(mem_dc, saved_pen);
-    DeleteObject(pen3);
+    let corner_r = if plain_renderer() { 0 } else { s(BASE_CORNER_RADIUS) };
+    if plain_renderer() {
+        // Single square outline, no bevel
+        let pen = CreatePen(PS_SOLID, 1, tint(BORDER_HIGHLIGHT, ACCENT_BORDER_MIX));
+        let saved_pen = SelectObject(mem_dc, pen);
+        Rectangle(mem_dc, 0, 0, w, h);
+        SelectObject(mem_dc, saved_pen);
+        DeleteObject(pen);
+    } else {
+        let pen1 = CreatePen(PS_SOLID, 1, tint(BORDER_SHADOW, ACCENT_BORDER_MIX));
+        let saved_pen = SelectObject(mem_dc, pen1);
+        RoundRect(mem_dc, 0, 0, w, h, corner_r * 2, corner_r * 2);
+
+        let pen2 = CreatePen(PS_SOLID, 1, tint(BORDER_HIGHLIGHT, ACCENT_BORDER_MIX));
+        SelectObject(mem_dc, pen2);
+        DeleteObject(pen1);
+        RoundRect(mem_dc, 1, 1, w - 1, h - 1, (corner_r - 1) * 2, (corner_r - 1) * 2);
+
+        let pen3 = CreatePen(PS_SOLID, 1, tint(BORDER_HIGHLIGHT_INNER, ACCENT_BORDER_MIX));
+        SelectObject(mem_dc, pen3);
+        DeleteObject(pen2);
+        RoundRect(mem_dc, 2, 2, w - 2, h - 2, (corner_r - 2) * 2, (corner_r - 2) * 2);
+
+        SelectObject(mem_dc, saved_pen);
+        DeleteObject(pen3);
+    }
     SelectObject(mem_dc, saved_brush);
 
     SetBkMode(mem_dc, TRANSPARENT as i32);
 
-    let has_scroll = CONTENT_HEIGHT > h;
+    let has_scroll = p.content_height > h;
 
-    #[allow(clippy::deref_addrof)]
-    let trans_ref = &*(&raw const TRANSLATED_TEXT);
-    if let Some(trans) = trans_ref {
+    {
+        let trans = &p.translated;
         let text_left = padding + 2;
         let cw = w - text_left * 2;
 
         let saved = SaveDC(mem_dc);
         IntersectClipRect(mem_dc, text_left, padding, text_left + cw, h - padding);
 
-        let f = create_font(s(BASE_FONT_TRANSLATED), true);
+        let f = create_font(s(BASE_FONT_TRANSLATED), true, trans);
         let old_f = SelectObject(mem_dc, f);
         SetTextColor(mem_dc, TRANSLATED_COLOR);
-        let text_top = padding - SCROLL_OFFSET;
-        let mut r = RECT { left: text_left, top: text_top, right: text_left + cw, bottom: text_top + CONTENT_HEIGHT };
-        DrawTextW(mem_dc, to_wide(trans).as_ptr(), -1, &mut r, DT_WORDBREAK | DT_NOPREFIX);
+        let text_top = padding - p.scroll_offset;
+        let mut r = RECT { left: text_left, top: text_top, right: text_left + cw, bottom: text_top + p.content_height };
+        let mut format = DT_WORDBREAK | DT_NOPREFIX;
+        format |= match text_align_for(trans) {
+            TextAlign::Center => DT_CENTER,
+            TextAlign::End => DT_RIGHT,
+            _ => DT_LEFT,
+        };
+        if is_rtl_text(trans) {
+            format |= DT_RTLREADING;
+        }
+        DrawTextW(mem_dc, to_wide(trans).as_ptr(), -1, &mut r, format);
         SelectObject(mem_dc, old_f);
         DeleteObject(f);
 
+        // The secondary translation, then the original once revealed
+        let below = p
+            .secondary
+            .as_deref()
+            .map(|t| (t, p.primary_height + s(BASE_SECONDARY_GAP)))
+            .into_iter()
+            .chain(p.original_top.map(|top| (p.original.as_str(), top)));
+        for (secondary, offset) in below {
+            let f = create_font(s(BASE_FONT_SECONDARY), false, secondary);
+            let old_f = SelectObject(mem_dc, f);
+            SetTextColor(mem_dc, SECONDARY_COLOR);
+            let top = text_top + offset;
+            let mut r = RECT { left: text_left, top, right: text_left + cw, bottom: text_top + p.content_height };
+            let mut format = DT_WORDBREAK | DT_NOPREFIX;
+            format |= match text_align_for(secondary) {
+                TextAlign::Center => DT_CENTER,
+                TextAlign::End => DT_RIGHT,
+                _ => DT_LEFT,
+            };
+            if is_rtl_text(secondary) {
+                format |= DT_RTLREADING;
+            }
+            DrawTextW(mem_dc, to_wide(secondary).as_ptr(), -1, &mut r, format);
+            SelectObject(mem_dc, old_f);
+            DeleteObject(f);
+        }
+
         RestoreDC(mem_dc, saved);
     }
 
+    if let Some(deadline) = p.hide_deadline.filter(|_| popup_options().countdown_bar) {
+        let left_ms = deadline.saturating_duration_since(Instant::now()).as_secs_f64() * 1000.0;
+        let remaining = (left_ms / p.hide_total_ms.max(1) as f64).clamp(0.0, 1.0);
+        let bar_w = ((w - corner_r * 2) as f64 * remaining) as i32;
+        if bar_w > 0 {
+            let bar_top = h - s(BASE_COUNTDOWN_BOTTOM) - s(BASE_COUNTDOWN_HEIGHT);
+            let bar = RECT {
+                left: corner_r,
+                top: bar_top,
+                right: corner_r + bar_w,
+                bottom: bar_top + s(BASE_COUNTDOWN_HEIGHT),
+            };
+            let brush = CreateSolidBrush(tint(COUNTDOWN_COLOR, ACCENT_BORDER_MIX));
+            FillRect(mem_dc, &bar, brush);
+            DeleteObject(brush);
+        }
+    }
+
     if has_scroll {
         let track_top = padding;
         let track_h = h - padding * 2;
-        let visible_ratio = track_h as f64 / CONTENT_HEIGHT as f64;
+        let visible_ratio = track_h as f64 / p.content_height as f64;
         let thumb_h = (visible_ratio * track_h as f64).max(20.0) as i32;
-        let max_scroll = CONTENT_HEIGHT - h;
-        let scroll_ratio = if max_scroll > 0 { SCROLL_OFFSET as f64 / max_scroll as f64 } else { 0.0 };
+        let max_scroll = p.content_height - h;
+        let scroll_ratio = if max_scroll > 0 { p.scroll_offset as f64 / max_scroll as f64 } else { 0.0 };
         let thumb_y = track_top + (scroll_ratio * (track_h - thumb_h) as f64) as i32;
 
         let bar_w = s(SCROLLBAR_WIDTH);
@@ -578,26 +1711,500 @@ unsafe fn paint(hwnd: HWND) {
     EndPaint(hwnd, &ps);
 }
 
-fn destroy_popup() {
+/// Whether a popup is on screen and not already on its way out.
+pub fn is_popup_visible() -> bool {
+    unsafe { popups().iter().any(|p| p.phase != PHASE_FADE_OUT) }
+}
+
+/// Remove every popup immediately, skipping the fade-out.
+pub fn hide_popup_now() {
+    unsafe {
+        while let Some(p) = popups().last() {
+            destroy_popup(p.hwnd);
+        }
+    }
+}
+
+/// Drop the state first so messages sent during destruction find none.
+unsafe fn destroy_popup(hwnd: HWND) {
+    popups().retain(|p| p.hwnd != hwnd);
+    KillTimer(hwnd, ANIM_TIMER);
+    KillTimer(hwnd, HIDE_TIMER);
+    DestroyWindow(hwnd);
+}
+
+struct SidebarEntry {
+    original: String,
+    translated: String,
+    translated_h: i32,
+    original_h: i32,
+}
+
+/// Append a translation to the docked sidebar (newest at top), creating the
+/// window on first use. The sidebar stays up until the app exits.
+pub fn show_in_sidebar(original: &str, translated: &str) {
+    unsafe {
+        if SIDEBAR_HWND.is_null() && !create_sidebar() {
+            return;
+        }
+
+        let mut rc: RECT = std::mem::zeroed();
+        GetClientRect(SIDEBAR_HWND, &mut rc);
+        let cw = rc.right - (s(BASE_PADDING) + 2) * 2;
+        let hdc = GetDC(ptr::null_mut());
+        let entry = SidebarEntry {
+            original: original.into(),
+            translated: translated.into(),
+            translated_h: measure_text(hdc, translated, s(BASE_FONT_TRANSLATED), true, cw),
+            original_h: measure_text(hdc, original, s(BASE_FONT_SECONDARY), false, cw),
+        };
+        ReleaseDC(ptr::null_mut(), hdc);
+
+        #[allow(clippy::deref_addrof)]
+        let entries = &mut *(&raw mut SIDEBAR_ENTRIES);
+        entries.insert(0, entry);
+        entries.truncate(SIDEBAR_MAX_ENTRIES);
+        let entries_h: i32 = entries
+            .iter()
+            .map(|e| e.translated_h + s(BASE_SECONDARY_GAP) + e.original_h)
+            .sum();
+        let gaps = s(BASE_SIDEBAR_ENTRY_GAP) * (entries.len() as i32 - 1);
+        SIDEBAR_CONTENT_HEIGHT = s(BASE_PADDING) * 2 + entries_h + gaps;
+
+        // Jump back to the top so the new entry is in view
+        SIDEBAR_SCROLL = 0;
+        InvalidateRect(SIDEBAR_HWND, ptr::null(), 0);
+    }
+}
+
+unsafe fn create_sidebar() -> bool {
+    let hwnd = CreateWindowExW(
+        card_ex_style(),
+        SIDEBAR_CLASS_NAME.get().unwrap().as_ptr(),
+        ptr::null(),
+        WS_POPUP,
+        0, 0, 0, 0,
+        ptr::null_mut(),
+        ptr::null_mut(),
+        GetModuleHandleW(ptr::null()),
+        ptr::null(),
+    );
+    if hwnd.is_null() {
+        tracing::warn!("Failed to create the sidebar window");
+        return false;
+    }
+    set_card_alpha(hwnd, MAX_ALPHA);
+    SIDEBAR_HWND = hwnd;
+    dock_sidebar(hwnd, MonitorFromPoint(POINT { x: 0, y: 0 }, MONITOR_DEFAULTTOPRIMARY));
+    ShowWindow(hwnd, SW_SHOWNOACTIVATE);
+    true
+}
+
+/// Fill the right edge of `hmon`'s work area, top to bottom.
+unsafe fn dock_sidebar(hwnd: HWND, hmon: HMONITOR) {
+    let mut mi: MONITORINFO = std::mem::zeroed();
+    mi.cbSize = std::mem::size_of::<MONITORINFO>() as u32;
+    GetMonitorInfoW(hmon, &mut mi);
+    let work = mi.rcWork;
+
+    let w = s(BASE_SIDEBAR_WIDTH);
+    let h = work.bottom - work.top - 8;
+    let x = work.right - w - 4;
+    let y = work.top + 4;
+    SetWindowPos(hwnd, HWND_TOPMOST, x, y, w, h, SWP_NOACTIVATE);
+    if !plain_renderer() {
+        let corner_r = s(BASE_CORNER_RADIUS);
+        SetWindowRgn(hwnd, CreateRoundRectRgn(0, 0, w, h, corner_r * 2, corner_r * 2), 1);
+    }
+
+    SIDEBAR_RECT.set(&RECT { left: x, top: y, right: x + w, bottom: y + h });
+
+    let max_scroll = (SIDEBAR_CONTENT_HEIGHT - h).max(0);
+    SIDEBAR_SCROLL = SIDEBAR_SCROLL.min(max_scroll);
+}
+
+/// Outline the rectangle between a selection's press and release points
+/// (`debug_overlay`), replacing the previous outline. It can't be clicked and
+/// goes away after `SELECTION_RECT_DURATION`.
+pub fn show_selection_rect(pos: SelectionPos) {
+    unsafe {
+        if !SELECTION_RECT_HWND.is_null() {
+            DestroyWindow(SELECTION_RECT_HWND);
+        }
+        let border = s(BASE_SELECTION_RECT_BORDER);
+        let left = pos.down_x.min(pos.up_x) - border;
+        let top = pos.down_y.min(pos.up_y) - border;
+        let w = (pos.down_x - pos.up_x).abs() + border * 2;
+        let h = (pos.down_y - pos.up_y).abs() + border * 2;
+
+        // Layered + transparent lets clicks through to whatever is underneath
+        let hwnd = CreateWindowExW(
+            WS_EX_TOPMOST | WS_EX_TOOLWINDOW | WS_EX_NOACTIVATE | WS_EX_LAYERED | WS_EX_TRANSPARENT,
+            SELECTION_RECT_CLASS_NAME.get().unwrap().as_ptr(),
+            ptr::null(),
+            WS_POPUP,
+            left, top, w, h,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            GetModuleHandleW(ptr::null()),
+            ptr::null(),
+        );
+        if hwnd.is_null() {
+            return;
+        }
+        SetLayeredWindowAttributes(hwnd, 0, 255, LWA_ALPHA);
+
+        // Cut out the middle so only the outline is drawn
+        let outline = CreateRectRgn(0, 0, w, h);
+        let inside = CreateRectRgn(border, border, w - border, h - border);
+        CombineRgn(outline, outline, inside, RGN_DIFF);
+        DeleteObject(inside);
+        SetWindowRgn(hwnd, outline, 0);
+
+        SELECTION_RECT_HWND = hwnd;
+        ShowWindow(hwnd, SW_SHOWNOACTIVATE);
+        SetTimer(hwnd, HIDE_TIMER, SELECTION_RECT_DURATION.as_millis() as u32, None);
+    }
+}
+
+unsafe extern "system" fn selection_rect_wnd_proc(
+    hwnd: HWND,
+    msg: u32,
+    wp: WPARAM,
+    lp: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_TIMER => {
+            DestroyWindow(hwnd);
+            0
+        }
+        WM_DESTROY => {
+            if SELECTION_RECT_HWND == hwnd {
+                SELECTION_RECT_HWND = ptr::null_mut();
+            }
+            0
+        }
+        WM_MOUSEACTIVATE => MA_NOACTIVATE as LRESULT,
+        _ => DefWindowProcW(hwnd, msg, wp, lp),
+    }
+}
+
+unsafe extern "system" fn sidebar_wnd_proc(
+    hwnd: HWND,
+    msg: u32,
+    wp: WPARAM,
+    lp: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_PAINT => {
+            paint_sidebar(hwnd);
+            0
+        }
+        WM_MOUSEACTIVATE => MA_NOACTIVATE as LRESULT,
+        WM_DISPLAYCHANGE => {
+            // Stay docked on whichever monitor is left nearest
+            dock_sidebar(hwnd, MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST));
+            InvalidateRect(hwnd, ptr::null(), 0);
+            0
+        }
+        WM_POPUP_SCROLL => {
+            let delta = wp as i16 as i32;
+            let pixels = -(delta as f64 / 120.0 * s(BASE_SCROLL_LINE) as f64) as i32;
+            let mut rc: RECT = std::mem::zeroed();
+            GetClientRect(hwnd, &mut rc);
+            let max_scroll = (SIDEBAR_CONTENT_HEIGHT - rc.bottom).max(0);
+            SIDEBAR_SCROLL = (SIDEBAR_SCROLL + pixels).clamp(0, max_scroll);
+            InvalidateRect(hwnd, ptr::null(), 0);
+            0
+        }
+        _ => DefWindowProcW(hwnd, msg, wp, lp),
+    }
+}
+
+unsafe fn paint_sidebar(hwnd: HWND) {
+    let mut ps: PAINTSTRUCT = std::mem::zeroed();
+    let hdc = BeginPaint(hwnd, &mut ps);
+
+    let mut rc = RECT { left: 0, top: 0, right: 0, bottom: 0 };
+    GetClientRect(hwnd, &mut rc);
+    let w = rc.right;
+    let h = rc.bottom;
+
+    let mem_dc = CreateCompatibleDC(hdc);
+    let mem_bmp = CreateCompatibleBitmap(hdc, w, h);
+    let old_bmp = SelectObject(mem_dc, mem_bmp);
+
+    let accent = popup_options().accent;
+    let tint = |base: u32, amount: f64| match accent {
+        Some(rgb) => blend_colorref(base, rgb, amount),
+        None => base,
+    };
+
+    let bg = CreateSolidBrush(tint(BG_COLOR, ACCENT_BG_MIX));
+    FillRect(mem_dc, &rc, bg);
+    DeleteObject(bg);
+
+    let corner_r = if plain_renderer() { 0 } else { s(BASE_CORNER_RADIUS) };
+    let saved_brush = SelectObject(mem_dc, GetStockObject(NULL_BRUSH));
+    let border = CreatePen(PS_SOLID, 1, tint(BORDER_HIGHLIGHT, ACCENT_BORDER_MIX));
+    let saved_pen = SelectObject(mem_dc, border);
+    RoundRect(mem_dc, 0, 0, w, h, corner_r * 2, corner_r * 2);
+    SelectObject(mem_dc, saved_pen);
+    SelectObject(mem_dc, saved_brush);
+    DeleteObject(border);
+
+    SetBkMode(mem_dc, TRANSPARENT as i32);
+
+    let padding = s(BASE_PADDING);
+    let text_left = padding + 2;
+    let cw = w - text_left * 2;
+    let saved = SaveDC(mem_dc);
+    IntersectClipRect(mem_dc, text_left, padding, text_left + cw, h - padding);
+
+    let separator = CreateSolidBrush(tint(BORDER_HIGHLIGHT_INNER, ACCENT_BORDER_MIX));
+
+    let entry_gap = s(BASE_SIDEBAR_ENTRY_GAP);
+    let mut y = padding - SIDEBAR_SCROLL;
+    #[allow(clippy::deref_addrof)]
+    let entries = &*(&raw const SIDEBAR_ENTRIES);
+    for (i, entry) in entries.iter().enumerate() {
+        let entry_h = entry.translated_h + s(BASE_SECONDARY_GAP) + entry.original_h;
+        if i > 0 {
+            let line_y = y - entry_gap / 2;
+            let line = RECT { left: text_left, top: line_y, right: text_left + cw, bottom: line_y + 1 };
+            FillRect(mem_dc, &line, separator);
+        }
+        // Only draw what's inside the visible band
+        if y + entry_h >= 0 && y <= h {
+            for (text, bold, color, top, height) in [
+                (&entry.translated, true, TRANSLATED_COLOR, y, entry.translated_h),
+                (&entry.original, false, SECONDARY_COLOR, y + entry.translated_h + s(BASE_SECONDARY_GAP), entry.original_h),
+            ] {
+                let size = if bold { BASE_FONT_TRANSLATED } else { BASE_FONT_SECONDARY };
+                let font = create_font(s(size), bold, text);
+                let old_f = SelectObject(mem_dc, font);
+                SetTextColor(mem_dc, color);
+                let mut r = RECT { left: text_left, top, right: text_left + cw, bottom: top + height };
+                let mut format = DT_WORDBREAK | DT_NOPREFIX;
+                format |= match text_align_for(text) {
+                    TextAlign::Center => DT_CENTER,
+                    TextAlign::End => DT_RIGHT,
+                    _ => DT_LEFT,
+                };
+                if is_rtl_text(text) {
+                    format |= DT_RTLREADING;
+                }
+                DrawTextW(mem_dc, to_wide(text).as_ptr(), -1, &mut r, format);
+                SelectObject(mem_dc, old_f);
+                DeleteObject(font);
+            }
+        }
+        y += entry_h + entry_gap;
+    }
+
+    DeleteObject(separator);
+    RestoreDC(mem_dc, saved);
+
+    if SIDEBAR_CONTENT_HEIGHT > h {
+        let track_h = h - padding * 2;
+        let thumb_h = ((track_h as f64 / SIDEBAR_CONTENT_HEIGHT as f64) * track_h as f64).max(20.0) as i32;
+        let max_scroll = SIDEBAR_CONTENT_HEIGHT - h;
+        let thumb_y = padding + (SIDEBAR_SCROLL as f64 / max_scroll as f64 * (track_h - thumb_h) as f64) as i32;
+        let bar_w = s(SCROLLBAR_WIDTH);
+        let bar_x = w - bar_w - s(3);
+
+        let brush = CreateSolidBrush(SCROLLBAR_COLOR);
+        let old_brush = SelectObject(mem_dc, brush);
+        let old_pen = SelectObject(mem_dc, GetStockObject(NULL_PEN));
+        RoundRect(mem_dc, bar_x, thumb_y, bar_x + bar_w, thumb_y + thumb_h, bar_w, bar_w);
+        SelectObject(mem_dc, old_pen);
+        SelectObject(mem_dc, old_brush);
+        DeleteObject(brush);
+    }
+
+    BitBlt(hdc, 0, 0, w, h, mem_dc, 0, 0, SRCCOPY);
+
+    SelectObject(mem_dc, old_bmp);
+    DeleteObject(mem_bmp);
+    DeleteDC(mem_dc);
+
+    EndPaint(hwnd, &ps);
+}
+
+/// Open the "Translate Text" window, or bring it back to the front.
+pub fn open_input_window() {
     unsafe {
-        if !POPUP_HWND.is_null() {
-            KillTimer(POPUP_HWND, ANIM_TIMER);
-            KillTimer(POPUP_HWND, HIDE_TIMER);
-            DestroyWindow(POPUP_HWND);
-            POPUP_HWND = ptr::null_mut();
-            TRANSLATED_TEXT = None;
-            PHASE = PHASE_NONE;
-            ANIM_START = None;
-            CLOSE_SCHEDULED = false;
-            DESELECT_CLOSE = false;
-            SCROLL_OFFSET = 0;
-            CONTENT_HEIGHT = 0;
+        if !INPUT_HWND.is_null() {
+            SetForegroundWindow(INPUT_HWND);
+            SetFocus(INPUT_EDIT);
+            return;
         }
+
+        let hi = GetModuleHandleW(ptr::null());
+        let padding = s(BASE_PADDING);
+        let client_w = s(BASE_INPUT_WIDTH);
+        let field_h = s(BASE_INPUT_FIELD_HEIGHT);
+        let result_h = s(BASE_INPUT_RESULT_HEIGHT);
+        let client_h = padding + field_h + padding / 2 + result_h + padding;
+
+        let style = WS_OVERLAPPED | WS_CAPTION | WS_SYSMENU;
+        let ex_style = WS_EX_TOPMOST | WS_EX_TOOLWINDOW;
+        let mut frame = RECT { left: 0, top: 0, right: client_w, bottom: client_h };
+        AdjustWindowRectEx(&mut frame, style, 0, ex_style);
+        let (w, h) = (frame.right - frame.left, frame.bottom - frame.top);
+
+        let hmon = MonitorFromPoint(POINT { x: 0, y: 0 }, MONITOR_DEFAULTTOPRIMARY);
+        let mut mi: MONITORINFO = std::mem::zeroed();
+        mi.cbSize = std::mem::size_of::<MONITORINFO>() as u32;
+        GetMonitorInfoW(hmon, &mut mi);
+        let work = mi.rcWork;
+        let x = work.left + (work.right - work.left - w) / 2;
+        let y = work.top + (work.bottom - work.top - h) / 3;
+
+        let hwnd = CreateWindowExW(
+            ex_style,
+            INPUT_CLASS_NAME.get().unwrap().as_ptr(),
+            to_wide(tr("input.title")).as_ptr(),
+            style,
+            x, y, w, h,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            hi,
+            ptr::null(),
+        );
+        if hwnd.is_null() {
+            tracing::warn!("Failed to create the Translate Text window");
+            return;
+        }
+        let edit = CreateWindowExW(
+            WS_EX_CLIENTEDGE,
+            to_wide("EDIT").as_ptr(),
+            ptr::null(),
+            WS_CHILD | WS_VISIBLE | ES_AUTOHSCROLL as u32,
+            padding, padding, client_w - padding * 2, field_h,
+            hwnd,
+            ptr::null_mut(),
+            hi,
+            ptr::null(),
+        );
+        let result = CreateWindowExW(
+            0,
+            to_wide("STATIC").as_ptr(),
+            ptr::null(),
+            WS_CHILD | WS_VISIBLE | SS_NOPREFIX,
+            padding, padding + field_h + padding / 2, client_w - padding * 2, result_h,
+            hwnd,
+            ptr::null_mut(),
+            hi,
+            ptr::null(),
+        );
+        INPUT_FONT = create_font(s(BASE_FONT_TRANSLATED), false, "");
+        SendMessageW(edit, WM_SETFONT, INPUT_FONT as WPARAM, 0);
+        SendMessageW(result, WM_SETFONT, INPUT_FONT as WPARAM, 0);
+
+        INPUT_HWND = hwnd;
+        INPUT_EDIT = edit;
+        INPUT_RESULT = result;
+        ShowWindow(hwnd, SW_SHOW);
+        SetForegroundWindow(hwnd);
+        SetFocus(edit);
+        cache_input_rect(hwnd);
+    }
+}
+
+/// What changed in the "Translate Text" field since the last call.
+pub fn poll_input_event() -> Option<InputEvent> {
+    let submitted = INPUT_SUBMITTED.swap(false, Ordering::Relaxed);
+    let changed = INPUT_CHANGED.swap(false, Ordering::Relaxed);
+    if !submitted && !changed {
+        return None;
+    }
+    let text = unsafe { window_text(INPUT_EDIT) };
+    Some(if submitted {
+        InputEvent::Submitted(text)
+    } else {
+        InputEvent::Changed(text)
+    })
+}
+
+/// Show `text` under the "Translate Text" field, if the window is open.
+pub fn set_input_result(text: &str) {
+    unsafe {
+        if !INPUT_RESULT.is_null() {
+            SetWindowTextW(INPUT_RESULT, to_wide(text).as_ptr());
+        }
+    }
+}
+
+/// Enter translates right away and Escape closes the window. Returns
+/// whether `msg` was handled and must not be dispatched.
+unsafe fn handle_input_key(msg: &MSG) -> bool {
+    if msg.message != WM_KEYDOWN || INPUT_EDIT.is_null() || msg.hwnd != INPUT_EDIT {
+        return false;
+    }
+    match msg.wParam as VIRTUAL_KEY {
+        VK_RETURN => INPUT_SUBMITTED.store(true, Ordering::Relaxed),
+        VK_ESCAPE => {
+            DestroyWindow(INPUT_HWND);
+        }
+        _ => return false,
+    }
+    true
+}
+
+unsafe fn window_text(hwnd: HWND) -> String {
+    if hwnd.is_null() {
+        return String::new();
+    }
+    let len = GetWindowTextLengthW(hwnd);
+    let mut buf = vec![0u16; len as usize + 1];
+    let copied = GetWindowTextW(hwnd, buf.as_mut_ptr(), buf.len() as i32);
+    String::from_utf16_lossy(&buf[..copied.max(0) as usize])
+}
+
+unsafe fn cache_input_rect(hwnd: HWND) {
+    let mut r: RECT = std::mem::zeroed();
+    GetWindowRect(hwnd, &mut r);
+    INPUT_RECT.set(&r);
+}
+
+unsafe extern "system" fn input_wnd_proc(
+    hwnd: HWND,
+    msg: u32,
+    wp: WPARAM,
+    lp: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_COMMAND => {
+            if (wp >> 16) as u32 & 0xFFFF == EN_CHANGE {
+                INPUT_CHANGED.store(true, Ordering::Relaxed);
+            }
+            0
+        }
+        // Match the result label to the window instead of dialog grey
+        WM_CTLCOLORSTATIC => {
+            SetBkColor(wp as HDC, GetSysColor(COLOR_WINDOW));
+            GetSysColorBrush(COLOR_WINDOW) as LRESULT
+        }
+        WM_MOVE => {
+            cache_input_rect(hwnd);
+            0
+        }
+        WM_DESTROY => {
+            DeleteObject(INPUT_FONT);
+            INPUT_FONT = ptr::null_mut();
+            INPUT_HWND = ptr::null_mut();
+            INPUT_EDIT = ptr::null_mut();
+            INPUT_RESULT = ptr::null_mut();
+            INPUT_RECT.clear();
+            0
+        }
+        _ => DefWindowProcW(hwnd, msg, wp, lp),
     }
 }
 
 unsafe fn measure_text(hdc: HDC, text: &str, font_size: i32, bold: bool, max_w: i32) -> i32 {
-    let font = create_font(font_size, bold);
+    let font = create_font(font_size, bold, text);
     let old = SelectObject(hdc, font);
     let wide = to_wide(text);
     let mut rc = RECT { left: 0, top: 0, right: max_w, bottom: 0 };
@@ -607,7 +2214,27 @@ unsafe fn measure_text(hdc: HDC, text: &str, font_size: i32, bold: bool, max_w:
     rc.bottom
 }
 
-unsafe fn create_font(size: i32, bold: bool) -> HFONT {
+/// Font family for `text`: the one mapped to its script in
+/// `popup_font_by_script`, else one with good glyphs for it. Segoe UI has no
+/// CJK glyphs and the fallback GDI picks can render as boxes.
+fn font_family_for(text: &str) -> String {
+    configured_font_for(text).unwrap_or_else(|| {
+        match text::dominant_script(text) {
+            Some(Script::Han) => "Microsoft YaHei UI",
+            Some(Script::Kana) => "Yu Gothic UI",
+            Some(Script::Hangul) => "Malgun Gothic",
+            _ => "Segoe UI",
+        }
+        .to_string()
+    })
+}
+
+unsafe fn create_font(size: i32, bold: bool, text: &str) -> HFONT {
+    let quality = match popup_options().text_rendering {
+        TextRendering::ClearType => CLEARTYPE_QUALITY,
+        TextRendering::Antialiased => ANTIALIASED_QUALITY,
+        TextRendering::Default => DEFAULT_QUALITY,
+    };
     CreateFontW(
         size, 0, 0, 0,
         if bold { FW_SEMIBOLD as i32 } else { FW_NORMAL as i32 },
@@ -615,9 +2242,9 @@ unsafe fn create_font(size: i32, bold: bool) -> HFONT {
         DEFAULT_CHARSET as u32,
         OUT_DEFAULT_PRECIS as u32,
         CLIP_DEFAULT_PRECIS as u32,
-        CLEARTYPE_QUALITY as u32,
+        quality as u32,
         DEFAULT_PITCH as u32,
-        to_wide("Segoe UI").as_ptr(),
+        to_wide(&font_family_for(text)).as_ptr(),
     )
 }
 
@@ -656,6 +2283,46 @@ pub fn show_info(title: &str, msg: &str) {
     }
 }
 
+/// Ask where to save a settings file, suggesting `default_name`.
+/// `None` if cancelled.
+pub fn choose_save_path(default_name: &str) -> Option<PathBuf> {
+    file_dialog(default_name, true)
+}
+
+/// Ask for a settings file to open. `None` if cancelled.
+pub fn choose_open_path() -> Option<PathBuf> {
+    file_dialog("", false)
+}
+
+fn file_dialog(default_name: &str, save: bool) -> Option<PathBuf> {
+    // Display name / pattern pairs, ending with an empty pair
+    let filter: Vec<u16> = format!("{} (*.toml)\0*.toml\0\0", tr("dialog.settings_files"))
+        .encode_utf16()
+        .collect();
+    let default_ext = to_wide("toml");
+    let mut file = [0u16; MAX_PATH as usize];
+    for (slot, unit) in file.iter_mut().zip(default_name.encode_utf16().take(MAX_PATH as usize - 1)) {
+        *slot = unit;
+    }
+    unsafe {
+        let mut ofn: OPENFILENAMEW = std::mem::zeroed();
+        ofn.lStructSize = std::mem::size_of::<OPENFILENAMEW>() as u32;
+        ofn.lpstrFilter = filter.as_ptr();
+        ofn.lpstrFile = file.as_mut_ptr();
+        ofn.nMaxFile = file.len() as u32;
+        ofn.lpstrDefExt = default_ext.as_ptr();
+        ofn.Flags = OFN_NOCHANGEDIR
+            | OFN_PATHMUSTEXIST
+            | if save { OFN_OVERWRITEPROMPT } else { OFN_FILEMUSTEXIST };
+        let chosen = if save { GetSaveFileNameW(&mut ofn) } else { GetOpenFileNameW(&mut ofn) };
+        if chosen == 0 {
+            return None;
+        }
+    }
+    let len = file.iter().position(|&c| c == 0).unwrap_or(file.len());
+    Some(PathBuf::from(String::from_utf16_lossy(&file[..len])))
+}
+
 fn to_wide(s: &str) -> Vec<u16> {
     s.encode_utf16().chain(std::iter::once(0)).collect()
 }
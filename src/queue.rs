@@ -0,0 +1,68 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+
+/// Multi-producer queue with a fixed capacity. When full, pushing drops the
+/// oldest pending item instead of blocking, so a stalled consumer can't build
+/// up an unbounded backlog of stale selections.
+pub struct BoundedQueue<T> {
+    inner: Arc<Inner<T>>,
+}
+
+struct Inner<T> {
+    name: &'static str,
+    capacity: usize,
+    items: Mutex<VecDeque<T>>,
+    available: Condvar,
+}
+
+impl<T> Clone for BoundedQueue<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> BoundedQueue<T> {
+    /// `name` identifies the queue in logs; a capacity of 0 is treated as 1.
+    pub fn new(name: &'static str, capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            inner: Arc::new(Inner {
+                name,
+                capacity,
+                items: Mutex::new(VecDeque::with_capacity(capacity)),
+                available: Condvar::new(),
+            }),
+        }
+    }
+
+    pub fn push(&self, item: T) {
+        let mut items = self.inner.items.lock().unwrap();
+        if items.len() >= self.inner.capacity {
+            items.pop_front();
+            tracing::warn!(
+                "{} queue full ({} pending), dropped the oldest request",
+                self.inner.name,
+                self.inner.capacity
+            );
+        }
+        items.push_back(item);
+        self.inner.available.notify_one();
+    }
+
+    /// Block until an item is available.
+    pub fn pop(&self) -> T {
+        let mut items = self.inner.items.lock().unwrap();
+        loop {
+            if let Some(item) = items.pop_front() {
+                return item;
+            }
+            items = self.inner.available.wait(items).unwrap();
+        }
+    }
+
+    pub fn try_pop(&self) -> Option<T> {
+        self.inner.items.lock().unwrap().pop_front()
+    }
+}
@@ -10,6 +10,11 @@ use std::os::windows::process::CommandExt;
 pub const SERVER_STARTING: u8 = 0;
 pub const SERVER_READY: u8 = 1;
 pub const SERVER_FAILED: u8 = 2;
+/// Another (non-LibreTranslate) process is answering on our port.
+pub const SERVER_PORT_CONFLICT: u8 = 3;
+
+/// Longest the LibreTranslate import check may run before it's skipped.
+const PYTHON_CHECK_TIMEOUT: Duration = Duration::from_secs(30);
 
 #[cfg(target_os = "windows")]
 const VENV_SCRIPT_DIR: &str = "Scripts";
@@ -36,24 +41,41 @@ impl LibreTranslateServer {
         Arc::clone(&self.child)
     }
 
+    /// `reserved_ports` are never taken when `preferred_port` is busy, see
+    /// `Config::reserved_ports`.
     pub fn start_background(
         python_path: Option<&str>,
         preferred_port: u16,
         load_languages: &str,
+        reserved_ports: &[u16],
     ) -> anyhow::Result<Self> {
-        Self::start_impl(python_path, preferred_port, load_languages, false)
+        Self::start_impl(python_path, preferred_port, load_languages, reserved_ports, None)
+    }
+
+    /// Like `start_background`, but blocks until the server accepts
+    /// connections, failing after `ready_timeout`.
+    pub fn start(
+        python_path: Option<&str>,
+        preferred_port: u16,
+        load_languages: &str,
+        reserved_ports: &[u16],
+        ready_timeout: Duration,
+    ) -> anyhow::Result<Self> {
+        Self::start_impl(python_path, preferred_port, load_languages, reserved_ports, Some(ready_timeout))
     }
 
     fn start_impl(
         python_path: Option<&str>,
         preferred_port: u16,
         load_languages: &str,
-        wait_ready: bool,
+        reserved_ports: &[u16],
+        wait_ready: Option<Duration>,
     ) -> anyhow::Result<Self> {
         let exe = Self::find_executable(python_path)?;
 
         // Find available port (try preferred, then next 10 ports)
         let port = (preferred_port..preferred_port + 10)
+            .filter(|p| *p == preferred_port || !reserved_ports.contains(p))
             .find(|&p| is_port_available(p))
             .ok_or_else(|| anyhow::anyhow!(
                 "No available ports in range {}-{}",
@@ -62,8 +84,10 @@ impl LibreTranslateServer {
             ))?;
 
         if port != preferred_port {
-            tracing::info!(
-                "Port {} occupied, using port {} instead",
+            // Only called when no LibreTranslate answers there, so it's someone else
+            tracing::warn!(
+                "api_port {} is used by another program, starting LibreTranslate on port {} instead. \
+                 Set api_port to a free port to avoid this.",
                 preferred_port,
                 port
             );
@@ -79,6 +103,7 @@ impl LibreTranslateServer {
             .map(|f| f.eq_ignore_ascii_case("python.exe") || f.eq_ignore_ascii_case("python") || f.eq_ignore_ascii_case("python3"))
             .unwrap_or(false);
         if is_python {
+            Self::check_python_env(&exe)?;
             if let Some(parent) = exe.parent() {
                 #[cfg(target_os = "windows")]
                 {
@@ -216,8 +241,8 @@ impl LibreTranslateServer {
             port,
         };
 
-        if wait_ready {
-            server.wait_for_ready()?;
+        if let Some(timeout) = wait_ready {
+            server.wait_for_ready(timeout)?;
         } else {
             tracing::info!("Started in background - will be ready in ~5-10 seconds");
         }
@@ -497,10 +522,85 @@ impl LibreTranslateServer {
         )
     }
 
-    fn wait_for_ready(&self) -> anyhow::Result<()> {
+    /// Import LibreTranslate with `exe` before starting the server and log
+    /// the versions found. An incompatible Python or a broken install then
+    /// fails here with its import error, instead of the server exiting right
+    /// away with nothing but a failed status to show for it.
+    fn check_python_env(exe: &std::path::Path) -> anyhow::Result<()> {
+        // Python's version first, so it's known even when the import fails
+        let script = r#"
+import sys
+print(sys.version.split()[0], flush=True)
+import importlib.metadata
+import libretranslate
+print(importlib.metadata.version("libretranslate"))
+"#;
+
+        let mut cmd = Command::new(exe);
+        cmd.args(["-c", script]).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+        if let Some(venv_root) = Self::find_venv_root(exe) {
+            cmd.env("PYTHONHOME", &venv_root);
+        }
+        if let Some(cacert) = Self::find_certifi_cacert(exe) {
+            cmd.env("SSL_CERT_FILE", &cacert);
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            const CREATE_NO_WINDOW: u32 = 0x08000000;
+            cmd.creation_flags(CREATE_NO_WINDOW);
+        }
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to run {}: {}", exe.display(), e))?;
+
+        // Importing LibreTranslate loads its web stack; a cold disk can take a while
+        let deadline = std::time::Instant::now() + PYTHON_CHECK_TIMEOUT;
+        loop {
+            match child.try_wait() {
+                Ok(Some(_)) => break,
+                Ok(None) if std::time::Instant::now() < deadline => {
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+                _ => {
+                    // Can't tell either way, so let the server start decide
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    tracing::warn!(
+                        "LibreTranslate import check didn't finish within {}s, skipping it",
+                        PYTHON_CHECK_TIMEOUT.as_secs()
+                    );
+                    return Ok(());
+                }
+            }
+        }
+
+        let output = child.wait_with_output()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut lines = stdout.lines().map(str::trim);
+        let python_version = lines.next().filter(|v| !v.is_empty()).unwrap_or("unknown");
+        if !output.status.success() {
+            // The exception itself is the last line of the traceback
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let error = stderr.lines().map(str::trim).rfind(|l| !l.is_empty()).unwrap_or("no output");
+            anyhow::bail!(
+                "LibreTranslate can't be imported by Python {} ({}): {}. \
+                 Reinstall LibreTranslate for this Python, or point python_path at a compatible one.",
+                python_version,
+                exe.display(),
+                error
+            );
+        }
+        let lt_version = lines.next().filter(|v| !v.is_empty()).unwrap_or("unknown");
+        tracing::info!("LibreTranslate {} with Python {}", lt_version, python_version);
+        Ok(())
+    }
+
+    fn wait_for_ready(&self, timeout: Duration) -> anyhow::Result<()> {
         tracing::info!("Waiting for LibreTranslate to be ready...");
 
-        for i in 0..45 {
+        for i in 0..timeout.as_secs() {
             std::thread::sleep(Duration::from_secs(1));
 
             if std::net::TcpStream::connect(format!("127.0.0.1:{}", self.port)).is_ok() {
@@ -513,7 +613,7 @@ impl LibreTranslateServer {
             }
         }
 
-        anyhow::bail!("LibreTranslate failed to start within 45s")
+        anyhow::bail!("LibreTranslate failed to start within {}s", timeout.as_secs())
     }
 }
 
@@ -756,30 +856,160 @@ fn dir_has_entries(path: &std::path::Path) -> bool {
     false
 }
 
-pub fn is_libretranslate_running(port: u16) -> bool {
-    // Don't just check if port is open - verify it's actually LibreTranslate
-    let url = format!("http://127.0.0.1:{}/languages", port);
-    match reqwest::blocking::Client::builder()
+/// Whether `url` points at this machine. Such requests must never go through
+/// a proxy: the proxy can't reach our local LibreTranslate.
+pub fn is_loopback_url(url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Port of a URL that points at this machine, explicit or the scheme default.
+pub fn loopback_port(url: &str) -> Option<u16> {
+    if !is_loopback_url(url) {
+        return None;
+    }
+    reqwest::Url::parse(url).ok()?.port_or_known_default()
+}
+
+/// Short name for a backend in the popup: "local" for this machine,
+/// otherwise the URL's host.
+pub fn backend_label(url: &str) -> String {
+    if is_loopback_url(url) {
+        return "local".into();
+    }
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| url.to_string())
+}
+
+/// Base URL of the local LibreTranslate server on `port`.
+pub fn local_base_url(port: u16) -> String {
+    format!("http://127.0.0.1:{}", port)
+}
+
+/// Server root of a LibreTranslate URL: `api_url` minus its `/translate`,
+/// so the older full-endpoint setting works wherever a base URL is needed.
+pub fn base_url(api_url: &str) -> &str {
+    let base = api_url.trim_end_matches('/');
+    base.strip_suffix("/translate").unwrap_or(base)
+}
+
+/// URL of a LibreTranslate endpoint (`"translate"`, `"detect"`,
+/// `"languages"`, `"frontend/settings"`) on the server at `base_url`.
+pub fn endpoint_url(base_url: &str, endpoint: &str) -> String {
+    format!("{}/{}", base_url.trim_end_matches('/'), endpoint)
+}
+
+/// What is listening on a local port, as seen by the `/languages` probe.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PortProbe {
+    /// Nothing accepted the request (port closed or not answering yet).
+    NoResponse,
+    LibreTranslate,
+    /// Something answered, but not like LibreTranslate's `/languages`.
+    Other,
+}
+
+pub fn probe_port(port: u16) -> PortProbe {
+    let url = endpoint_url(&local_base_url(port), "languages");
+    let client = match reqwest::blocking::Client::builder()
         .timeout(Duration::from_millis(500))
+        .no_proxy()
         .build()
     {
-        Ok(client) => match client.get(&url).send() {
-            Ok(resp) => {
-                // Check if response looks like LibreTranslate /languages endpoint
-                resp.status().is_success() && resp.text().unwrap_or_default().contains("code")
+        Ok(client) => client,
+        Err(_) => return PortProbe::NoResponse,
+    };
+    match client.get(&url).send() {
+        Ok(resp) => {
+            // Check if response looks like LibreTranslate /languages endpoint
+            if resp.status().is_success() && resp.text().unwrap_or_default().contains("code") {
+                PortProbe::LibreTranslate
+            } else {
+                PortProbe::Other
             }
-            Err(_) => false,
-        },
-        Err(_) => false,
+        }
+        Err(e) if e.is_connect() => PortProbe::NoResponse,
+        // Accepted the connection but never produced a valid HTTP reply
+        Err(e) if e.is_timeout() && !is_port_available(port) => PortProbe::Other,
+        Err(_) => PortProbe::NoResponse,
     }
 }
 
+pub fn is_libretranslate_running(port: u16) -> bool {
+    // Don't just check if port is open - verify it's actually LibreTranslate
+    probe_port(port) == PortProbe::LibreTranslate
+}
+
+/// One entry of LibreTranslate's `/languages` response.
+#[derive(serde::Deserialize)]
+pub struct ServerLanguage {
+    pub code: String,
+    #[serde(default)]
+    pub targets: Vec<String>,
+}
+
+/// Fetch the languages the translation server at `api_url` (its `/translate`
+/// endpoint) supports, with the targets each source can be translated into.
+/// Sends `api_key` and `headers` as translations do, for servers behind a
+/// gateway that wants them on every request.
+pub fn fetch_languages(
+    api_url: &str,
+    api_key: Option<&str>,
+    headers: &std::collections::BTreeMap<String, String>,
+) -> anyhow::Result<Vec<ServerLanguage>> {
+    let mut url = reqwest::Url::parse(&endpoint_url(base_url(api_url), "languages"))?;
+    if let Some(key) = api_key.filter(|k| !k.is_empty()) {
+        url.query_pairs_mut().append_pair("api_key", key);
+    }
+    let mut builder = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .default_headers(crate::translator::header_map(headers)?);
+    if is_loopback_url(api_url) {
+        builder = builder.no_proxy();
+    }
+    let client = builder.build()?;
+    // The URL may carry the key, so keep it out of errors that get logged
+    let resp = client
+        .get(url)
+        .send()
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.without_url())?;
+    Ok(resp.json().map_err(|e| e.without_url())?)
+}
+
+/// Target languages reachable from `source_lang` ("auto" = from any source).
+pub fn supported_targets(
+    languages: &[ServerLanguage],
+    source_lang: &str,
+) -> std::collections::HashSet<String> {
+    languages
+        .iter()
+        .filter(|l| source_lang == "auto" || l.code == source_lang)
+        .flat_map(|l| l.targets.iter().cloned())
+        .collect()
+}
+
 fn is_port_available(port: u16) -> bool {
     std::net::TcpStream::connect(format!("127.0.0.1:{}", port)).is_err()
 }
 
+/// Consecutive non-LibreTranslate answers before the port is reported as taken.
+const PORT_CONFLICT_PROBES: u32 = 3;
+
 /// Spawn a background thread that monitors whether LibreTranslate becomes ready.
-/// Updates the shared `status` from SERVER_STARTING → SERVER_READY or SERVER_FAILED.
+/// Updates the shared `status` from SERVER_STARTING → SERVER_READY, SERVER_FAILED,
+/// or SERVER_PORT_CONFLICT when another program is answering on `port`.
+/// Status stays SERVER_STARTING for up to `max_wait`, which is also how long
+/// failed translations are shown as "loading" rather than a crash.
 ///
 /// Uses `Child::try_wait()` for reliable crash detection on all platforms.
 /// The previous macOS implementation used `kill(pid, 0)` which returns true
@@ -789,12 +1019,13 @@ pub fn spawn_readiness_monitor(
     port: u16,
     child: Arc<Mutex<Child>>,
     status: Arc<AtomicU8>,
+    max_wait: Duration,
 ) {
     std::thread::spawn(move || {
         let pid = child.lock().unwrap().id();
-        let max_wait = Duration::from_secs(180); // first run downloads models
         let start = std::time::Instant::now();
         let mut last_log = 0u64;
+        let mut foreign_answers = 0u32;
 
         loop {
             std::thread::sleep(Duration::from_secs(2));
@@ -805,6 +1036,10 @@ pub fn spawn_readiness_monitor(
                 let mut child = child.lock().unwrap();
                 match child.try_wait() {
                     Ok(Some(exit_status)) => {
+                        if probe_port(port) == PortProbe::Other {
+                            report_port_conflict(port, &status);
+                            return;
+                        }
                         tracing::error!(
                             "LibreTranslate process (PID {}) exited with: {}. \
                              Check libretranslate.log in app data folder for details.",
@@ -830,10 +1065,20 @@ pub fn spawn_readiness_monitor(
             }
 
             // Check if the HTTP endpoint is responding
-            if is_libretranslate_running(port) {
-                tracing::info!("LibreTranslate is ready on port {}", port);
-                status.store(SERVER_READY, Ordering::Relaxed);
-                return;
+            match probe_port(port) {
+                PortProbe::LibreTranslate => {
+                    tracing::info!("LibreTranslate is ready on port {}", port);
+                    status.store(SERVER_READY, Ordering::Relaxed);
+                    return;
+                }
+                PortProbe::Other => {
+                    foreign_answers += 1;
+                    if foreign_answers >= PORT_CONFLICT_PROBES {
+                        report_port_conflict(port, &status);
+                        return;
+                    }
+                }
+                PortProbe::NoResponse => foreign_answers = 0,
             }
 
             let elapsed = start.elapsed().as_secs();
@@ -843,8 +1088,13 @@ pub fn spawn_readiness_monitor(
             }
 
             if start.elapsed() > max_wait {
+                if probe_port(port) == PortProbe::Other {
+                    report_port_conflict(port, &status);
+                    return;
+                }
                 tracing::error!(
-                    "LibreTranslate did not become ready within {}s",
+                    "LibreTranslate did not become ready within {}s \
+                     (raise server_startup_timeout_secs on slow machines)",
                     max_wait.as_secs()
                 );
                 status.store(SERVER_FAILED, Ordering::Relaxed);
@@ -853,3 +1103,33 @@ pub fn spawn_readiness_monitor(
         }
     });
 }
+
+fn report_port_conflict(port: u16, status: &AtomicU8) {
+    tracing::error!(
+        "Port {} is answering, but not as LibreTranslate. Another program is using it; \
+         set a different api_port in config.toml.",
+        port
+    );
+    status.store(SERVER_PORT_CONFLICT, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loopback_hosts() {
+        assert!(is_loopback_url("http://127.0.0.1:5000/translate"));
+        assert!(is_loopback_url("http://localhost:5000/translate"));
+        assert!(is_loopback_url("http://LOCALHOST/translate"));
+        assert!(is_loopback_url("http://[::1]:5000/translate"));
+    }
+
+    #[test]
+    fn remote_and_malformed_urls() {
+        assert!(!is_loopback_url("https://libretranslate.com/translate"));
+        assert!(!is_loopback_url("http://192.168.1.10:5000/translate"));
+        assert!(!is_loopback_url("not a url"));
+        assert!(!is_loopback_url("http://[::1/translate"));
+    }
+}
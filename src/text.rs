@@ -0,0 +1,453 @@
+/// Writing systems the app can tell apart. Coarse on purpose: enough to decide
+/// whether a selection is in a script the user already reads.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Arabic,
+    Hebrew,
+    Devanagari,
+    Thai,
+    Han,
+    /// Hiragana and Katakana.
+    Kana,
+    Hangul,
+}
+
+impl Script {
+    /// Parse a `native_scripts` entry, e.g. "latin" or "cyrillic".
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "latin" => Some(Self::Latin),
+            "greek" => Some(Self::Greek),
+            "cyrillic" => Some(Self::Cyrillic),
+            "arabic" => Some(Self::Arabic),
+            "hebrew" => Some(Self::Hebrew),
+            "devanagari" => Some(Self::Devanagari),
+            "thai" => Some(Self::Thai),
+            "han" | "cjk" => Some(Self::Han),
+            "kana" | "hiragana" | "katakana" => Some(Self::Kana),
+            "hangul" => Some(Self::Hangul),
+            _ => None,
+        }
+    }
+
+    /// Scripts a language is normally written in.
+    pub fn for_language(code: &str) -> &'static [Script] {
+        match code {
+            "el" => &[Self::Greek],
+            "ru" | "uk" => &[Self::Cyrillic],
+            "ar" | "fa" => &[Self::Arabic],
+            "he" => &[Self::Hebrew],
+            "hi" => &[Self::Devanagari],
+            "th" => &[Self::Thai],
+            "zh" | "zt" => &[Self::Han],
+            "ja" => &[Self::Han, Self::Kana],
+            "ko" => &[Self::Hangul, Self::Han],
+            _ => &[Self::Latin],
+        }
+    }
+}
+
+/// Script of a letter; `None` for digits, punctuation, symbols and whitespace.
+pub fn script_of(c: char) -> Option<Script> {
+    if !c.is_alphabetic() {
+        return None;
+    }
+    let script = match c as u32 {
+        0x0000..=0x024F | 0x1E00..=0x1EFF => Script::Latin,
+        0x0370..=0x03FF | 0x1F00..=0x1FFF => Script::Greek,
+        0x0400..=0x052F => Script::Cyrillic,
+        0x0590..=0x05FF | 0xFB1D..=0xFB4F => Script::Hebrew,
+        0x0600..=0x08FF | 0xFB50..=0xFDFF | 0xFE70..=0xFEFF => Script::Arabic,
+        0x0900..=0x097F => Script::Devanagari,
+        0x0E00..=0x0E7F => Script::Thai,
+        0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => Script::Hangul,
+        0x3040..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9F => Script::Kana,
+        0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF => Script::Han,
+        _ => return None,
+    };
+    Some(script)
+}
+
+/// The script most letters of `text` are written in. Japanese mixes Kanji
+/// into Kana, so any Kana makes it `Kana`.
+pub fn dominant_script(text: &str) -> Option<Script> {
+    let mut counts: Vec<(Script, usize)> = Vec::new();
+    for script in text.chars().filter_map(script_of) {
+        if script == Script::Kana {
+            return Some(Script::Kana);
+        }
+        match counts.iter_mut().find(|(s, _)| *s == script) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((script, 1)),
+        }
+    }
+    counts.into_iter().max_by_key(|&(_, n)| n).map(|(s, _)| s)
+}
+
+/// Whether every letter in `text` belongs to one of `scripts`. Text without
+/// any letters (numbers, symbols) counts as native.
+pub fn is_only_in_scripts(text: &str, scripts: &[Script]) -> bool {
+    text.chars()
+        .filter_map(script_of)
+        .all(|s| scripts.contains(&s))
+}
+
+/// Whether any letter in `text` belongs to one of `scripts`.
+pub fn contains_any_script(text: &str, scripts: &[Script]) -> bool {
+    text.chars()
+        .filter_map(script_of)
+        .any(|s| scripts.contains(&s))
+}
+
+/// Whether `text` is long enough to translate. Space-separated words are
+/// counted against `min_words`; Chinese and Japanese don't separate words,
+/// so text containing them is measured in characters against `min_cjk_chars`.
+pub fn meets_min_length(text: &str, min_words: usize, min_cjk_chars: usize) -> bool {
+    let cjk_chars = text
+        .chars()
+        .filter(|&c| matches!(script_of(c), Some(Script::Han | Script::Kana)))
+        .count();
+    if cjk_chars > 0 {
+        cjk_chars >= min_cjk_chars
+    } else {
+        text.split_whitespace().count() >= min_words
+    }
+}
+
+/// Whether `translated` is just `original` again, ignoring case, runs of
+/// whitespace and punctuation at either end.
+pub fn is_unchanged(original: &str, translated: &str) -> bool {
+    fn normalize(text: &str) -> String {
+        text.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim_matches(|c: char| c.is_ascii_punctuation() || c.is_whitespace())
+            .to_lowercase()
+    }
+    normalize(original) == normalize(translated)
+}
+
+/// Plain text of an HTML clipboard fragment: tags dropped, block ends turned
+/// into line breaks and common entities decoded.
+pub fn html_to_text(html: &str) -> String {
+    // CF_HTML wraps the copied markup in a header and fragment markers
+    let html = match (html.find("<!--StartFragment-->"), html.find("<!--EndFragment-->")) {
+        (Some(start), Some(end)) if start < end => &html[start + "<!--StartFragment-->".len()..end],
+        _ => html,
+    };
+
+    let mut out = String::new();
+    let mut rest = html;
+    while let Some(open) = rest.find('<') {
+        out.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = rest[open + 1..open + close].trim().to_ascii_lowercase();
+        rest = &rest[open + close + 1..];
+        let name = tag.split(|c: char| c.is_whitespace() || c == '/').find(|s| !s.is_empty()).unwrap_or("");
+        match name {
+            // Their content is never visible text
+            "script" | "style" | "head" if !tag.starts_with('/') => {
+                let end = format!("</{}", name);
+                rest = rest.to_ascii_lowercase().find(&end).map_or("", |i| &rest[i..]);
+            }
+            "br" => out.push('\n'),
+            "p" | "div" | "li" | "tr" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" if tag.starts_with('/') => {
+                out.push('\n')
+            }
+            _ => {}
+        }
+    }
+    out.push_str(rest);
+
+    let decoded = decode_entities(&out);
+    decoded
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let entity = rest.find(';').filter(|&end| end <= 10).map(|end| &rest[1..end]);
+        let decoded = entity.and_then(|e| match e {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => {
+                let code = match e.strip_prefix("#x").or_else(|| e.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => e.strip_prefix('#').and_then(|dec| dec.parse().ok()),
+                };
+                code.and_then(char::from_u32)
+            }
+        });
+        match (entity, decoded) {
+            (Some(e), Some(c)) => {
+                out.push(c);
+                rest = &rest[e.len() + 2..];
+            }
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Plain text of an RTF document. Formatting, font/color tables and other
+/// destinations are skipped; `\uN` escapes are decoded, and `\'hh` bytes are
+/// read as Latin-1, which covers what Windows-1252 RTF writers mostly emit.
+pub fn rtf_to_text(rtf: &str) -> String {
+    // Groups starting with these hold metadata, not document text
+    const SKIPPED: &[&str] = &[
+        "fonttbl", "colortbl", "stylesheet", "info", "pict", "header", "footer", "listtable",
+        "listoverridetable", "themedata", "colorschememapping", "datastore", "latentstyles",
+        "rsidtbl", "generator", "xmlnstbl",
+    ];
+
+    let chars: Vec<char> = rtf.chars().collect();
+    let mut out = String::new();
+    // (skipping, \uc value) to restore when a group closes
+    let mut saved: Vec<(bool, usize)> = Vec::new();
+    let mut skipping = false;
+    let mut uc = 1usize;
+    // Fallback characters still to drop after a \u escape
+    let mut fallback = 0usize;
+    let mut group_start = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+        match c {
+            '{' => {
+                saved.push((skipping, uc));
+                group_start = true;
+                continue;
+            }
+            '}' => {
+                (skipping, uc) = saved.pop().unwrap_or((false, 1));
+                fallback = 0;
+            }
+            '\\' => {
+                let Some(&next) = chars.get(i) else { break };
+                if next.is_ascii_alphabetic() {
+                    let start = i;
+                    while i < chars.len() && chars[i].is_ascii_alphabetic() {
+                        i += 1;
+                    }
+                    let word: String = chars[start..i].iter().collect();
+                    let num_start = i;
+                    if chars.get(i) == Some(&'-') {
+                        i += 1;
+                    }
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                    let param: Option<i32> = chars[num_start..i].iter().collect::<String>().parse().ok();
+                    if chars.get(i) == Some(&' ') {
+                        i += 1;
+                    }
+                    if group_start && SKIPPED.contains(&word.as_str()) {
+                        skipping = true;
+                    }
+                    if !skipping {
+                        match word.as_str() {
+                            "par" | "line" => out.push('\n'),
+                            "tab" => out.push('\t'),
+                            "uc" => uc = param.unwrap_or(1).max(0) as usize,
+                            "u" => {
+                                if let Some(n) = param {
+                                    let code = if n < 0 { n + 0x10000 } else { n } as u32;
+                                    out.extend(char::from_u32(code));
+                                    fallback = uc;
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                } else {
+                    i += 1;
+                    match next {
+                        // {\*\destination ...}: optional content a reader may ignore
+                        '*' if group_start => {
+                            skipping = true;
+                            continue;
+                        }
+                        '\'' => {
+                            let hex: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+                            i += hex.len();
+                            if !skipping {
+                                if fallback > 0 {
+                                    fallback -= 1;
+                                } else if let Ok(byte) = u8::from_str_radix(&hex, 16) {
+                                    out.push(byte as char);
+                                }
+                            }
+                        }
+                        '\\' | '{' | '}' if !skipping => out.push(next),
+                        '~' if !skipping => out.push('\u{A0}'),
+                        _ => {}
+                    }
+                }
+            }
+            '\r' | '\n' => continue,
+            _ if skipping => {}
+            _ if fallback > 0 => fallback -= 1,
+            _ => out.push(c),
+        }
+        group_start = false;
+    }
+    out.trim().to_string()
+}
+
+/// Split `text` after sentence-ending punctuation and line breaks. Each
+/// piece keeps the whitespace that follows it, so the pieces concatenate
+/// back to `text`.
+pub fn split_sentences(text: &str) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next_is_break = chars.peek().is_none_or(|&(_, next)| next.is_whitespace());
+        // CJK full stops need no space after them; "3.5" and "e.g.x" aren't breaks
+        let ends = matches!(c, '。' | '！' | '？' | '\n')
+            || (matches!(c, '.' | '!' | '?' | '…') && next_is_break);
+        if !ends || text[start..i].trim().is_empty() {
+            continue;
+        }
+        let mut end = i + c.len_utf8();
+        while let Some(&(j, next)) = chars.peek().filter(|(_, next)| next.is_whitespace()) {
+            end = j + next.len_utf8();
+            chars.next();
+        }
+        pieces.push(&text[start..end]);
+        start = end;
+    }
+    if start < text.len() {
+        pieces.push(&text[start..]);
+    }
+    pieces
+}
+
+/// How line breaks inside a selection are handled before translating.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum JoinLines {
+    /// Keep every line break.
+    None,
+    /// Join lines wrapped mid-sentence (as copied from PDFs), keeping breaks
+    /// after sentence punctuation and blank-line paragraph breaks.
+    Smart,
+    /// Replace every line break with a space.
+    All,
+}
+
+impl JoinLines {
+    /// Parse the `join_lines` config value ("none", "smart", "all").
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "none" => Some(Self::None),
+            "smart" => Some(Self::Smart),
+            "all" => Some(Self::All),
+            _ => None,
+        }
+    }
+}
+
+/// Split an inline target marker off the front of `text`: `">>ja hello"`
+/// gives `("ja", "hello")`. The code is 2-3 letters, optionally followed by
+/// a region or script subtag (`pt-BR`, `zh-Hant`); the rest must not be empty.
+pub fn split_lang_marker(text: &str) -> Option<(&str, &str)> {
+    let rest = text.strip_prefix(">>")?;
+    let end = rest.find(char::is_whitespace)?;
+    let (code, remainder) = rest.split_at(end);
+    let mut parts = code.split('-');
+    let primary = parts.next()?;
+    let valid_primary = (2..=3).contains(&primary.len()) && primary.chars().all(|c| c.is_ascii_alphabetic());
+    let valid_subtags = parts.all(|p| (2..=4).contains(&p.len()) && p.chars().all(|c| c.is_ascii_alphanumeric()));
+    let remainder = remainder.trim();
+    (valid_primary && valid_subtags && !remainder.is_empty()).then_some((code, remainder))
+}
+
+/// Apply `mode` to the line breaks in `text`.
+pub fn join_lines(text: &str, mode: JoinLines) -> String {
+    match mode {
+        JoinLines::None => text.to_string(),
+        JoinLines::All => {
+            let mut out = String::with_capacity(text.len());
+            for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                push_joined(&mut out, line);
+            }
+            out
+        }
+        JoinLines::Smart => {
+            // Blank lines separate paragraphs
+            let mut paragraphs = Vec::new();
+            let mut lines = Vec::new();
+            for line in text.lines().map(str::trim) {
+                if !line.is_empty() {
+                    lines.push(line);
+                } else if !lines.is_empty() {
+                    paragraphs.push(join_wrapped(&lines));
+                    lines.clear();
+                }
+            }
+            if !lines.is_empty() {
+                paragraphs.push(join_wrapped(&lines));
+            }
+            paragraphs.join("\n\n")
+        }
+    }
+}
+
+/// Join the lines of one paragraph unless the previous one ends a sentence.
+fn join_wrapped(lines: &[&str]) -> String {
+    let mut out = String::new();
+    for &line in lines {
+        if out.ends_with(|c: char| ".!?:;。！？：；…".contains(c)) {
+            out.push('\n');
+            out.push_str(line);
+        } else {
+            push_joined(&mut out, line);
+        }
+    }
+    out
+}
+
+/// Append `line` to `out` as a continuation of the same sentence.
+pub(crate) fn push_joined(out: &mut String, line: &str) {
+    let Some(last) = out.chars().last() else {
+        out.push_str(line);
+        return;
+    };
+    let first = line.chars().next().unwrap_or(' ');
+    // "trans-" + "lation": a word hyphenated at the line end
+    if last == '-' && first.is_lowercase() && out.chars().rev().nth(1).is_some_and(char::is_alphabetic) {
+        out.pop();
+    } else if !(is_unspaced(last) && is_unspaced(first)) {
+        // Chinese and Japanese don't put spaces between words
+        out.push(' ');
+    }
+    out.push_str(line);
+}
+
+fn is_unspaced(c: char) -> bool {
+    matches!(script_of(c), Some(Script::Han | Script::Kana)) || ('\u{3000}'..='\u{303F}').contains(&c)
+}
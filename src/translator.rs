@@ -1,4 +1,6 @@
-use std::sync::{Arc, RwLock};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -16,6 +18,13 @@ struct TranslateRequest<'a> {
 #[serde(rename_all = "camelCase")]
 struct TranslateResponse {
     translated_text: String,
+    /// Only present when the request used `source: "auto"`.
+    detected_language: Option<DetectedLanguage>,
+}
+
+#[derive(Deserialize)]
+struct DetectedLanguage {
+    language: String,
 }
 
 #[derive(Deserialize)]
@@ -23,12 +32,293 @@ struct ErrorResponse {
     error: String,
 }
 
+/// The server answered successfully but with no text, as some broken
+/// language models do. Reported as an error instead of a blank popup.
+#[derive(Debug)]
+pub struct EmptyTranslation;
+
+impl std::fmt::Display for EmptyTranslation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("LibreTranslate returned an empty translation")
+    }
+}
+
+impl std::error::Error for EmptyTranslation {}
+
+/// The server answered HTTP 429, as shared public instances do under load.
+#[derive(Debug)]
+pub struct RateLimited {
+    /// Seconds from `Retry-After`, when the server sent it as a number.
+    pub retry_after_secs: Option<u64>,
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.retry_after_secs {
+            Some(secs) => write!(f, "LibreTranslate rate limit hit, retry after {}s", secs),
+            None => f.write_str("LibreTranslate rate limit hit"),
+        }
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// Longest wait before a retry; a server asking for more is given up on.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Timeout and retries for one backend's requests.
+#[derive(Clone, Copy, Debug)]
+pub struct RequestPolicy {
+    pub timeout: Duration,
+    /// Retries after the first attempt, for timeouts, refused connections
+    /// and rate limits only.
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for each one after.
+    pub backoff: Duration,
+}
+
+impl Default for RequestPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            max_retries: 0,
+            backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+impl RequestPolicy {
+    /// The top-level settings, used for `api_url`.
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        Self {
+            timeout: Duration::from_secs(config.request_timeout_secs.max(1)),
+            max_retries: config.max_retries,
+            backoff: Duration::from_millis(config.retry_backoff_ms),
+        }
+    }
+
+    /// `backend`'s own settings, falling back to these for the ones it leaves unset.
+    pub fn for_backend(&self, backend: &crate::config::CompareBackend) -> Self {
+        Self {
+            timeout: backend
+                .request_timeout_secs
+                .map_or(self.timeout, |secs| Duration::from_secs(secs.max(1))),
+            max_retries: backend.max_retries.unwrap_or(self.max_retries),
+            backoff: backend.retry_backoff_ms.map_or(self.backoff, Duration::from_millis),
+        }
+    }
+
+    /// How long to wait before retry number `attempt` (0-based) after `error`,
+    /// or `None` if it shouldn't be retried.
+    fn retry_delay(&self, error: &anyhow::Error, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max_retries {
+            return None;
+        }
+        let backoff = self.backoff.saturating_mul(2u32.saturating_pow(attempt));
+        let delay = if let Some(limited) = error.downcast_ref::<RateLimited>() {
+            limited.retry_after_secs.map_or(backoff, Duration::from_secs)
+        } else if error
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_timeout() || e.is_connect())
+        {
+            backoff
+        } else {
+            return None;
+        };
+        (delay <= MAX_RETRY_DELAY).then_some(delay)
+    }
+}
+
+/// Source language to assume for short selections when `source_lang` is "auto".
+/// Detection is unreliable on a handful of characters, especially CJK.
+#[derive(Clone)]
+pub struct SourceHint {
+    pub lang: String,
+    pub max_chars: usize,
+}
+
+impl SourceHint {
+    /// The hint configured by `source_lang_hint`, if any.
+    pub fn from_config(config: &crate::config::Config) -> Option<Self> {
+        config
+            .source_lang_hint
+            .as_deref()
+            .map(str::trim)
+            .filter(|lang| !lang.is_empty())
+            .map(|lang| Self {
+                lang: lang.to_string(),
+                max_chars: config.source_hint_max_chars,
+            })
+    }
+
+    fn applies_to(&self, text: &str) -> bool {
+        if text.chars().count() > self.max_chars {
+            return false;
+        }
+        // CJK hints only make sense when the text is actually in that script,
+        // so a short English word isn't forced through as Japanese.
+        let in_script = |range: &[(u32, u32)]| {
+            text.chars()
+                .any(|c| range.iter().any(|&(lo, hi)| (lo..=hi).contains(&(c as u32))))
+        };
+        match self.lang.as_str() {
+            "zh" | "zt" => in_script(&[(0x4E00, 0x9FFF), (0x3400, 0x4DBF)]),
+            "ja" => in_script(&[(0x3040, 0x30FF), (0x4E00, 0x9FFF)]),
+            "ko" => in_script(&[(0xAC00, 0xD7AF), (0x1100, 0x11FF)]),
+            _ => true,
+        }
+    }
+}
+
+/// Languages auto-detection may report. Anything else is treated as a
+/// misdetection and the text is translated again from `fallback`.
+#[derive(Clone)]
+pub struct DetectFilter {
+    pub allowlist: Vec<String>,
+    pub fallback: String,
+}
+
+impl DetectFilter {
+    /// The filter configured by `detect_allowlist`; `None` when it's empty.
+    pub fn from_config(config: &crate::config::Config) -> Option<Self> {
+        let allowlist: Vec<String> = config
+            .detect_allowlist
+            .split(',')
+            .map(|s| s.trim().to_lowercase())
+            .filter(|s| !s.is_empty())
+            .collect();
+        (!allowlist.is_empty()).then(|| Self {
+            allowlist,
+            fallback: config.detect_fallback_lang.trim().to_string(),
+        })
+    }
+
+    fn allows(&self, lang: &str) -> bool {
+        self.allowlist.iter().any(|l| l.eq_ignore_ascii_case(lang))
+    }
+}
+
+/// Parse `source_target_rules` ("ja:id,es:en") into source → target pairs,
+/// warning about and skipping malformed entries.
+pub fn parse_target_rules(rules: &str) -> HashMap<String, String> {
+    rules
+        .split(',')
+        .filter(|rule| !rule.trim().is_empty())
+        .filter_map(|rule| match rule.split_once(':') {
+            Some((source, target)) if !source.trim().is_empty() && !target.trim().is_empty() => {
+                Some((source.trim().to_lowercase(), target.trim().to_lowercase()))
+            }
+            _ => {
+                tracing::warn!("Invalid rule \"{}\" in source_target_rules, ignoring", rule.trim());
+                None
+            }
+        })
+        .collect()
+}
+
+/// Sentences kept by `Translator::with_sentence_cache`.
+const SENTENCE_CACHE_CAPACITY: usize = 256;
+
+/// (source, target, sentence) of a cached translation.
+type SentenceKey = (String, String, String);
+
+/// Recently translated sentences, oldest evicted first.
+#[derive(Default)]
+struct SentenceCache {
+    /// Translation and the source language it was detected as.
+    entries: HashMap<SentenceKey, (String, String)>,
+    order: VecDeque<SentenceKey>,
+}
+
+impl SentenceCache {
+    fn insert(&mut self, key: SentenceKey, value: (String, String)) {
+        if self.entries.insert(key.clone(), value).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > SENTENCE_CACHE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// A finished translation and the language pair it was made with.
+pub struct Translation {
+    pub text: String,
+    /// The detected language when the request used "auto" (if the server reports it).
+    pub source: String,
+    pub target: String,
+}
+
 pub struct Translator {
     client: reqwest::Client,
     api_url: String,
     api_key: Option<String>,
     source_lang: String,
+    source_hint: Option<SourceHint>,
+    detect_filter: Option<DetectFilter>,
+    /// Source language → target to use instead of `target_lang`.
+    target_rules: HashMap<String, String>,
     target_lang: Arc<RwLock<String>>,
+    sentence_cache: Option<Mutex<SentenceCache>>,
+    policy: RequestPolicy,
+}
+
+/// The `headers` config table as request headers. Fails on a header name or
+/// value HTTP doesn't allow.
+pub fn header_map(headers: &BTreeMap<String, String>) -> Result<reqwest::header::HeaderMap> {
+    let mut map = reqwest::header::HeaderMap::new();
+    for (name, value) in headers {
+        let header_name = reqwest::header::HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|_| anyhow::anyhow!("Invalid header name \"{}\"", name))?;
+        let mut header_value = reqwest::header::HeaderValue::from_str(value)
+            .map_err(|_| anyhow::anyhow!("Invalid value for header \"{}\"", name))?;
+        // Keeps it out of reqwest's Debug output; values are often secrets
+        header_value.set_sensitive(true);
+        map.insert(header_name, header_value);
+    }
+    Ok(map)
+}
+
+/// Proxy variables reqwest reads; it silently skips a value it can't parse.
+const PROXY_ENV_VARS: [&str; 6] = [
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+];
+
+/// Reject a malformed proxy URL instead of quietly connecting without it.
+fn check_proxy_env() -> Result<()> {
+    for var in PROXY_ENV_VARS {
+        let Ok(value) = std::env::var(var) else {
+            continue;
+        };
+        if value.trim().is_empty() {
+            continue;
+        }
+        reqwest::Proxy::all(value.trim())
+            .map_err(|e| anyhow::anyhow!("Invalid proxy in {}: {}", var, e))?;
+    }
+    Ok(())
+}
+
+/// HTTP client for `api_url`, sending `headers` with every request.
+fn build_client(api_url: &str, headers: reqwest::header::HeaderMap) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder().default_headers(headers);
+    // A system proxy can't reach the bundled local server
+    if crate::server::is_loopback_url(api_url) {
+        builder = builder.no_proxy();
+    } else {
+        check_proxy_env()?;
+    }
+    // Fails on e.g. an unusable TLS setup
+    builder
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create HTTP client: {}", e))
 }
 
 impl Translator {
@@ -36,32 +326,221 @@ impl Translator {
         api_url: String,
         api_key: Option<String>,
         source_lang: String,
+        source_hint: Option<SourceHint>,
+        detect_filter: Option<DetectFilter>,
+        target_rules: HashMap<String, String>,
         target_lang: Arc<RwLock<String>>,
-    ) -> Self {
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(10))
-            .build()
-            .expect("Failed to create HTTP client");
+    ) -> Result<Self> {
+        let client = build_client(&api_url, reqwest::header::HeaderMap::new())?;
 
-        Self {
+        Ok(Self {
             client,
             api_url,
             api_key,
             source_lang,
+            source_hint,
+            detect_filter,
+            target_rules,
             target_lang,
+            sentence_cache: None,
+            policy: RequestPolicy::default(),
+        })
+    }
+
+    /// Send `headers` (the `headers` config table) with every request.
+    /// Fails on a header name or value HTTP doesn't allow.
+    pub fn with_headers(mut self, headers: &BTreeMap<String, String>) -> Result<Self> {
+        if headers.is_empty() {
+            return Ok(self);
+        }
+        self.client = build_client(&self.api_url, header_map(headers)?)?;
+        Ok(self)
+    }
+
+    /// Use `policy` instead of the default 10 s timeout without retries.
+    pub fn with_request_policy(mut self, policy: RequestPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Translate sentence by sentence, reusing earlier translations of the
+    /// same sentences. A selection grown by a sentence or a word then only
+    /// requests the sentences that are new or changed.
+    pub fn with_sentence_cache(mut self) -> Self {
+        self.sentence_cache = Some(Mutex::new(SentenceCache::default()));
+        self
+    }
+
+    fn source_for(&self, text: &str) -> &str {
+        if self.source_lang == "auto" {
+            if let Some(hint) = self.source_hint.as_ref().filter(|h| h.applies_to(text)) {
+                return &hint.lang;
+            }
+        }
+        &self.source_lang
+    }
+
+    pub async fn translate(&self, text: &str) -> Result<Translation> {
+        let source = self.source_for(text);
+        let target = match self.target_rules.get(source) {
+            Some(rule) => rule.clone(),
+            None => self.target_lang.read().unwrap().clone(),
+        };
+        let translation = self.translate_with(text, source, &target).await?;
+
+        // A rule for the detected language only applies once detection has run
+        if let Some(rule) = self
+            .target_rules
+            .get(&translation.source)
+            .filter(|rule| **rule != target && **rule != translation.source)
+        {
+            tracing::info!("Source \"{}\" has a target rule, translating to \"{}\"", translation.source, rule);
+            return self.translate_with(text, &translation.source, rule).await;
+        }
+        Ok(translation)
+    }
+
+    /// Translate into `target` instead of the configured target language.
+    pub async fn translate_to(&self, text: &str, target: &str) -> Result<Translation> {
+        self.translate_with(text, self.source_for(text), target).await
+    }
+
+    /// Translate with an explicit language pair instead of the configured one.
+    pub async fn translate_with(&self, text: &str, source: &str, target: &str) -> Result<Translation> {
+        match &self.sentence_cache {
+            Some(cache) => self.translate_sentences(cache, text, source, target).await,
+            None => self.translate_whole(text, source, target).await,
+        }
+    }
+
+    /// Translate each sentence on its own, taking the ones seen before from
+    /// the cache. LibreTranslate splits text into sentences internally as
+    /// well, so this doesn't lose context it would have used.
+    async fn translate_sentences(
+        &self,
+        cache: &Mutex<SentenceCache>,
+        text: &str,
+        source: &str,
+        target: &str,
+    ) -> Result<Translation> {
+        let sentences = crate::text::split_sentences(text);
+        let mut out = String::new();
+        let mut detected: Option<String> = None;
+        let mut reused = 0;
+
+        for sentence in &sentences {
+            let trimmed = sentence.trim();
+            if !trimmed.is_empty() {
+                let key = (source.to_string(), target.to_string(), trimmed.to_string());
+                let cached = cache.lock().unwrap().entries.get(&key).cloned();
+                let (translated, sentence_source) = match cached {
+                    Some(hit) => {
+                        reused += 1;
+                        hit
+                    }
+                    None => {
+                        let t = self.translate_whole(trimmed, source, target).await?;
+                        cache.lock().unwrap().insert(key, (t.text.clone(), t.source.clone()));
+                        (t.text, t.source)
+                    }
+                };
+                detected.get_or_insert(sentence_source);
+                if out.ends_with(char::is_whitespace) {
+                    out.push_str(&translated);
+                } else {
+                    // "。" needs no space after it in the source, but may in the target
+                    crate::text::push_joined(&mut out, &translated);
+                }
+            }
+            // Keep the original spacing and line breaks between sentences
+            out.push_str(&sentence[sentence.trim_end().len()..]);
+        }
+
+        if reused > 0 {
+            tracing::info!("Reused {} of {} sentences from cache", reused, sentences.len());
+        }
+        Ok(Translation {
+            text: out.trim_end().to_string(),
+            source: detected.unwrap_or_else(|| source.to_string()),
+            target: target.to_string(),
+        })
+    }
+
+    async fn translate_whole(&self, text: &str, source: &str, target: &str) -> Result<Translation> {
+        let result = self.request(text, source, target).await?;
+        let mut source = source.to_string();
+
+        if let Some(detected) = result.detected_language.filter(|_| source == "auto") {
+            if let Some(filter) = self.detect_filter.as_ref().filter(|f| !f.allows(&detected.language)) {
+                tracing::info!(
+                    "Detected \"{}\" is not in detect_allowlist, translating from \"{}\"",
+                    detected.language,
+                    filter.fallback
+                );
+                let retry = self.request(text, &filter.fallback, target).await?;
+                return Ok(Translation {
+                    text: retry.translated_text,
+                    source: filter.fallback.clone(),
+                    target: target.to_string(),
+                });
+            }
+            source = detected.language;
+        }
+        Ok(Translation {
+            text: result.translated_text,
+            source,
+            target: target.to_string(),
+        })
+    }
+
+    async fn request(&self, text: &str, source: &str, target: &str) -> Result<TranslateResponse> {
+        let mut attempt = 0;
+        loop {
+            match self.request_once(text, source, target).await {
+                Err(e) => {
+                    let Some(delay) = self.policy.retry_delay(&e, attempt) else {
+                        return Err(e);
+                    };
+                    attempt += 1;
+                    tracing::warn!(
+                        "Request to {} failed ({}), retry {}/{} in {}ms",
+                        self.api_url,
+                        e,
+                        attempt,
+                        self.policy.max_retries,
+                        delay.as_millis()
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
         }
     }
 
-    pub async fn translate(&self, text: &str) -> Result<String> {
-        let target = self.target_lang.read().unwrap().clone();
+    async fn request_once(&self, text: &str, source: &str, target: &str) -> Result<TranslateResponse> {
         let body = TranslateRequest {
             q: text,
-            source: &self.source_lang,
-            target: &target,
+            source,
+            target,
             api_key: self.api_key.as_deref(),
         };
 
-        let resp = self.client.post(&self.api_url).json(&body).send().await?;
+        let resp = self
+            .client
+            .post(&self.api_url)
+            .timeout(self.policy.timeout)
+            .json(&body)
+            .send()
+            .await?;
+
+        if resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after_secs = resp
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok());
+            return Err(RateLimited { retry_after_secs }.into());
+        }
 
         if !resp.status().is_success() {
             let status = resp.status();
@@ -73,6 +552,125 @@ impl Translator {
         }
 
         let result: TranslateResponse = resp.json().await?;
-        Ok(result.translated_text)
+        if result.translated_text.trim().is_empty() {
+            return Err(EmptyTranslation.into());
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn malformed_proxy_fails_client_build() {
+        std::env::set_var("HTTPS_PROXY", "http://[bad");
+        let result = Translator::new(
+            "https://translate.example.com/translate".to_string(),
+            None,
+            "auto".to_string(),
+            None,
+            None,
+            HashMap::new(),
+            Arc::new(RwLock::new("en".to_string())),
+        );
+        std::env::remove_var("HTTPS_PROXY");
+
+        let err = result.err().expect("client build should fail");
+        assert!(err.to_string().contains("HTTPS_PROXY"), "{}", err);
+    }
+
+    /// Serve one request on a loopback port, answering 200 with `body`.
+    fn serve_once(body: &'static str) -> String {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            // Read the whole request so closing doesn't reset the connection
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            loop {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let headers = text[..end].to_ascii_lowercase();
+                    let length = headers
+                        .lines()
+                        .find_map(|l| l.strip_prefix("content-length:"))
+                        .and_then(|v| v.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if request.len() >= end + 4 + length {
+                        break;
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        format!("http://{}/translate", addr)
+    }
+
+    #[test]
+    fn loopback_requests_skip_the_proxy() {
+        let api_url = serve_once(r#"{"translatedText":"halo"}"#);
+        // Nothing listens on the discard port, so a proxied request fails
+        std::env::set_var("HTTP_PROXY", "http://127.0.0.1:9");
+        let translator = Translator::new(
+            api_url,
+            None,
+            "en".to_string(),
+            None,
+            None,
+            HashMap::new(),
+            Arc::new(RwLock::new("id".to_string())),
+        );
+        std::env::remove_var("HTTP_PROXY");
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let outcome = runtime.block_on(translator.unwrap().translate("hello"));
+
+        match outcome {
+            Ok(translation) => assert_eq!(translation.text, "halo"),
+            Err(e) => panic!("loopback request went through the proxy: {:#}", e),
+        }
+    }
+
+    #[test]
+    fn blank_translation_is_empty_translation_error() {
+        let api_url = serve_once(r#"{"translatedText":"  "}"#);
+        let translator = Translator::new(
+            api_url,
+            None,
+            "en".to_string(),
+            None,
+            None,
+            HashMap::new(),
+            Arc::new(RwLock::new("id".to_string())),
+        )
+        .unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let Err(err) = runtime.block_on(translator.translate("hello")) else {
+            panic!("blank translation should be an error");
+        };
+
+        assert!(err.downcast_ref::<EmptyTranslation>().is_some(), "{:#}", err);
     }
 }
@@ -2,6 +2,8 @@ use anyhow::Result;
 use tray_icon::menu::{CheckMenuItem, Menu, MenuEvent, MenuId, MenuItem, PredefinedMenuItem, Submenu};
 use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
 
+use crate::i18n::{tr, tr_args};
+
 /// Public accessor for language display names (used by main.rs for notifications).
 pub fn lang_display_name_pub(code: &str) -> &str {
     lang_display_name(code)
@@ -46,11 +48,18 @@ fn lang_display_name(code: &str) -> &str {
 }
 
 pub struct Tray {
-    _tray: TrayIcon,
+    tray: TrayIcon,
     pub monitor_item: CheckMenuItem,
     quit_id: MenuId,
     lang_items: Vec<(CheckMenuItem, String)>,
     update_item: MenuItem,
+    test_item: MenuItem,
+    settings_item: MenuItem,
+    export_item: MenuItem,
+    import_item: MenuItem,
+    verbose_item: CheckMenuItem,
+    reload_item: MenuItem,
+    input_item: MenuItem,
 }
 
 impl Tray {
@@ -58,12 +67,13 @@ impl Tray {
         languages: &[String],
         current_lang: &str,
         installed_languages: &std::collections::HashSet<String>,
+        monitoring: bool,
     ) -> Result<Self> {
         let menu = Menu::new();
-        let monitor_item = CheckMenuItem::new("Monitoring Active", true, true, None);
+        let monitor_item = CheckMenuItem::new(tr("tray.monitoring"), true, monitoring, None);
         menu.append(&monitor_item)?;
 
-        let lang_submenu = Submenu::new("Target Language", true);
+        let lang_submenu = Submenu::new(tr("tray.target_language"), true);
         let mut lang_items = Vec::new();
         for code in languages {
             let name = lang_display_name(code);
@@ -79,12 +89,33 @@ impl Tray {
         }
         menu.append(&lang_submenu)?;
 
-        let update_item = MenuItem::new("Check for Updates", true, None);
+        let input_item = MenuItem::new(tr("tray.translate_text"), true, None);
+        menu.append(&input_item)?;
+
+        let test_item = MenuItem::new(tr("tray.test"), true, None);
+        menu.append(&test_item)?;
+
+        let settings_item = MenuItem::new(tr("tray.settings"), true, None);
+        menu.append(&settings_item)?;
+
+        let export_item = MenuItem::new(tr("tray.export_settings"), true, None);
+        menu.append(&export_item)?;
+
+        let import_item = MenuItem::new(tr("tray.import_settings"), true, None);
+        menu.append(&import_item)?;
+
+        let reload_item = MenuItem::new(tr("tray.reload_config"), true, None);
+        menu.append(&reload_item)?;
+
+        let verbose_item = CheckMenuItem::new(tr("tray.verbose_logging"), true, false, None);
+        menu.append(&verbose_item)?;
+
+        let update_item = MenuItem::new(tr("tray.check_updates"), true, None);
         menu.append(&update_item)?;
 
         menu.append(&PredefinedMenuItem::separator())?;
 
-        let quit_item = MenuItem::new("Quit", true, None);
+        let quit_item = MenuItem::new(tr("tray.quit"), true, None);
         let quit_id = quit_item.id().clone();
         menu.append(&quit_item)?;
 
@@ -94,14 +125,23 @@ impl Tray {
             .with_tooltip("Screen Translate")
             .with_menu(Box::new(menu))
             .with_icon(icon)
+            // Template images are tinted by macOS to match light/dark menu bars
+            .with_icon_as_template(cfg!(target_os = "macos"))
             .build()?;
 
         Ok(Self {
-            _tray: tray,
+            tray,
             monitor_item,
             quit_id,
             lang_items,
             update_item,
+            test_item,
+            settings_item,
+            export_item,
+            import_item,
+            verbose_item,
+            reload_item,
+            input_item,
         })
     }
 
@@ -116,6 +156,34 @@ impl Tray {
         }
     }
 
+    /// Check the item for `code`, e.g. after the target changed in the config.
+    pub fn set_target_language(&self, code: &str) {
+        for (item, item_code) in &self.lang_items {
+            item.set_checked(item_code == code);
+        }
+    }
+
+    /// Reflect what the translation server can actually produce. Unsupported
+    /// languages stay selectable when they can be downloaded locally (they
+    /// already carry the download marker); otherwise they are greyed out.
+    pub fn set_supported_languages(
+        &self,
+        supported: &std::collections::HashSet<String>,
+        downloadable: bool,
+    ) {
+        for (item, code) in &self.lang_items {
+            if supported.contains(code) || downloadable {
+                item.set_enabled(true);
+            } else {
+                item.set_text(tr_args(
+                    "tray.not_on_server",
+                    &[("language", &lang_display_name(code)), ("code", code)],
+                ));
+                item.set_enabled(false);
+            }
+        }
+    }
+
     pub fn handle_menu_event(&self) -> TrayAction {
         if let Ok(event) = MenuEvent::receiver().try_recv() {
             if *event.id() == self.quit_id {
@@ -126,6 +194,34 @@ impl Tray {
                 return TrayAction::CheckForUpdates;
             }
 
+            if *event.id() == *self.test_item.id() {
+                return TrayAction::TestTranslation;
+            }
+
+            if *event.id() == *self.settings_item.id() {
+                return TrayAction::ShowSettings;
+            }
+
+            if *event.id() == *self.export_item.id() {
+                return TrayAction::ExportSettings;
+            }
+
+            if *event.id() == *self.import_item.id() {
+                return TrayAction::ImportSettings;
+            }
+
+            if *event.id() == *self.reload_item.id() {
+                return TrayAction::ReloadConfig;
+            }
+
+            if *event.id() == *self.verbose_item.id() {
+                return TrayAction::ToggleVerboseLogging(self.verbose_item.is_checked());
+            }
+
+            if *event.id() == *self.input_item.id() {
+                return TrayAction::TranslateText;
+            }
+
             for (item, code) in &self.lang_items {
                 if *event.id() == *item.id() {
                     // Uncheck all, check the selected one
@@ -142,8 +238,13 @@ impl Tray {
         TrayAction::None
     }
 
+    /// Replace the hover text, e.g. to show server startup progress.
+    pub fn set_tooltip(&self, text: &str) {
+        let _ = self.tray.set_tooltip(Some(text));
+    }
+
     pub fn set_update_in_progress(&self) {
-        self.update_item.set_text("Updating...");
+        self.update_item.set_text(tr("tray.updating"));
         self.update_item.set_enabled(false);
     }
 }
@@ -153,6 +254,21 @@ fn load_default_icon() -> Result<Icon> {
     let img = image::load_from_memory(png_bytes)?
         .resize(32, 32, image::imageops::FilterType::Lanczos3)
         .to_rgba8();
+
+    // macOS: turn the logo into a template image. Only the alpha channel is
+    // used, so flatten the color to black and keep the shape's coverage.
+    // Windows keeps the colored icon.
+    #[cfg(target_os = "macos")]
+    let img = {
+        let mut img = img;
+        for pixel in img.pixels_mut() {
+            pixel.0[0] = 0;
+            pixel.0[1] = 0;
+            pixel.0[2] = 0;
+        }
+        img
+    };
+
     let (w, h) = img.dimensions();
     Ok(Icon::from_rgba(img.into_raw(), w, h)?)
 }
@@ -163,4 +279,12 @@ pub enum TrayAction {
     ToggleMonitoring(bool),
     ChangeLanguage(String),
     CheckForUpdates,
+    TestTranslation,
+    ShowSettings,
+    ExportSettings,
+    ImportSettings,
+    ReloadConfig,
+    /// DEBUG logging on (true) or back to INFO.
+    ToggleVerboseLogging(bool),
+    TranslateText,
 }
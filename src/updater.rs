@@ -1,5 +1,5 @@
 use anyhow::{bail, Context, Result};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 const GITHUB_API_URL: &str =
@@ -10,9 +10,67 @@ pub struct UpdateInfo {
     pub version: String,
     pub download_url: String,
     pub asset_name: String,
+    /// Architecture the update was picked for, as named in release assets ("arm64", "x64").
+    pub arch: &'static str,
     strategy: UpdateStrategy,
 }
 
+/// GitHub refused the release lookup because the API rate limit is exhausted.
+#[derive(Debug)]
+pub struct RateLimited {
+    /// Unix timestamp (seconds) from `X-RateLimit-Reset`, when known.
+    pub reset_at: Option<u64>,
+}
+
+impl RateLimited {
+    /// Human-readable retry time, e.g. "14:05 UTC (in about 12 min)".
+    pub fn retry_after(&self) -> String {
+        let Some(reset) = self.reset_at else {
+            return "a while".into();
+        };
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let mins = reset.saturating_sub(now).div_ceil(60);
+        let day_secs = reset % 86_400;
+        format!(
+            "{:02}:{:02} UTC (in about {} min)",
+            day_secs / 3600,
+            (day_secs % 3600) / 60,
+            mins
+        )
+    }
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GitHub API rate limit exceeded, resets at {}", self.retry_after())
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// The process to start once the app has shut down, finishing an update.
+/// Started only after teardown so the new instance doesn't race the old one
+/// for the server port and the installer doesn't find the exe still running.
+pub struct Relaunch {
+    command: Command,
+}
+
+impl Relaunch {
+    fn new(program: impl AsRef<std::ffi::OsStr>) -> Self {
+        Self {
+            command: Command::new(program),
+        }
+    }
+
+    pub fn spawn(mut self) -> Result<()> {
+        self.command.spawn().context("Failed to relaunch after update")?;
+        Ok(())
+    }
+}
+
 #[derive(Clone)]
 enum UpdateStrategy {
     InstallerSilent, // Windows installed via Inno Setup
@@ -21,6 +79,65 @@ enum UpdateStrategy {
     BinarySwap,      // macOS standalone or Windows standalone
 }
 
+/// What an update downloads. Release asset names have drifted between
+/// versions, so each kind is matched loosely when the expected name is absent.
+#[derive(Clone, Copy)]
+enum AssetKind {
+    /// `ScreenTranslate-<version>-setup.exe`
+    Installer,
+    /// `ScreenTranslate-<version>.dmg`
+    #[allow(dead_code)] // Only constructed on macOS
+    Dmg,
+    /// `screen-translate-<os>-<arch>.zip`
+    BinaryZip,
+}
+
+impl AssetKind {
+    fn describe(self) -> &'static str {
+        match self {
+            AssetKind::Installer => "installer",
+            AssetKind::Dmg => "DMG",
+            AssetKind::BinaryZip => "binary zip",
+        }
+    }
+}
+
+/// Name tokens meaning this OS, and ones meaning another OS.
+fn os_tokens() -> (&'static [&'static str], &'static [&'static str]) {
+    const WINDOWS: &[&str] = &["windows", "win", "win64", "win32"];
+    const MACOS: &[&str] = &["macos", "mac", "darwin", "osx", "apple"];
+    if cfg!(target_os = "windows") {
+        (WINDOWS, &["macos", "mac", "darwin", "osx", "apple", "linux"])
+    } else {
+        (MACOS, &["windows", "win", "win64", "win32", "linux"])
+    }
+}
+
+/// The running architecture as release assets name it.
+fn arch_label() -> &'static str {
+    match std::env::consts::ARCH {
+        "aarch64" => "arm64",
+        "x86_64" => "x64",
+        other => other,
+    }
+}
+
+/// Name tokens meaning this CPU architecture, and ones meaning another.
+fn arch_tokens() -> (&'static [&'static str], &'static [&'static str]) {
+    const ARM: &[&str] = &["arm64", "aarch64", "arm"];
+    const X64: &[&str] = &["x64", "x86", "amd64", "intel"];
+    if arch_label() == "arm64" {
+        (ARM, X64)
+    } else {
+        (X64, ARM)
+    }
+}
+
+fn binary_zip_name() -> String {
+    let os = if cfg!(target_os = "windows") { "windows" } else { "macos" };
+    format!("screen-translate-{}-{}.zip", os, arch_label())
+}
+
 fn current_version() -> (u64, u64, u64) {
     let v = env!("CARGO_PKG_VERSION");
     parse_version(v).unwrap_or((0, 0, 0))
@@ -132,7 +249,11 @@ pub fn cleanup_old_binary() {
 }
 
 /// Check GitHub for a newer release. Returns update info if available.
-pub fn check_for_update() -> Result<Option<UpdateInfo>> {
+///
+/// A rate-limited response is returned as a [`RateLimited`] error so callers
+/// can downcast it and tell the user when to retry. `github_token` raises the
+/// limit from 60 to 5000 requests per hour.
+pub fn check_for_update(github_token: Option<&str>) -> Result<Option<UpdateInfo>> {
     let current = current_version();
     tracing::info!(
         "Checking for updates... (current v{}.{}.{})",
@@ -143,14 +264,42 @@ pub fn check_for_update() -> Result<Option<UpdateInfo>> {
 
     let client = build_client()?;
 
-    let resp: serde_json::Value = client
-        .get(GITHUB_API_URL)
-        .send()
-        .context("Failed to reach GitHub API")?
-        .error_for_status()
-        .context("GitHub API returned error")?
-        .json()
-        .context("Failed to parse release JSON")?;
+    let mut req = client.get(GITHUB_API_URL);
+    if let Some(token) = github_token.filter(|t| !t.is_empty()) {
+        req = req.bearer_auth(token);
+    }
+    let resp = req.send().context("Failed to reach GitHub API")?;
+
+    let status = resp.status();
+    let header_u64 = |name: &str| {
+        resp.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+    };
+    if (status == reqwest::StatusCode::FORBIDDEN || status == reqwest::StatusCode::TOO_MANY_REQUESTS)
+        && header_u64("x-ratelimit-remaining") == Some(0)
+    {
+        return Err(RateLimited {
+            reset_at: header_u64("x-ratelimit-reset"),
+        }
+        .into());
+    }
+
+    let resp = resp.error_for_status().context("GitHub API returned error")?;
+
+    // Proxies and captive portals redirect to, or answer with, an HTML page
+    let is_json = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("json"));
+    let host = resp.url().host_str().unwrap_or_default().to_string();
+    let body = resp.text().context("Failed to read release response")?;
+    let Some(resp) = serde_json::from_str::<serde_json::Value>(&body).ok().filter(|_| is_json) else {
+        tracing::debug!("Unexpected update response from {}: {:.200}", host, body);
+        bail!("Unexpected response from {} (captive portal?)", host);
+    };
 
     let tag = resp["tag_name"]
         .as_str()
@@ -173,38 +322,36 @@ pub fn check_for_update() -> Result<Option<UpdateInfo>> {
 
     let version = tag.strip_prefix('v').unwrap_or(tag).to_string();
 
+    let zip = |strategy| {
+        find_asset(&resp, AssetKind::BinaryZip, &binary_zip_name(), &version)
+            .map(|(name, url)| (strategy, name, url))
+    };
+
     // Determine update strategy and find the right asset
-    let (strategy, asset_name) = if is_inno_setup_install() {
+    let (strategy, asset_name, download_url) = if is_inno_setup_install() {
         let installer_name = format!("ScreenTranslate-{}-setup.exe", version);
-        if find_asset_url(&resp, &installer_name).is_ok() {
-            (UpdateStrategy::InstallerSilent, installer_name)
-        } else {
-            tracing::info!("Installer asset not found, falling back to binary zip");
-            (
-                UpdateStrategy::BinarySwap,
-                "screen-translate-windows-x64.zip".to_string(),
-            )
+        match find_asset(&resp, AssetKind::Installer, &installer_name, &version) {
+            Ok((name, url)) => (UpdateStrategy::InstallerSilent, name, url),
+            Err(_) => {
+                tracing::info!("Installer asset not found, falling back to binary zip");
+                zip(UpdateStrategy::BinarySwap)?
+            }
         }
     } else if cfg!(target_os = "macos") && is_app_bundle() {
         #[cfg(target_os = "macos")]
         {
             if app_bundle_has_libretranslate() {
                 // Full bundle exists — just update the binary (fast, ~20MB)
-                (
-                    UpdateStrategy::DmgInstall,
-                    "screen-translate-macos-arm64.zip".to_string(),
-                )
+                zip(UpdateStrategy::DmgInstall)?
             } else {
                 // .app bundle but no LibreTranslate — need the full DMG
                 let dmg_name = format!("ScreenTranslate-{}.dmg", version);
-                if find_asset_url(&resp, &dmg_name).is_ok() {
-                    (UpdateStrategy::DmgInstall, dmg_name)
-                } else {
-                    tracing::info!("DMG asset not found, falling back to binary zip");
-                    (
-                        UpdateStrategy::DmgInstall,
-                        "screen-translate-macos-arm64.zip".to_string(),
-                    )
+                match find_asset(&resp, AssetKind::Dmg, &dmg_name, &version) {
+                    Ok((name, url)) => (UpdateStrategy::DmgInstall, name, url),
+                    Err(_) => {
+                        tracing::info!("DMG asset not found, falling back to binary zip");
+                        zip(UpdateStrategy::DmgInstall)?
+                    }
                 }
             }
         }
@@ -212,31 +359,24 @@ pub fn check_for_update() -> Result<Option<UpdateInfo>> {
         {
             unreachable!("is_app_bundle() returned true on non-macOS")
         }
-    } else if cfg!(target_os = "macos") {
-        // Standalone binary (not inside .app), use binary swap
-        (
-            UpdateStrategy::BinarySwap,
-            "screen-translate-macos-arm64.zip".to_string(),
-        )
     } else {
-        (
-            UpdateStrategy::BinarySwap,
-            "screen-translate-windows-x64.zip".to_string(),
-        )
+        // Standalone binary (macOS outside an .app, or Windows without the installer)
+        zip(UpdateStrategy::BinarySwap)?
     };
 
-    let download_url = find_asset_url(&resp, &asset_name)?;
-
+    tracing::info!("Update asset for {}: {}", arch_label(), asset_name);
     Ok(Some(UpdateInfo {
         version,
         download_url,
         asset_name,
+        arch: arch_label(),
         strategy,
     }))
 }
 
-/// Download and apply the update.
-pub fn perform_update(info: &UpdateInfo) -> Result<()> {
+/// Download and apply the update. The caller shuts the app down and then
+/// spawns the returned `Relaunch`.
+pub fn perform_update(info: &UpdateInfo) -> Result<Relaunch> {
     // Use a longer timeout for potentially large DMG downloads
     let client = reqwest::blocking::Client::builder()
         .user_agent(format!("screen-translate/{}", env!("CARGO_PKG_VERSION")))
@@ -261,12 +401,12 @@ pub fn perform_update(info: &UpdateInfo) -> Result<()> {
 
     match info.strategy {
         UpdateStrategy::InstallerSilent => {
-            tracing::info!("Launching silent installer...");
-            Command::new(&download_path)
-                .args(["/VERYSILENT", "/SUPPRESSMSGBOXES", "/NORESTART"])
-                .spawn()
-                .context("Failed to launch installer")?;
-            std::process::exit(0);
+            tracing::info!("Installer ready, launching after shutdown");
+            let mut relaunch = Relaunch::new(&download_path);
+            relaunch
+                .command
+                .args(["/VERYSILENT", "/SUPPRESSMSGBOXES", "/NORESTART"]);
+            Ok(relaunch)
         }
         UpdateStrategy::DmgInstall => {
             #[cfg(target_os = "macos")]
@@ -277,10 +417,9 @@ pub fn perform_update(info: &UpdateInfo) -> Result<()> {
                 if info.asset_name.ends_with(".dmg") {
                     perform_dmg_update(&download_path, &app_root)?;
                 } else {
-                    perform_app_binary_swap(&download_path, &tmp_dir, &app_root)?;
+                    perform_app_binary_swap(&download_path, &tmp_dir, &app_root, info.arch)?;
                 }
-                // Unreachable: both functions call exit(0) on success
-                Ok(())
+                Ok(relaunch_app(&app_root))
             }
             #[cfg(not(target_os = "macos"))]
             {
@@ -296,6 +435,7 @@ pub fn perform_update(info: &UpdateInfo) -> Result<()> {
             if !extracted.exists() {
                 bail!("Extracted binary not found at {}", extracted.display());
             }
+            verify_binary_arch(&extracted, info.arch)?;
 
             let current_exe =
                 std::env::current_exe().context("Cannot determine current exe path")?;
@@ -317,18 +457,21 @@ pub fn perform_update(info: &UpdateInfo) -> Result<()> {
                 .context("Failed to set executable permission")?;
             }
 
-            tracing::info!("Update applied — relaunching...");
-
-            Command::new(&current_exe)
-                .spawn()
-                .context("Failed to relaunch after update")?;
-
-            std::process::exit(0);
+            tracing::info!("Update applied, relaunching after shutdown");
+            Ok(Relaunch::new(&current_exe))
         }
     }
 }
 
-/// Full DMG update: mount DMG, copy .app bundle, relaunch.
+/// Reopen the .app bundle through LaunchServices.
+#[cfg(target_os = "macos")]
+fn relaunch_app(app_root: &PathBuf) -> Relaunch {
+    let mut relaunch = Relaunch::new("open");
+    relaunch.command.arg("-a").arg(app_root);
+    relaunch
+}
+
+/// Full DMG update: mount DMG, copy .app bundle (relaunched by the caller).
 #[cfg(target_os = "macos")]
 fn perform_dmg_update(dmg_path: &PathBuf, app_root: &PathBuf) -> Result<()> {
     tracing::info!("Mounting DMG...");
@@ -411,22 +554,17 @@ fn perform_dmg_update(dmg_path: &PathBuf, app_root: &PathBuf) -> Result<()> {
         .arg(app_root)
         .status();
 
-    tracing::info!("DMG update applied — relaunching...");
-    Command::new("open")
-        .arg("-a")
-        .arg(app_root)
-        .spawn()
-        .context("Failed to relaunch .app after DMG update")?;
-
-    std::process::exit(0);
+    tracing::info!("DMG update applied");
+    Ok(())
 }
 
-/// Binary-only update for .app bundles: extract binary from zip, swap in place, relaunch via `open`.
+/// Binary-only update for .app bundles: extract binary from zip and swap it in place.
 #[cfg(target_os = "macos")]
 fn perform_app_binary_swap(
     zip_path: &PathBuf,
     tmp_dir: &PathBuf,
     app_root: &PathBuf,
+    arch: &str,
 ) -> Result<()> {
     tracing::info!("Extracting binary for .app bundle update...");
     extract_binary(zip_path, tmp_dir)?;
@@ -435,6 +573,7 @@ fn perform_app_binary_swap(
     if !extracted.exists() {
         bail!("Extracted binary not found at {}", extracted.display());
     }
+    verify_binary_arch(&extracted, arch)?;
 
     let current_exe = std::env::current_exe().context("Cannot determine current exe path")?;
     let old_exe = current_exe.with_extension("old");
@@ -458,30 +597,85 @@ fn perform_app_binary_swap(
         .arg(app_root)
         .status();
 
-    tracing::info!("Binary update applied — relaunching .app...");
-    Command::new("open")
-        .arg("-a")
-        .arg(app_root)
-        .spawn()
-        .context("Failed to relaunch .app after binary update")?;
-
-    std::process::exit(0);
+    tracing::info!("Binary update applied to .app bundle");
+    Ok(())
 }
 
-fn find_asset_url(release: &serde_json::Value, asset_name: &str) -> Result<String> {
+/// Find the release asset named `expected`, or failing that the best asset
+/// of the same kind for this OS and architecture. Returns (name, url).
+fn find_asset(
+    release: &serde_json::Value,
+    kind: AssetKind,
+    expected: &str,
+    version: &str,
+) -> Result<(String, String)> {
     let assets = release["assets"]
         .as_array()
         .context("Missing assets array")?;
+    let candidates = assets.iter().filter_map(|asset| {
+        Some((
+            asset["name"].as_str()?,
+            asset["browser_download_url"].as_str()?,
+        ))
+    });
+
+    if let Some((name, url)) = candidates.clone().find(|(name, _)| *name == expected) {
+        return Ok((name.to_string(), url.to_string()));
+    }
 
-    for asset in assets {
-        if asset["name"].as_str() == Some(asset_name) {
-            return asset["browser_download_url"]
-                .as_str()
-                .map(String::from)
-                .context("Missing browser_download_url");
+    // Highest score wins; on a tie the earlier asset is kept
+    let best = candidates
+        .filter_map(|(name, url)| Some((asset_score(name, kind, version)?, name, url)))
+        .fold(None, |best: Option<(u32, &str, &str)>, candidate| match best {
+            Some(b) if b.0 >= candidate.0 => Some(b),
+            _ => Some(candidate),
+        });
+    match best {
+        Some((_, name, url)) => {
+            tracing::info!("Asset '{}' not in release, using '{}' instead", expected, name);
+            Ok((name.to_string(), url.to_string()))
         }
+        None => bail!(
+            "No {} asset for this platform in release (expected '{}')",
+            kind.describe(),
+            expected
+        ),
+    }
+}
+
+/// How well `name` fits `kind` on this machine; `None` if it doesn't fit at all.
+fn asset_score(name: &str, kind: AssetKind, version: &str) -> Option<u32> {
+    let lower = name.to_lowercase();
+    let tokens: Vec<&str> = lower
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .collect();
+    let has = |words: &[&str]| tokens.iter().any(|t| words.contains(t));
+    let (this_os, other_os) = os_tokens();
+    let (this_arch, other_arch) = arch_tokens();
+
+    let fits = match kind {
+        AssetKind::Installer => lower.ends_with(".exe") && has(&["setup", "installer", "install"]),
+        AssetKind::Dmg => lower.ends_with(".dmg"),
+        // A bare zip could be for any OS, so it has to say which
+        AssetKind::BinaryZip => lower.ends_with(".zip") && has(this_os),
+    };
+    if !fits || has(other_os) {
+        return None;
+    }
+
+    let mut score = if has(this_arch) || has(&["universal"]) {
+        2
+    } else if has(other_arch) {
+        // A build for the other architecture may not run here at all
+        return None;
+    } else {
+        1
+    };
+    if lower.contains(version) {
+        score += 1;
     }
-    bail!("Asset '{}' not found in release", asset_name);
+    Some(score)
 }
 
 fn tempdir() -> Result<PathBuf> {
@@ -491,7 +685,118 @@ fn tempdir() -> Result<PathBuf> {
     Ok(dir)
 }
 
+/// Refuse to swap in a binary that can't run natively here, e.g. an arm64
+/// build on an Intel Mac when a release only ships one architecture.
+fn verify_binary_arch(path: &PathBuf, arch: &str) -> Result<()> {
+    use std::io::Read;
+
+    let mut header = Vec::with_capacity(4096);
+    std::fs::File::open(path)
+        .and_then(|f| f.take(4096).read_to_end(&mut header))
+        .context("Failed to read extracted binary")?;
+
+    let found = binary_archs(&header);
+    if found.is_empty() {
+        bail!("Extracted file at {} is not a recognizable executable", path.display());
+    }
+    if !found.contains(&arch) {
+        bail!(
+            "Downloaded binary is built for {}, but this machine is {}",
+            found.join("+"),
+            arch
+        );
+    }
+    Ok(())
+}
+
+/// Architectures in a Mach-O (thin or universal) or PE header, named as in
+/// release assets. Empty when the format isn't recognized.
+fn binary_archs(header: &[u8]) -> Vec<&'static str> {
+    let u32_at = |at: usize, big_endian: bool| {
+        let bytes: [u8; 4] = header.get(at..at + 4)?.try_into().ok()?;
+        Some(if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    };
+    let mach_cpu = |cpu: u32| match cpu {
+        0x0100_0007 => Some("x64"),
+        0x0100_000C => Some("arm64"),
+        _ => None,
+    };
+
+    match header.get(..4) {
+        // 64-bit Mach-O, little-endian
+        Some([0xCF, 0xFA, 0xED, 0xFE]) => u32_at(4, false).and_then(mach_cpu).into_iter().collect(),
+        // Universal binary: big-endian list of 20-byte fat_arch entries
+        Some([0xCA, 0xFE, 0xBA, 0xBE]) => {
+            let count = u32_at(4, true).unwrap_or(0) as usize;
+            (0..count.min(8))
+                .filter_map(|i| u32_at(8 + i * 20, true).and_then(mach_cpu))
+                .collect()
+        }
+        Some([b'M', b'Z', ..]) => {
+            let Some(pe) = u32_at(0x3C, false).map(|at| at as usize) else {
+                return Vec::new();
+            };
+            if header.get(pe..pe + 4) != Some(b"PE\0\0") {
+                return Vec::new();
+            }
+            let machine = header
+                .get(pe + 4..pe + 6)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]));
+            match machine {
+                Some(0x8664) => vec!["x64"],
+                Some(0xAA64) => vec!["arm64"],
+                _ => Vec::new(),
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Extract `binary_name()` from the release zip into `out_dir`, with the
+/// system's unzip/tar, or the built-in reader when that is missing or fails.
 fn extract_binary(zip_path: &PathBuf, out_dir: &PathBuf) -> Result<()> {
+    if let Err(e) = extract_binary_with_tool(zip_path, out_dir) {
+        tracing::warn!("{:#}, extracting with the built-in unzip", e);
+        extract_binary_builtin(zip_path, out_dir)?;
+    }
+    Ok(())
+}
+
+/// Pure-Rust fallback for `extract_binary`: the first file named
+/// `binary_name()` anywhere in the archive, like `unzip -j`.
+fn extract_binary_builtin(zip_path: &Path, out_dir: &Path) -> Result<()> {
+    let file = std::fs::File::open(zip_path).context("Failed to open update archive")?;
+    let mut archive = zip::ZipArchive::new(file).context("Failed to read update archive")?;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let is_binary = entry.is_file()
+            && Path::new(entry.name())
+                .file_name()
+                .is_some_and(|name| name == binary_name());
+        if !is_binary {
+            continue;
+        }
+        let dest = out_dir.join(binary_name());
+        let mut out = std::fs::File::create(&dest)
+            .with_context(|| format!("Failed to create {}", dest.display()))?;
+        std::io::copy(&mut entry, &mut out).context("Failed to extract update")?;
+        // Keep it executable, as unzip would
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = entry.unix_mode().unwrap_or(0o755);
+            std::fs::set_permissions(&dest, std::fs::Permissions::from_mode(mode))?;
+        }
+        return Ok(());
+    }
+    bail!("{} not found in {}", binary_name(), zip_path.display())
+}
+
+fn extract_binary_with_tool(zip_path: &PathBuf, out_dir: &PathBuf) -> Result<()> {
     #[cfg(target_os = "macos")]
     {
         let status = Command::new("unzip")
@@ -524,3 +829,65 @@ fn extract_binary(zip_path: &PathBuf, out_dir: &PathBuf) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// Fresh scratch directory under the system temp dir.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "screen-translate-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Write a zip at `path` holding `files` as (name, contents).
+    fn write_zip(path: &Path, files: &[(&str, &[u8])]) {
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        for (name, contents) in files {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(contents).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn builtin_extracts_nested_binary_flat() {
+        let dir = scratch_dir("extract");
+        let zip_path = dir.join("release.zip");
+        let nested = format!("screen-translate-v1.2.3/{}", binary_name());
+        write_zip(
+            &zip_path,
+            &[
+                ("screen-translate-v1.2.3/README.md", b"readme"),
+                (&nested, b"new binary"),
+            ],
+        );
+
+        extract_binary_builtin(&zip_path, &dir).unwrap();
+
+        let extracted = std::fs::read(dir.join(binary_name())).unwrap();
+        assert_eq!(extracted, b"new binary");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn builtin_reports_missing_binary() {
+        let dir = scratch_dir("extract-missing");
+        let zip_path = dir.join("release.zip");
+        write_zip(&zip_path, &[("README.md", b"readme")]);
+
+        let err = extract_binary_builtin(&zip_path, &dir).unwrap_err();
+
+        assert!(err.to_string().contains("not found"), "{}", err);
+        assert!(!dir.join(binary_name()).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}